@@ -29,9 +29,19 @@ fn other_abs(x : f64) -> f64 {
     res
 }
 
-fn factorial(n : u64) -> () {
+fn factorial(n : u64) -> u128 {
+    // n! = 1 * 2 * ... * n, as an 'expression' again. u128 holds up to 34!, after that
+    // checked_mul gives None and we stop with a clear message instead of silently wrapping
+    let mut result : u128 = 1;
+    for k in 1..=n as u128 {
+        result = result.checked_mul(k).expect("factorial overflows u128 (n > 34)");
+    }
+    result
+}
+
+fn print_factorial(n : u64) -> () {
     // A function without return value is marked by (n : type) -> () and making sure there are no "expressions" without ;
-    println!("Reading the input '{}' for factorial", n);
+    println!("\n{}! = {}", n, factorial(n));
     // return or you can just add a 'return' like in Python
 }
 
@@ -55,5 +65,7 @@ fn main(){
     println!("\nAbsolute Value of {} is {}", val, other_abs(val));
 
     // Factorial
-    factorial(34);
+    print_factorial(5);
+    print_factorial(34);
+    assert_eq!(factorial(5), 120);
 }
\ No newline at end of file
@@ -41,10 +41,6 @@ fn do_sum(arr : &[i32]) -> i32 {
 
     // By doing (arr : &[i32]), the compiler will know the size of the pointer at compilation time, which is fixed, it's an address!
     // But the actual array can vary in length
-    let mut sum = 0;
-
-    for i in 0..arr.len() {
-        sum += arr[i];
-    }
-    sum
+    // Iterating over the slice directly (no indices), and letting .sum() add everything up
+    arr.iter().sum()
 }
\ No newline at end of file
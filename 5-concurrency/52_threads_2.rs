@@ -3,12 +3,14 @@ use std::thread;
 use std::iter::FromIterator;
 
 fn main(){
-    let num = Vec::from_iter(0..=1000);
+    let num = Vec::from_iter(0..=1001);
 
     let t = thread::spawn(move || {
         let len = num.len();
         let sum = num.iter().sum::<usize>();
-        sum / len
+        // sum / len would be an integer division, which truncates (500.5 -> 500)
+        // Convert both to f64 first to keep the fractional part
+        sum as f64 / len as f64
     });
 
     let average = t.join().unwrap();
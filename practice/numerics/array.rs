@@ -0,0 +1,282 @@
+// N-dimensional arrays, the Rust version of a (small) numpy.ndarray
+//
+// The data lives in one contiguous Vec in row-major (C) order, with a shape and the
+// strides (how far to jump in the Vec for +1 along each axis). Unlike a Vec<Vec<f64>>,
+// rows cannot have different lengths, and an empty array is just a shape with a 0 in it.
+// Shape problems are reported with ShapeError instead of a panic deep inside a loop.
+
+use crate::numerics::scalar::Scalar;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Array<T> {
+    data: Vec<T>,
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShapeError {
+    SizeMismatch { shape: Vec<usize>, len: usize },     // The shape does not hold that many elements
+    Jagged { row: usize, len: usize, expected: usize }, // Rows of different lengths
+    Incompatible { left: Vec<usize>, right: Vec<usize> },
+    AxisOutOfBounds { axis: usize, ndim: usize },
+    WrongNdim { expected: usize, shape: Vec<usize> },   // e.g. a matrix that isn't 2D
+    EmptyLane { axis: usize },                          // Nothing to take the min / max of
+    OutOfBounds { axis: usize, index: usize, len: usize },
+    Overlapping { axis: usize },                        // Views that would share elements
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShapeError::SizeMismatch { shape, len } => {
+                write!(f, "shape {:?} needs {} elements, got {}", shape, shape.iter().product::<usize>(), len)
+            }
+            ShapeError::Jagged { row, len, expected } => {
+                write!(f, "row {} has {} elements, expected {} like the first row", row, len, expected)
+            }
+            ShapeError::Incompatible { left, right } => write!(f, "shapes {:?} and {:?} are incompatible", left, right),
+            ShapeError::AxisOutOfBounds { axis, ndim } => {
+                write!(f, "axis {} is out of bounds for an array with {} dimensions", axis, ndim)
+            }
+            ShapeError::WrongNdim { expected, shape } => {
+                write!(f, "expected a {}D array, got shape {:?}", expected, shape)
+            }
+            ShapeError::EmptyLane { axis } => write!(f, "nothing to reduce along axis {} (empty or all NaN)", axis),
+            ShapeError::OutOfBounds { axis, index, len } => {
+                write!(f, "index {} is out of bounds for axis {} of length {}", index, axis, len)
+            }
+            ShapeError::Overlapping { axis } => {
+                write!(f, "splitting along axis {} would give two views sharing elements", axis)
+            }
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+pub fn row_major_strides(shape: &[usize]) -> Vec<usize> {
+    // The last axis moves fastest: strides of [2, 3, 4] are [12, 4, 1]
+    let mut strides = vec![1; shape.len()];
+    for k in (0..shape.len().saturating_sub(1)).rev() {
+        strides[k] = strides[k + 1] * shape[k + 1];
+    }
+    strides
+}
+
+impl<T> Array<T> {
+    pub fn from_shape_vec(shape: &[usize], data: Vec<T>) -> Result<Self, ShapeError> {
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(ShapeError::SizeMismatch { shape: shape.to_vec(), len: data.len() });
+        }
+        Ok(Array { data, shape: shape.to_vec(), strides: row_major_strides(shape) })
+    }
+
+    pub fn from_vec(data: Vec<T>) -> Self {
+        // 1D array
+        let shape = vec![data.len()];
+        Array { data, strides: vec![1], shape }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, ShapeError> {
+        // 2D array from the Vec<Vec<T>> layout used in the early scripts
+        // Every row must have the length of the first one, and no rows gives shape [0, 0]
+        let n_cols = rows.first().map_or(0, |row| row.len());
+        if let Some((row, r)) = rows.iter().enumerate().find(|(_, r)| r.len() != n_cols) {
+            return Err(ShapeError::Jagged { row, len: r.len(), expected: n_cols });
+        }
+        let n_rows = rows.len();
+        let data: Vec<T> = rows.into_iter().flatten().collect();
+        Array::from_shape_vec(&[n_rows, n_cols], data)
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    pub fn offset(&self, index: &[usize]) -> Option<usize> {
+        // Position in the data Vec of a multi-index, None if it is out of bounds
+        if index.len() != self.shape.len() {
+            return None;
+        }
+        let mut offset = 0;
+        for ((&i, &n), &s) in index.iter().zip(self.shape.iter()).zip(self.strides.iter()) {
+            if i >= n {
+                return None;
+            }
+            offset += i * s;
+        }
+        Some(offset)
+    }
+
+    pub fn get(&self, index: &[usize]) -> Option<&T> {
+        self.offset(index).map(|k| &self.data[k])
+    }
+
+    pub fn get_mut(&mut self, index: &[usize]) -> Option<&mut T> {
+        self.offset(index).map(move |k| &mut self.data[k])
+    }
+
+    pub fn unravel(&self, offset: usize) -> Vec<usize> {
+        // Multi-index of the element at position `offset` of the data Vec
+        let mut rest = offset;
+        self.strides
+            .iter()
+            .map(|&s| {
+                let i = rest / s;
+                rest %= s;
+                i
+            })
+            .collect()
+    }
+
+    pub fn reshape(self, shape: &[usize]) -> Result<Self, ShapeError> {
+        Array::from_shape_vec(shape, self.data)
+    }
+
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Array<U> {
+        Array { data: self.data.iter().map(f).collect(), shape: self.shape.clone(), strides: self.strides.clone() }
+    }
+
+    fn index_panic(&self, index: &[usize]) -> ! {
+        panic!("index {:?} is out of bounds for an array of shape {:?}", index, self.shape)
+    }
+}
+
+impl<T: Clone> Array<T> {
+    pub fn full(shape: &[usize], value: T) -> Self {
+        let n = shape.iter().product();
+        Array { data: vec![value; n], shape: shape.to_vec(), strides: row_major_strides(shape) }
+    }
+
+    pub fn to_rows(&self) -> Result<Vec<Vec<T>>, ShapeError> {
+        // Back to Vec<Vec<T>>, only for 2D arrays
+        if self.ndim() != 2 {
+            return Err(ShapeError::WrongNdim { expected: 2, shape: self.shape.clone() });
+        }
+        if self.shape[1] == 0 {
+            return Ok(vec![Vec::new(); self.shape[0]]);
+        }
+        Ok(self.data.chunks(self.shape[1]).map(|row| row.to_vec()).collect())
+    }
+}
+
+impl<T: Scalar> Array<T> {
+    pub fn zeros(shape: &[usize]) -> Self {
+        Array::full(shape, T::zero())
+    }
+
+    pub fn ones(shape: &[usize]) -> Self {
+        Array::full(shape, T::one())
+    }
+
+    pub fn zeros_like<U>(other: &Array<U>) -> Self {
+        Array::zeros(other.shape())
+    }
+
+    pub fn ones_like<U>(other: &Array<U>) -> Self {
+        Array::ones(other.shape())
+    }
+}
+
+// Indexing like numpy: a[i] for 1D, a[(i, j)] for 2D, a[(i, j, k)] for 3D, a[&[i, j, k, l][..]] for any
+impl<T> Index<&[usize]> for Array<T> {
+    type Output = T;
+
+    fn index(&self, index: &[usize]) -> &T {
+        match self.offset(index) {
+            Some(k) => &self.data[k],
+            None => self.index_panic(index),
+        }
+    }
+}
+
+impl<T> IndexMut<&[usize]> for Array<T> {
+    fn index_mut(&mut self, index: &[usize]) -> &mut T {
+        match self.offset(index) {
+            Some(k) => &mut self.data[k],
+            None => self.index_panic(index),
+        }
+    }
+}
+
+impl<T> Index<usize> for Array<T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        &self[&[i][..]]
+    }
+}
+
+impl<T> IndexMut<usize> for Array<T> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        &mut self[&[i][..]]
+    }
+}
+
+impl<T> Index<(usize, usize)> for Array<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self[&[i, j][..]]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Array<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        &mut self[&[i, j][..]]
+    }
+}
+
+impl<T> Index<(usize, usize, usize)> for Array<T> {
+    type Output = T;
+
+    fn index(&self, (i, j, k): (usize, usize, usize)) -> &T {
+        &self[&[i, j, k][..]]
+    }
+}
+
+impl<T> IndexMut<(usize, usize, usize)> for Array<T> {
+    fn index_mut(&mut self, (i, j, k): (usize, usize, usize)) -> &mut T {
+        &mut self[&[i, j, k][..]]
+    }
+}
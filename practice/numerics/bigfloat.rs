@@ -0,0 +1,100 @@
+// Exact binary floating point: value = mantissa * 2^exponent with a BigInt mantissa
+// Every f64 is one of these, and +, -, * never round, so sums of products of f64
+// inputs come out exact. Only the final to_f64() rounds (once, to nearest)
+
+use crate::numerics::bigint::BigInt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Clone, Debug)]
+pub struct BigFloat {
+    pub mantissa: BigInt,
+    pub exponent: i64,
+}
+
+impl BigFloat {
+    pub fn zero() -> Self {
+        BigFloat { mantissa: BigInt::zero(), exponent: 0 }
+    }
+
+    pub fn from_int(x: i64) -> Self {
+        BigFloat { mantissa: BigInt::from_i64(x), exponent: 0 }.normalised()
+    }
+
+    pub fn from_bigint(x: BigInt) -> Self {
+        BigFloat { mantissa: x, exponent: 0 }.normalised()
+    }
+
+    pub fn from_f64(x: f64) -> Self {
+        // Exact: pull the 52-bit fraction and the exponent out of the IEEE-754 bits
+        assert!(x.is_finite(), "Cannot represent {} exactly", x);
+        let bits = x.to_bits();
+        let negative = bits >> 63 == 1;
+        let biased = ((bits >> 52) & 0x7FF) as i64;
+        let fraction = bits & ((1u64 << 52) - 1);
+        let (m, e) = if biased == 0 {
+            (fraction, -1074)                       // Subnormal
+        } else {
+            (fraction | (1u64 << 52), biased - 1075)
+        };
+        let mantissa = BigInt::from_u64(m);
+        let mantissa = if negative { -mantissa } else { mantissa };
+        BigFloat { mantissa, exponent: e }.normalised()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa.to_f64_scaled(self.exponent)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    fn normalised(self) -> Self {
+        // Move the trailing zero bits of the mantissa into the exponent, keeps the numbers small
+        if self.mantissa.is_zero() {
+            return BigFloat::zero();
+        }
+        let tz = self.mantissa.trailing_zeros();
+        BigFloat { mantissa: self.mantissa.shr(tz), exponent: self.exponent + tz as i64 }
+    }
+
+    pub fn powi(&self, n: u32) -> BigFloat {
+        BigFloat { mantissa: self.mantissa.pow(n), exponent: self.exponent * n as i64 }
+    }
+}
+
+impl<'a> Add<&'a BigFloat> for &'a BigFloat {
+    type Output = BigFloat;
+
+    fn add(self, other: &BigFloat) -> BigFloat {
+        // Bring both to the smaller exponent, then it's an integer addition
+        let e = self.exponent.min(other.exponent);
+        let a = self.mantissa.shl((self.exponent - e) as u64);
+        let b = other.mantissa.shl((other.exponent - e) as u64);
+        BigFloat { mantissa: &a + &b, exponent: e }.normalised()
+    }
+}
+
+impl<'a> Sub<&'a BigFloat> for &'a BigFloat {
+    type Output = BigFloat;
+
+    fn sub(self, other: &BigFloat) -> BigFloat {
+        self + &(-other)
+    }
+}
+
+impl<'a> Mul<&'a BigFloat> for &'a BigFloat {
+    type Output = BigFloat;
+
+    fn mul(self, other: &BigFloat) -> BigFloat {
+        BigFloat { mantissa: &self.mantissa * &other.mantissa, exponent: self.exponent + other.exponent }.normalised()
+    }
+}
+
+impl Neg for &BigFloat {
+    type Output = BigFloat;
+
+    fn neg(self) -> BigFloat {
+        BigFloat { mantissa: -&self.mantissa, exponent: self.exponent }
+    }
+}
@@ -0,0 +1,374 @@
+// Arbitrary-size integers, just enough for exact reference calculations
+// The magnitude is stored little-endian in base 2^32 with no leading (high) zero digits,
+// so zero is an empty vector. Schoolbook algorithms everywhere: the numbers we deal with
+// have a few thousand bits at most
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>,
+}
+
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y {
+            return x.cmp(y);
+        }
+    }
+    Ordering::Equal
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut out = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &x) in long.iter().enumerate() {
+        let sum = x as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        out.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        out.push(carry as u32);
+    }
+    out
+}
+
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    // a - b, needs |a| >= |b|
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &x) in a.iter().enumerate() {
+        let mut diff = x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = if diff < 0 { 1 } else { 0 };
+        if diff < 0 {
+            diff += 1 << 32;
+        }
+        out.push(diff as u32);
+    }
+    trim(&mut out);
+    out
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as u64 * y as u64 + out[i + j] as u64 + carry;
+            out[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+    trim(&mut out);
+    out
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt { negative: false, digits: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        BigInt::from_u64(1)
+    }
+
+    pub fn from_u64(x: u64) -> Self {
+        let mut digits = vec![x as u32, (x >> 32) as u32];
+        trim(&mut digits);
+        BigInt { negative: false, digits }
+    }
+
+    pub fn from_i64(x: i64) -> Self {
+        let mut out = BigInt::from_u64(x.unsigned_abs());
+        out.negative = x < 0 && !out.digits.is_empty();
+        out
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, digits: self.digits.clone() }
+    }
+
+    pub fn bits(&self) -> u64 {
+        // Number of bits of the magnitude (0 for zero)
+        match self.digits.last() {
+            None => 0,
+            Some(&top) => 32 * (self.digits.len() as u64 - 1) + (32 - top.leading_zeros() as u64),
+        }
+    }
+
+    pub fn trailing_zeros(&self) -> u64 {
+        // Number of zero bits at the bottom of the magnitude (0 for zero)
+        let mut count = 0;
+        for &d in &self.digits {
+            if d == 0 {
+                count += 32;
+            } else {
+                return count + d.trailing_zeros() as u64;
+            }
+        }
+        0
+    }
+
+    fn bit(&self, k: u64) -> bool {
+        let digit = (k / 32) as usize;
+        digit < self.digits.len() && (self.digits[digit] >> (k % 32)) & 1 == 1
+    }
+
+    pub fn mul_small(&self, k: u32) -> BigInt {
+        let mut out = BigInt { negative: self.negative, digits: mul_mag(&self.digits, &[k]) };
+        out.negative &= !out.digits.is_empty();
+        out
+    }
+
+    pub fn div_small(&self, k: u32) -> (BigInt, u32) {
+        // Truncated division by a small number: (quotient, remainder of the magnitude)
+        assert!(k != 0, "Division by zero");
+        let mut digits = vec![0u32; self.digits.len()];
+        let mut rem = 0u64;
+        for i in (0..self.digits.len()).rev() {
+            let cur = (rem << 32) | self.digits[i] as u64;
+            digits[i] = (cur / k as u64) as u32;
+            rem = cur % k as u64;
+        }
+        trim(&mut digits);
+        let negative = self.negative && !digits.is_empty();
+        (BigInt { negative, digits }, rem as u32)
+    }
+
+    pub fn shl(&self, n: u64) -> BigInt {
+        if self.is_zero() {
+            return self.clone();
+        }
+        let (words, bits) = ((n / 32) as usize, (n % 32) as u32);
+        let mut digits = vec![0u32; words];
+        let mut carry = 0u32;
+        for &d in &self.digits {
+            if bits == 0 {
+                digits.push(d);
+            } else {
+                digits.push((d << bits) | carry);
+                carry = d >> (32 - bits);
+            }
+        }
+        if carry > 0 {
+            digits.push(carry);
+        }
+        BigInt { negative: self.negative, digits }
+    }
+
+    pub fn shr(&self, n: u64) -> BigInt {
+        // Shift of the magnitude (rounds towards zero)
+        let (words, bits) = ((n / 32) as usize, (n % 32) as u32);
+        if words >= self.digits.len() {
+            return BigInt::zero();
+        }
+        let src = &self.digits[words..];
+        let mut digits: Vec<u32> = (0..src.len())
+            .map(|i| {
+                let low = src[i] >> bits;
+                let high = if bits > 0 { src.get(i + 1).map_or(0, |&h| h << (32 - bits)) } else { 0 };
+                low | high
+            })
+            .collect();
+        trim(&mut digits);
+        let negative = self.negative && !digits.is_empty();
+        BigInt { negative, digits }
+    }
+
+    pub fn pow(&self, exp: u32) -> BigInt {
+        let mut result = BigInt::one();
+        let mut base = self.clone();
+        let mut e = exp;
+        while e > 0 {
+            if e & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            e >>= 1;
+        }
+        result
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        // Correctly rounded (to nearest, ties to even)
+        self.to_f64_scaled(0)
+    }
+
+    pub fn to_f64_scaled(&self, exponent: i64) -> f64 {
+        // self * 2^exponent, rounded once to the nearest f64 (as long as it is a normal number)
+        let n_bits = self.bits();
+        if n_bits == 0 {
+            return 0.0;
+        }
+        let sign = if self.negative { -1.0 } else { 1.0 };
+        if n_bits <= 53 {
+            let m = self.digits.iter().rev().fold(0u64, |acc, &d| (acc << 32) | d as u64);
+            return sign * scale_by_pow2(m as f64, exponent);
+        }
+        // Keep 53 bits, then round with the next bit and a sticky bit for everything below it
+        let drop = n_bits - 53;
+        let mut kept = self.abs().shr(drop).digits.iter().rev().fold(0u64, |acc, &d| (acc << 32) | d as u64);
+        let half = self.bit(drop - 1);
+        let sticky = drop >= 2 && self.trailing_zeros() < drop - 1;
+        if half && (sticky || kept & 1 == 1) {
+            kept += 1;
+        }
+        sign * scale_by_pow2(kept as f64, exponent + drop as i64)
+    }
+}
+
+fn scale_by_pow2(x: f64, e: i64) -> f64 {
+    // x * 2^e, in steps so that 2^e itself never overflows / underflows
+    let mut out = x;
+    let mut e = e;
+    while e > 1000 {
+        out *= 2f64.powi(1000);
+        e -= 1000;
+    }
+    while e < -1000 {
+        out *= 2f64.powi(-1000);
+        e += 1000;
+    }
+    out * 2f64.powi(e as i32)
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.digits, &other.digits),
+            (true, true) => cmp_mag(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Add<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt { negative: self.negative, digits: add_mag(&self.digits, &other.digits) };
+        }
+        // Different signs: subtract the smaller magnitude, keep the sign of the larger
+        match cmp_mag(&self.digits, &other.digits) {
+            Ordering::Equal => BigInt::zero(),
+            Ordering::Greater => BigInt { negative: self.negative, digits: sub_mag(&self.digits, &other.digits) },
+            Ordering::Less => BigInt { negative: other.negative, digits: sub_mag(&other.digits, &self.digits) },
+        }
+    }
+}
+
+impl<'a> Sub<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl<'a> Mul<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let digits = mul_mag(&self.digits, &other.digits);
+        let negative = (self.negative != other.negative) && !digits.is_empty();
+        BigInt { negative, digits }
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt { negative: !self.negative && !self.digits.is_empty(), digits: self.digits.clone() }
+    }
+}
+
+impl Add for BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: BigInt) -> BigInt {
+        &self + &other
+    }
+}
+
+impl Sub for BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: BigInt) -> BigInt {
+        &self - &other
+    }
+}
+
+impl Mul for BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: BigInt) -> BigInt {
+        &self * &other
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        -&self
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Peel off 9 decimal digits at a time
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut chunks = Vec::new();
+        let mut rest = self.abs();
+        while !rest.is_zero() {
+            let (q, r) = rest.div_small(1_000_000_000);
+            chunks.push(r);
+            rest = q;
+        }
+        let mut s = String::new();
+        if self.negative {
+            s.push('-');
+        }
+        s.push_str(&chunks.pop().unwrap().to_string());
+        for chunk in chunks.iter().rev() {
+            s.push_str(&format!("{:09}", chunk));
+        }
+        write!(f, "{}", s)
+    }
+}
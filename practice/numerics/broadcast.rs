@@ -0,0 +1,225 @@
+// Element-wise arithmetic with numpy broadcasting
+//
+// Shapes are compared from the last axis backwards, and two sizes are compatible if
+// they are equal or one of them is 1 (missing axes count as 1):
+//     [4, 3] + [3]    -> [4, 3]     (the row is added to every row)
+//     [4, 1] * [1, 3] -> [4, 3]     (outer product)
+//     [4, 3] + [4]    -> error      (3 vs 4)
+// The operators (&a + &b, a * 2.0, a += &b, ...) panic with the ShapeError message on
+// a mismatch, like indexing out of bounds does. try_add & co. return the error instead.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::scalar::Scalar;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+pub fn broadcast_shape(left: &[usize], right: &[usize]) -> Result<Vec<usize>, ShapeError> {
+    let ndim = left.len().max(right.len());
+    let mut shape = vec![0; ndim];
+    for k in 0..ndim {
+        // k counts from the last axis
+        let a = if k < left.len() { left[left.len() - 1 - k] } else { 1 };
+        let b = if k < right.len() { right[right.len() - 1 - k] } else { 1 };
+        shape[ndim - 1 - k] = if a == b || b == 1 {
+            a
+        } else if a == 1 {
+            b
+        } else {
+            return Err(ShapeError::Incompatible { left: left.to_vec(), right: right.to_vec() });
+        };
+    }
+    Ok(shape)
+}
+
+fn broadcast_offsets<T>(arr: &Array<T>, out_shape: &[usize]) -> Vec<usize> {
+    // Position in arr's data of every element of the broadcast result (row-major order).
+    // Stretched axes get a stride of 0 so they keep reading the same element
+    let skip = out_shape.len() - arr.ndim();
+    let strides: Vec<usize> = (0..out_shape.len())
+        .map(|k| if k < skip || arr.shape()[k - skip] == 1 { 0 } else { arr.strides()[k - skip] })
+        .collect();
+
+    let total: usize = out_shape.iter().product();
+    let mut offsets = Vec::with_capacity(total);
+    let mut index = vec![0; out_shape.len()];
+    let mut offset = 0;
+    for _ in 0..total {
+        offsets.push(offset);
+        // Odometer increment of the multi-index, last axis first
+        for k in (0..out_shape.len()).rev() {
+            index[k] += 1;
+            offset += strides[k];
+            if index[k] < out_shape[k] {
+                break;
+            }
+            offset -= strides[k] * index[k];
+            index[k] = 0;
+        }
+    }
+    offsets
+}
+
+impl<T: Copy> Array<T> {
+    pub fn zip_with<U: Copy, V, F: Fn(T, U) -> V>(&self, other: &Array<U>, f: F) -> Result<Array<V>, ShapeError> {
+        // f applied element by element after broadcasting both arrays to a common shape
+        if self.shape() == other.shape() {
+            let data = self.iter().zip(other.iter()).map(|(&a, &b)| f(a, b)).collect();
+            return Array::from_shape_vec(self.shape(), data);
+        }
+        let shape = broadcast_shape(self.shape(), other.shape())?;
+        let (a, b) = (self.as_slice(), other.as_slice());
+        let data = broadcast_offsets(self, &shape)
+            .into_iter()
+            .zip(broadcast_offsets(other, &shape))
+            .map(|(i, j)| f(a[i], b[j]))
+            .collect();
+        Array::from_shape_vec(&shape, data)
+    }
+
+    pub fn broadcast_to(&self, shape: &[usize]) -> Result<Array<T>, ShapeError> {
+        // A copy stretched to `shape`, which must be what broadcasting would give
+        if broadcast_shape(self.shape(), shape)? != shape {
+            return Err(ShapeError::Incompatible { left: self.shape().to_vec(), right: shape.to_vec() });
+        }
+        let data = self.as_slice();
+        let values = broadcast_offsets(self, shape).into_iter().map(|i| data[i]).collect();
+        Array::from_shape_vec(shape, values)
+    }
+
+    fn update_with<U: Copy, F: Fn(T, U) -> T>(&mut self, other: &Array<U>, f: F) -> Result<(), ShapeError> {
+        // In-place version of zip_with: other must broadcast to our own shape
+        let offsets = if self.shape() == other.shape() {
+            (0..other.len()).collect()
+        } else if broadcast_shape(self.shape(), other.shape())? == self.shape() {
+            broadcast_offsets(other, self.shape())
+        } else {
+            return Err(ShapeError::Incompatible { left: self.shape().to_vec(), right: other.shape().to_vec() });
+        };
+        let b = other.as_slice();
+        for (a, j) in self.iter_mut().zip(offsets) {
+            *a = f(*a, b[j]);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Scalar> Array<T> {
+    pub fn try_add(&self, other: &Array<T>) -> Result<Array<T>, ShapeError> {
+        self.zip_with(other, |a, b| a + b)
+    }
+
+    pub fn try_sub(&self, other: &Array<T>) -> Result<Array<T>, ShapeError> {
+        self.zip_with(other, |a, b| a - b)
+    }
+
+    pub fn try_mul(&self, other: &Array<T>) -> Result<Array<T>, ShapeError> {
+        self.zip_with(other, |a, b| a * b)
+    }
+
+    pub fn try_div(&self, other: &Array<T>) -> Result<Array<T>, ShapeError> {
+        self.zip_with(other, |a, b| a / b)
+    }
+}
+
+// The four operators share the same code, so a macro writes every impl:
+// array (op) array, by reference and by value, array (op) scalar, and the *Assign versions
+macro_rules! impl_array_op {
+    ($Op:ident, $op:ident, $OpAssign:ident, $op_assign:ident) => {
+        impl<'a, T: Scalar> $Op<&'a Array<T>> for &'a Array<T> {
+            type Output = Array<T>;
+
+            fn $op(self, other: &Array<T>) -> Array<T> {
+                self.zip_with(other, |a, b| a.$op(b)).unwrap_or_else(|e| panic!("{}", e))
+            }
+        }
+
+        impl<T: Scalar> $Op<Array<T>> for Array<T> {
+            type Output = Array<T>;
+
+            fn $op(self, other: Array<T>) -> Array<T> {
+                (&self).$op(&other)
+            }
+        }
+
+        impl<'a, T: Scalar> $Op<&'a Array<T>> for Array<T> {
+            type Output = Array<T>;
+
+            fn $op(self, other: &Array<T>) -> Array<T> {
+                (&self).$op(other)
+            }
+        }
+
+        impl<T: Scalar> $Op<T> for &Array<T> {
+            type Output = Array<T>;
+
+            fn $op(self, scalar: T) -> Array<T> {
+                self.map(|&a| a.$op(scalar))
+            }
+        }
+
+        impl<T: Scalar> $Op<T> for Array<T> {
+            type Output = Array<T>;
+
+            fn $op(mut self, scalar: T) -> Array<T> {
+                for a in self.iter_mut() {
+                    *a = a.$op(scalar);
+                }
+                self
+            }
+        }
+
+        impl<'a, T: Scalar> $OpAssign<&'a Array<T>> for Array<T> {
+            fn $op_assign(&mut self, other: &Array<T>) {
+                self.update_with(other, |a, b| a.$op(b)).unwrap_or_else(|e| panic!("{}", e))
+            }
+        }
+
+        impl<T: Scalar> $OpAssign<Array<T>> for Array<T> {
+            fn $op_assign(&mut self, other: Array<T>) {
+                self.$op_assign(&other)
+            }
+        }
+
+        impl<T: Scalar> $OpAssign<T> for Array<T> {
+            fn $op_assign(&mut self, scalar: T) {
+                for a in self.iter_mut() {
+                    *a = a.$op(scalar);
+                }
+            }
+        }
+    };
+}
+
+impl_array_op!(Add, add, AddAssign, add_assign);
+impl_array_op!(Sub, sub, SubAssign, sub_assign);
+impl_array_op!(Mul, mul, MulAssign, mul_assign);
+impl_array_op!(Div, div, DivAssign, div_assign);
+
+// scalar (op) array. The orphan rules do not allow `impl<T> Add<Array<T>> for T`,
+// so this one is written for each primitive
+macro_rules! impl_scalar_lhs {
+    ($($t:ty),*) => {
+        $(
+            impl Add<&Array<$t>> for $t {
+                type Output = Array<$t>;
+                fn add(self, arr: &Array<$t>) -> Array<$t> { arr.map(|&a| self + a) }
+            }
+
+            impl Sub<&Array<$t>> for $t {
+                type Output = Array<$t>;
+                fn sub(self, arr: &Array<$t>) -> Array<$t> { arr.map(|&a| self - a) }
+            }
+
+            impl Mul<&Array<$t>> for $t {
+                type Output = Array<$t>;
+                fn mul(self, arr: &Array<$t>) -> Array<$t> { arr.map(|&a| self * a) }
+            }
+
+            impl Div<&Array<$t>> for $t {
+                type Output = Array<$t>;
+                fn div(self, arr: &Array<$t>) -> Array<$t> { arr.map(|&a| self / a) }
+            }
+        )*
+    };
+}
+
+impl_scalar_lhs!(f64, f32, i32, i64, u32, u64, usize);
@@ -0,0 +1,318 @@
+// Complex numbers a + bi, for the FFT and for complex pupil fields A exp(i phi)
+//
+// Complex<T> is a Scalar itself, so Array<Complex<f64>> gets the element-wise operators
+// of broadcast.rs for free. The functions that need sin / exp / ... (abs, arg, exp, ln,
+// powers) are there when T is a Float. At the bottom are the Array helpers: building a
+// complex array from real ones, and taking the real part, imaginary part, modulus, phase
+// of one.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::scalar::{Float, Scalar};
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T: Scalar> Complex<T> {
+    pub fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+
+    pub fn i() -> Self {
+        Complex::new(T::zero(), T::one())
+    }
+
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, T::zero() - self.im)
+    }
+
+    pub fn norm_sqr(self) -> T {
+        // |z|^2, no square root needed
+        self.re * self.re + self.im * self.im
+    }
+
+    pub fn scale(self, k: T) -> Self {
+        Complex::new(self.re * k, self.im * k)
+    }
+
+    pub fn inv(self) -> Self {
+        Self::one() / self
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        // Square and multiply, negative n through 1 / z
+        let mut base = if n < 0 { self.inv() } else { self };
+        let mut e = n.unsigned_abs();
+        let mut acc = Self::one();
+        while e > 0 {
+            if e & 1 == 1 {
+                acc *= base;
+            }
+            base = base * base;
+            e >>= 1;
+        }
+        acc
+    }
+}
+
+impl<T: Float> Complex<T> {
+    pub fn from_polar(r: T, theta: T) -> Self {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    pub fn cis(theta: T) -> Self {
+        // cos(theta) + i sin(theta) = exp(i theta)
+        Complex::new(theta.cos(), theta.sin())
+    }
+
+    pub fn abs(self) -> T {
+        // hypot avoids the overflow of sqrt(re^2 + im^2) for huge parts
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg(self) -> T {
+        // Phase in (-pi, pi]
+        self.im.atan2(self.re)
+    }
+
+    pub fn to_polar(self) -> (T, T) {
+        (self.abs(), self.arg())
+    }
+
+    pub fn exp(self) -> Self {
+        // e^(a + bi) = e^a (cos b + i sin b)
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+
+    pub fn ln(self) -> Self {
+        // Principal branch: ln|z| + i arg(z), with the cut along the negative real axis
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    pub fn sqrt(self) -> Self {
+        // Principal square root, from the polar form
+        let (r, theta) = self.to_polar();
+        Complex::from_polar(r.sqrt(), theta / T::from_f64(2.0))
+    }
+
+    fn zero_pow(p: Self) -> Self {
+        // 0^p, where ln 0 can't be used: 1 for p = 0 like f64::powf, 0 for Re p > 0,
+        // infinity for Re p < 0, and NaN for a purely imaginary p (0^(i b) has no limit)
+        if p == Self::zero() {
+            Self::one()
+        } else if p.re > T::zero() {
+            Self::zero()
+        } else if p.re < T::zero() {
+            Complex::new(T::infinity(), T::zero())
+        } else {
+            Complex::new(T::nan(), T::nan())
+        }
+    }
+
+    pub fn powf(self, p: T) -> Self {
+        if self == Self::zero() {
+            return Self::zero_pow(Complex::from(p));
+        }
+        let (r, theta) = self.to_polar();
+        Complex::from_polar(r.powf(p), theta * p)
+    }
+
+    pub fn powc(self, p: Self) -> Self {
+        // z^p = exp(p ln z)
+        if self == Self::zero() {
+            return Self::zero_pow(p);
+        }
+        (p * self.ln()).exp()
+    }
+
+    pub fn is_nan(self) -> bool {
+        self.re.is_nan() || self.im.is_nan()
+    }
+}
+
+impl<T: Scalar> From<T> for Complex<T> {
+    fn from(re: T) -> Self {
+        Complex::new(re, T::zero())
+    }
+}
+
+impl<T: Float + fmt::Display> fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // 1.5-2i, with the precision applied to both parts: format!("{:.2}", z)
+        let sign = if self.im < T::zero() { "-" } else { "+" };
+        match f.precision() {
+            Some(p) => write!(f, "{:.*}{}{:.*}i", p, self.re, sign, p, self.im.abs()),
+            None => write!(f, "{}{}{}i", self.re, sign, self.im.abs()),
+        }
+    }
+}
+
+impl<T: Scalar> Add for Complex<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl<T: Scalar> Sub for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl<T: Scalar> Mul for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        // (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+        Complex::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+    }
+}
+
+impl<T: Scalar> Div for Complex<T> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        // Multiply top and bottom by the conjugate of the bottom
+        let d = other.norm_sqr();
+        let top = self * other.conj();
+        Complex::new(top.re / d, top.im / d)
+    }
+}
+
+impl<T: Scalar> Neg for Complex<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Complex::new(T::zero() - self.re, T::zero() - self.im)
+    }
+}
+
+// Complex (op) real: z * 2.0, z + 1.0, ...
+impl<T: Scalar> Add<T> for Complex<T> {
+    type Output = Self;
+
+    fn add(self, x: T) -> Self {
+        Complex::new(self.re + x, self.im)
+    }
+}
+
+impl<T: Scalar> Sub<T> for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, x: T) -> Self {
+        Complex::new(self.re - x, self.im)
+    }
+}
+
+impl<T: Scalar> Mul<T> for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, x: T) -> Self {
+        self.scale(x)
+    }
+}
+
+impl<T: Scalar> Div<T> for Complex<T> {
+    type Output = Self;
+
+    fn div(self, x: T) -> Self {
+        Complex::new(self.re / x, self.im / x)
+    }
+}
+
+// The *Assign operators, for both a complex and a real right-hand side
+macro_rules! impl_complex_assign {
+    ($OpAssign:ident, $op_assign:ident, $op:tt) => {
+        impl<T: Scalar> $OpAssign for Complex<T> {
+            fn $op_assign(&mut self, other: Self) {
+                *self = *self $op other;
+            }
+        }
+
+        impl<T: Scalar> $OpAssign<T> for Complex<T> {
+            fn $op_assign(&mut self, x: T) {
+                *self = *self $op x;
+            }
+        }
+    };
+}
+
+impl_complex_assign!(AddAssign, add_assign, +);
+impl_complex_assign!(SubAssign, sub_assign, -);
+impl_complex_assign!(MulAssign, mul_assign, *);
+impl_complex_assign!(DivAssign, div_assign, /);
+
+impl<T: Scalar> Scalar for Complex<T> {
+    fn zero() -> Self {
+        Complex::new(T::zero(), T::zero())
+    }
+
+    fn one() -> Self {
+        Complex::new(T::one(), T::zero())
+    }
+}
+
+// Arrays
+
+impl<T: Float> Array<T> {
+    pub fn to_complex(&self) -> Array<Complex<T>> {
+        self.map(|&x| Complex::from(x))
+    }
+
+    pub fn cis(&self) -> Array<Complex<T>> {
+        // exp(i phase) of every element, e.g. a phase map in radians
+        self.map(|&phase| Complex::cis(phase))
+    }
+
+    pub fn from_re_im(re: &Array<T>, im: &Array<T>) -> Result<Array<Complex<T>>, ShapeError> {
+        re.zip_with(im, Complex::new)
+    }
+
+    pub fn from_polar(r: &Array<T>, theta: &Array<T>) -> Result<Array<Complex<T>>, ShapeError> {
+        r.zip_with(theta, Complex::from_polar)
+    }
+}
+
+impl<T: Float> Array<Complex<T>> {
+    pub fn re(&self) -> Array<T> {
+        self.map(|z| z.re)
+    }
+
+    pub fn im(&self) -> Array<T> {
+        self.map(|z| z.im)
+    }
+
+    pub fn abs(&self) -> Array<T> {
+        self.map(|z| z.abs())
+    }
+
+    pub fn arg(&self) -> Array<T> {
+        self.map(|z| z.arg())
+    }
+
+    pub fn norm_sqr(&self) -> Array<T> {
+        // |z|^2, the intensity of a field
+        self.map(|z| z.norm_sqr())
+    }
+
+    pub fn conj(&self) -> Array<Complex<T>> {
+        self.map(|z| z.conj())
+    }
+
+    pub fn exp(&self) -> Array<Complex<T>> {
+        self.map(|z| z.exp())
+    }
+
+    pub fn scale_by(&self, factor: &Array<T>) -> Result<Array<Complex<T>>, ShapeError> {
+        // Complex times real, element-wise with broadcasting (an amplitude times a field)
+        self.zip_with(factor, |z, x| z.scale(x))
+    }
+}
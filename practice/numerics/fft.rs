@@ -0,0 +1,312 @@
+// Fast Fourier transforms without FFTW: numpy.fft's fft / ifft / fft2 / rfft / fftshift
+//
+// Sizes whose prime factors are all small go through a mixed-radix Cooley-Tukey
+// (iterative radix 2 for powers of two, recursive splits by 3, 5, 7 otherwise).
+// A size with a bigger prime factor (17, 1009, ...) goes through Bluestein's algorithm,
+// which rewrites the DFT as a convolution that a power-of-two FFT can do.
+// Same conventions as numpy: exp(-2 pi i j k / n) forwards, and the inverse divides by n.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::complex::Complex;
+use crate::numerics::scalar::Scalar;
+use std::f64::consts::PI;
+
+type C64 = Complex<f64>;
+
+// Largest factor the mixed-radix splits handle directly (each split costs n * factor)
+const MAX_RADIX: usize = 7;
+
+fn twiddle(k: usize, n: usize, inverse: bool) -> C64 {
+    // exp(-2 pi i k / n), or exp(+2 pi i k / n) for the inverse. Reducing k mod n first
+    // keeps the angle small, and so accurate, even for k up to n^2
+    let angle = 2.0 * PI * (k % n) as f64 / n as f64;
+    C64::cis(if inverse { angle } else { -angle })
+}
+
+fn smallest_factor(n: usize) -> usize {
+    (2..).take_while(|p| p * p <= n).find(|&p| n.is_multiple_of(p)).unwrap_or(n)
+}
+
+fn largest_prime_factor(mut n: usize) -> usize {
+    let mut largest = 1;
+    while n > 1 {
+        let p = smallest_factor(n);
+        largest = p;
+        n /= p;
+    }
+    largest
+}
+
+fn radix2(buf: &mut [C64], inverse: bool) {
+    // In-place iterative Cooley-Tukey, buf.len() must be a power of two (> 1)
+    let n = buf.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            buf.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let w: Vec<C64> = (0..half).map(|k| twiddle(k, len, inverse)).collect();
+        for chunk in buf.chunks_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(half);
+            for ((a, b), &wk) in lo.iter_mut().zip(hi.iter_mut()).zip(w.iter()) {
+                let t = *b * wk;
+                *b = *a - t;
+                *a += t;
+            }
+        }
+        len *= 2;
+    }
+}
+
+fn mixed_radix(x: &[C64], inverse: bool) -> Vec<C64> {
+    // n = p q with p the smallest factor: transform the p interleaved subsequences
+    // x[r], x[r + p], ... of length q, then X[k + q s] = sum_r w_n^(r (k + q s)) Y_r[k]
+    let n = x.len();
+    if n.is_power_of_two() {
+        let mut out = x.to_vec();
+        if n > 1 {
+            radix2(&mut out, inverse);
+        }
+        return out;
+    }
+    let p = smallest_factor(n);
+    let q = n / p;
+    let subs: Vec<Vec<C64>> = (0..p)
+        .map(|r| {
+            let sub: Vec<C64> = x.iter().skip(r).step_by(p).cloned().collect();
+            mixed_radix(&sub, inverse)
+        })
+        .collect();
+    let mut out = vec![C64::zero(); n];
+    for (idx, o) in out.iter_mut().enumerate() {
+        let k = idx % q;
+        *o = subs.iter().enumerate().fold(C64::zero(), |acc, (r, y)| acc + y[k] * twiddle(r * idx, n, inverse));
+    }
+    out
+}
+
+fn bluestein(x: &[C64], inverse: bool) -> Vec<C64> {
+    // With j k = (j^2 + k^2 - (k - j)^2) / 2, the DFT becomes
+    //     X[k] = w[k] sum_j (x[j] w[j]) conj(w[k - j]),    w[k] = exp(-pi i k^2 / n)
+    // a convolution, done with power-of-two FFTs of length m >= 2n - 1
+    let n = x.len();
+    let m = (2 * n - 1).next_power_of_two();
+    let chirp: Vec<C64> = (0..n)
+        .map(|k| {
+            let angle = PI * ((k * k) % (2 * n)) as f64 / n as f64;
+            C64::cis(if inverse { angle } else { -angle })
+        })
+        .collect();
+
+    let mut a = vec![C64::zero(); m];
+    for ((a, &xk), &wk) in a.iter_mut().zip(x).zip(chirp.iter()) {
+        *a = xk * wk;
+    }
+    let mut b = vec![C64::zero(); m];
+    b[0] = chirp[0].conj();
+    for k in 1..n {
+        b[k] = chirp[k].conj();
+        b[m - k] = chirp[k].conj();
+    }
+    radix2(&mut a, false);
+    radix2(&mut b, false);
+    for (a, b) in a.iter_mut().zip(b.iter()) {
+        *a *= *b;
+    }
+    radix2(&mut a, true);
+    a.iter().zip(chirp.iter()).map(|(&y, &wk)| (y * wk).scale(1.0 / m as f64)).collect()
+}
+
+pub fn transform(x: &[C64], inverse: bool) -> Vec<C64> {
+    // 1D DFT of a slice, any length. The inverse includes the 1/n
+    let n = x.len();
+    let mut out = if n <= 1 {
+        x.to_vec()
+    } else if largest_prime_factor(n) <= MAX_RADIX {
+        mixed_radix(x, inverse)
+    } else {
+        bluestein(x, inverse)
+    };
+    if inverse && n > 1 {
+        for v in out.iter_mut() {
+            *v = v.scale(1.0 / n as f64);
+        }
+    }
+    out
+}
+
+fn map_lanes<T: Copy, U: Scalar, F>(a: &Array<T>, axis: usize, out_len: usize, f: F) -> Result<Array<U>, ShapeError>
+where
+    F: Fn(&[T]) -> Vec<U>,
+{
+    // Replaces every lane along axis (see reduce.rs) by f(lane), which has out_len elements
+    let shape = a.shape();
+    if axis >= shape.len() {
+        return Err(ShapeError::AxisOutOfBounds { axis, ndim: shape.len() });
+    }
+    let outer: usize = shape[..axis].iter().product();
+    let n = shape[axis];
+    let inner: usize = shape[axis + 1..].iter().product();
+    let data = a.as_slice();
+
+    let mut out_shape = shape.to_vec();
+    out_shape[axis] = out_len;
+    let mut out = Array::zeros(&out_shape);
+    let out_data = out.as_mut_slice();
+    let mut lane = Vec::with_capacity(n);
+    for o in 0..outer {
+        for i in 0..inner {
+            lane.clear();
+            lane.extend((0..n).map(|k| data[(o * n + k) * inner + i]));
+            for (k, v) in f(&lane).into_iter().enumerate() {
+                out_data[(o * out_len + k) * inner + i] = v;
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn last_axis<T>(a: &Array<T>) -> Result<usize, ShapeError> {
+    a.ndim().checked_sub(1).ok_or(ShapeError::AxisOutOfBounds { axis: 0, ndim: 0 })
+}
+
+pub fn fft_axis(a: &Array<C64>, axis: usize, inverse: bool) -> Result<Array<C64>, ShapeError> {
+    let n = a.shape().get(axis).cloned().unwrap_or(0);
+    map_lanes(a, axis, n, |lane| transform(lane, inverse))
+}
+
+pub fn fft(a: &Array<C64>) -> Result<Array<C64>, ShapeError> {
+    // Along the last axis, like numpy
+    fft_axis(a, last_axis(a)?, false)
+}
+
+pub fn ifft(a: &Array<C64>) -> Result<Array<C64>, ShapeError> {
+    fft_axis(a, last_axis(a)?, true)
+}
+
+pub fn fft2(a: &Array<C64>) -> Result<Array<C64>, ShapeError> {
+    // Over the last two axes: every row, then every column
+    if a.ndim() < 2 {
+        return Err(ShapeError::AxisOutOfBounds { axis: 1, ndim: a.ndim() });
+    }
+    fft_axis(&fft_axis(a, a.ndim() - 1, false)?, a.ndim() - 2, false)
+}
+
+pub fn ifft2(a: &Array<C64>) -> Result<Array<C64>, ShapeError> {
+    if a.ndim() < 2 {
+        return Err(ShapeError::AxisOutOfBounds { axis: 1, ndim: a.ndim() });
+    }
+    fft_axis(&fft_axis(a, a.ndim() - 1, true)?, a.ndim() - 2, true)
+}
+
+fn real_transform(x: &[f64]) -> Vec<C64> {
+    // The n / 2 + 1 non-redundant terms of the DFT of real data (the rest are conjugates).
+    // For even n, pack the even / odd samples as one complex signal of half the length,
+    // transform that, then untangle: X[k] = E[k] + w_n^k O[k]
+    let n = x.len();
+    if n < 2 || !n.is_multiple_of(2) {
+        let full = transform(&x.iter().map(|&v| C64::from(v)).collect::<Vec<_>>(), false);
+        return full.into_iter().take(n / 2 + 1).collect();
+    }
+    let h = n / 2;
+    let z: Vec<C64> = x.chunks(2).map(|pair| C64::new(pair[0], pair[1])).collect();
+    let zf = transform(&z, false);
+    (0..=h)
+        .map(|k| {
+            let zk = zf[k % h];
+            let zc = zf[(h - k) % h].conj();
+            let even = (zk + zc).scale(0.5);
+            let odd = (zk - zc) * C64::new(0.0, -0.5);
+            even + twiddle(k, n, false) * odd
+        })
+        .collect()
+}
+
+pub fn rfft(a: &Array<f64>) -> Result<Array<C64>, ShapeError> {
+    // Real input along the last axis: n -> n / 2 + 1 complex values (numpy.fft.rfft)
+    let axis = last_axis(a)?;
+    map_lanes(a, axis, a.shape()[axis] / 2 + 1, real_transform)
+}
+
+pub fn irfft(a: &Array<C64>, n: usize) -> Result<Array<f64>, ShapeError> {
+    // Inverse of rfft back to n real values. n is needed because n and n + 1 have the same
+    // number of rfft terms. Missing terms count as 0, extra terms are ignored (like numpy)
+    let axis = last_axis(a)?;
+    map_lanes(a, axis, n, |lane| {
+        let term = |k: usize| lane.get(k).cloned().unwrap_or_else(C64::zero);
+        let full: Vec<C64> = (0..n).map(|k| if k <= n / 2 { term(k) } else { term(n - k).conj() }).collect();
+        transform(&full, true).iter().map(|z| z.re).collect()
+    })
+}
+
+fn roll_all<T: Clone>(a: &Array<T>, source: fn(usize, usize) -> usize) -> Array<T> {
+    // out[i, j, ...] = a[source(i, n0), source(j, n1), ...]
+    let mut out = a.clone();
+    let data = a.as_slice();
+    for (k, v) in out.as_mut_slice().iter_mut().enumerate() {
+        let index: Vec<usize> = a.unravel(k).iter().zip(a.shape()).map(|(&i, &n)| source(i, n)).collect();
+        *v = data[a.offset(&index).unwrap()].clone();
+    }
+    out
+}
+
+pub fn fftshift<T: Clone>(a: &Array<T>) -> Array<T> {
+    // Zero frequency to the centre (index n / 2) of every axis
+    roll_all(a, |i, n| (i + n - n / 2) % n)
+}
+
+pub fn ifftshift<T: Clone>(a: &Array<T>) -> Array<T> {
+    // Undoes fftshift, also for odd sizes where the two are not the same
+    roll_all(a, |i, n| (i + n / 2) % n)
+}
+
+pub fn fftfreq(n: usize, d: f64) -> Array<f64> {
+    // Frequencies of the fft terms for a sample spacing d: 0, 1, ..., -2, -1 over n d
+    let half = n.div_ceil(2);
+    Array::from_vec((0..n).map(|k| if k < half { k as f64 } else { k as f64 - n as f64 } / (d * n as f64)).collect())
+}
+
+pub fn rfftfreq(n: usize, d: f64) -> Array<f64> {
+    Array::from_vec((0..=n / 2).map(|k| k as f64 / (d * n as f64)).collect())
+}
+
+fn pad_at<T: Scalar>(a: &Array<T>, shape: &[usize], corner: &[usize]) -> Array<T> {
+    let mut out = Array::zeros(shape);
+    for (k, &v) in a.iter().enumerate() {
+        let index: Vec<usize> = a.unravel(k).iter().zip(corner).map(|(i, c)| i + c).collect();
+        out[&index[..]] = v;
+    }
+    out
+}
+
+pub fn next_fast_len(n: usize) -> usize {
+    // Smallest size >= n with no prime factor above MAX_RADIX, so it skips Bluestein.
+    // Padding to it before a transform (convolutions, ...) is nearly free
+    (n.max(1)..).find(|&m| largest_prime_factor(m) <= MAX_RADIX).unwrap()
+}
+
+fn check_pad<T>(a: &Array<T>, shape: &[usize]) -> Result<(), ShapeError> {
+    if shape.len() != a.ndim() || shape.iter().zip(a.shape()).any(|(new, old)| new < old) {
+        return Err(ShapeError::Incompatible { left: a.shape().to_vec(), right: shape.to_vec() });
+    }
+    Ok(())
+}
+
+pub fn zero_pad<T: Scalar>(a: &Array<T>, shape: &[usize]) -> Result<Array<T>, ShapeError> {
+    // a in the first corner of a bigger array of zeros (finer frequency sampling)
+    check_pad(a, shape)?;
+    Ok(pad_at(a, shape, &vec![0; shape.len()]))
+}
+
+pub fn zero_pad_centered<T: Scalar>(a: &Array<T>, shape: &[usize]) -> Result<Array<T>, ShapeError> {
+    // a in the middle of the zeros, with its centre element (n / 2) landing on the new
+    // centre: a pupil padded like this gives a PSF sampled more finely than Nyquist
+    check_pad(a, shape)?;
+    let corner: Vec<usize> = shape.iter().zip(a.shape()).map(|(new, old)| new / 2 - old / 2).collect();
+    Ok(pad_at(a, shape, &corner))
+}
@@ -0,0 +1,400 @@
+// Interpolation: 1D on scattered increasing x, and 2D on the pixel grid of an image
+//
+// Methods, from cheap to smooth:
+//     Nearest  the closest sample
+//     Linear   straight lines (bilinear in 2D)
+//     Cubic    Keys' cubic convolution, a = -1/2 (Catmull-Rom): local, passes through the
+//              samples, exact for quadratics
+//     Spline   cubic spline: natural spline in 1D, cubic B-spline in 2D (the prefiltered
+//              coefficients with mirror boundaries, like scipy.ndimage order 3)
+//
+// 2D positions are in pixel units, (y, x) = (row, column), so pixel (i, j) sits at exactly
+// (i, j) and every pixel covers +-0.5 around its centre. Outside the image gives NaN.
+// Masked maps store NaN outside the pupil. To keep that NaN from eating into the pupil,
+// the NaNs are first filled by extrapolating the valid values outwards (fill_nan), then
+// the filled map is interpolated, and the result is NaN only where the nearest pixel was
+// NaN. The edge of the pupil stays where it was instead of growing by the kernel size.
+
+use crate::numerics::array::{Array, ShapeError};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Nearest,
+    Linear,
+    Cubic,
+    Spline,
+}
+
+// 1D
+
+fn check_increasing(x: &[f64]) {
+    assert!(x.windows(2).all(|w| w[0] < w[1]), "interpolation needs strictly increasing x");
+}
+
+fn bracket(x: &[f64], t: f64) -> usize {
+    // Index k of the interval x[k] <= t <= x[k + 1]
+    x.partition_point(|&v| v <= t).clamp(1, x.len() - 1) - 1
+}
+
+#[derive(Clone, Debug)]
+pub struct CubicSpline {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    m: Vec<f64>, // second derivatives at the knots
+}
+
+impl CubicSpline {
+    pub fn new(x: &[f64], y: &[f64]) -> Result<Self, ShapeError> {
+        // Natural spline (zero curvature at both ends) through (x[k], y[k]). The second
+        // derivatives solve a tridiagonal system, done with the Thomas algorithm
+        if x.len() != y.len() || x.is_empty() {
+            return Err(ShapeError::Incompatible { left: vec![x.len()], right: vec![y.len()] });
+        }
+        check_increasing(x);
+        let n = x.len();
+        let mut m = vec![0.0; n];
+        if n > 2 {
+            let mut diag = vec![0.0; n];
+            let mut rhs = vec![0.0; n];
+            for k in 1..n - 1 {
+                let (h0, h1) = (x[k] - x[k - 1], x[k + 1] - x[k]);
+                diag[k] = 2.0 * (h0 + h1);
+                rhs[k] = 6.0 * ((y[k + 1] - y[k]) / h1 - (y[k] - y[k - 1]) / h0);
+            }
+            // Forward elimination of the sub-diagonal h0, then back substitution
+            for k in 2..n - 1 {
+                let factor = (x[k] - x[k - 1]) / diag[k - 1];
+                diag[k] -= factor * (x[k] - x[k - 1]);
+                rhs[k] -= factor * rhs[k - 1];
+            }
+            for k in (1..n - 1).rev() {
+                m[k] = (rhs[k] - (x[k + 1] - x[k]) * m[k + 1]) / diag[k];
+            }
+        }
+        Ok(CubicSpline { x: x.to_vec(), y: y.to_vec(), m })
+    }
+
+    pub fn eval(&self, t: f64) -> f64 {
+        // NaN outside [x_0, x_(n-1)]
+        let (x, y, m) = (&self.x, &self.y, &self.m);
+        if !(x[0]..=x[x.len() - 1]).contains(&t) {
+            return f64::NAN;
+        }
+        if x.len() == 1 {
+            return y[0];
+        }
+        let k = bracket(x, t);
+        let h = x[k + 1] - x[k];
+        let (a, b) = ((x[k + 1] - t) / h, (t - x[k]) / h);
+        a * y[k] + b * y[k + 1] + ((a * a * a - a) * m[k] + (b * b * b - b) * m[k + 1]) * h * h / 6.0
+    }
+
+    pub fn derivative(&self, t: f64) -> f64 {
+        let (x, y, m) = (&self.x, &self.y, &self.m);
+        if !(x[0]..=x[x.len() - 1]).contains(&t) {
+            return f64::NAN;
+        }
+        if x.len() == 1 {
+            return 0.0;
+        }
+        let k = bracket(x, t);
+        let h = x[k + 1] - x[k];
+        let (a, b) = ((x[k + 1] - t) / h, (t - x[k]) / h);
+        (y[k + 1] - y[k]) / h + ((1.0 - 3.0 * a * a) * m[k] + (3.0 * b * b - 1.0) * m[k + 1]) * h / 6.0
+    }
+}
+
+fn hermite(x: &[f64], y: &[f64], k: usize, t: f64) -> f64 {
+    // Cubic Hermite on [x_k, x_(k+1)] with finite-difference slopes; for equal spacing
+    // that's exactly Catmull-Rom, the 1D version of the Cubic kernel
+    let n = x.len();
+    let slope = |i: usize| {
+        let (lo, hi) = (i.saturating_sub(1), (i + 1).min(n - 1));
+        (y[hi] - y[lo]) / (x[hi] - x[lo])
+    };
+    let h = x[k + 1] - x[k];
+    let s = (t - x[k]) / h;
+    let (s2, s3) = (s * s, s * s * s);
+    (2.0 * s3 - 3.0 * s2 + 1.0) * y[k]
+        + (s3 - 2.0 * s2 + s) * h * slope(k)
+        + (-2.0 * s3 + 3.0 * s2) * y[k + 1]
+        + (s3 - s2) * h * slope(k + 1)
+}
+
+pub fn interp1d(x: &[f64], y: &[f64], xi: &[f64], method: Method) -> Result<Vec<f64>, ShapeError> {
+    // y(xi) from the samples (x, y), x strictly increasing. NaN samples are left out and
+    // points outside [x_0, x_(n-1)] give NaN (no extrapolation)
+    if x.len() != y.len() {
+        return Err(ShapeError::Incompatible { left: vec![x.len()], right: vec![y.len()] });
+    }
+    let (x, y): (Vec<f64>, Vec<f64>) = x.iter().zip(y.iter()).filter(|(_, v)| !v.is_nan()).unzip();
+    if x.is_empty() {
+        return Ok(vec![f64::NAN; xi.len()]);
+    }
+    check_increasing(&x);
+    if method == Method::Spline {
+        let spline = CubicSpline::new(&x, &y)?;
+        return Ok(xi.iter().map(|&t| spline.eval(t)).collect());
+    }
+    let n = x.len();
+    let out = xi
+        .iter()
+        .map(|&t| {
+            if !(x[0]..=x[n - 1]).contains(&t) {
+                return f64::NAN;
+            }
+            if n == 1 {
+                return y[0];
+            }
+            let k = bracket(&x, t);
+            match method {
+                Method::Nearest => {
+                    if t - x[k] <= x[k + 1] - t { y[k] } else { y[k + 1] }
+                }
+                Method::Linear => y[k] + (t - x[k]) / (x[k + 1] - x[k]) * (y[k + 1] - y[k]),
+                _ => hermite(&x, &y, k, t),
+            }
+        })
+        .collect();
+    Ok(out)
+}
+
+// 2D
+
+fn dims(img: &Array<f64>) -> Result<(usize, usize), ShapeError> {
+    match *img.shape() {
+        [rows, cols] => Ok((rows, cols)),
+        _ => Err(ShapeError::WrongNdim { expected: 2, shape: img.shape().to_vec() }),
+    }
+}
+
+pub fn fill_nan(img: &Array<f64>) -> Result<Array<f64>, ShapeError> {
+    // Grow the valid region one ring of pixels at a time: every NaN pixel next to valid
+    // ones (8-neighbours) takes their average. A smooth enough continuation for the
+    // interpolation kernels to see across the pupil edge. All-NaN images stay NaN
+    let (rows, cols) = dims(img)?;
+    let mut out = img.clone();
+    let mut pending: Vec<(usize, usize)> =
+        (0..rows).flat_map(|i| (0..cols).map(move |j| (i, j))).filter(|&(i, j)| img[(i, j)].is_nan()).collect();
+    while !pending.is_empty() {
+        let mut filled = Vec::new();
+        let mut still = Vec::new();
+        for &(i, j) in &pending {
+            let (mut sum, mut count) = (0.0, 0);
+            for ni in i.saturating_sub(1)..(i + 2).min(rows) {
+                for nj in j.saturating_sub(1)..(j + 2).min(cols) {
+                    let v = out[(ni, nj)];
+                    if !v.is_nan() {
+                        sum += v;
+                        count += 1;
+                    }
+                }
+            }
+            if count > 0 {
+                filled.push((i, j, sum / count as f64));
+            } else {
+                still.push((i, j));
+            }
+        }
+        if filled.is_empty() {
+            break;
+        }
+        // Written after the sweep so a ring only sees the previous rings
+        for (i, j, v) in filled {
+            out[(i, j)] = v;
+        }
+        pending = still;
+    }
+    Ok(out)
+}
+
+fn keys(d: f64) -> f64 {
+    // Cubic convolution kernel with a = -1/2
+    let t = d.abs();
+    if t <= 1.0 {
+        (1.5 * t - 2.5) * t * t + 1.0
+    } else if t < 2.0 {
+        ((-0.5 * t + 2.5) * t - 4.0) * t + 2.0
+    } else {
+        0.0
+    }
+}
+
+fn bspline3(d: f64) -> f64 {
+    // Cubic B-spline
+    let t = d.abs();
+    if t < 1.0 {
+        2.0 / 3.0 - t * t + 0.5 * t * t * t
+    } else if t < 2.0 {
+        (2.0 - t).powi(3) / 6.0
+    } else {
+        0.0
+    }
+}
+
+fn mirror(i: isize, n: usize) -> usize {
+    // Whole-sample symmetric extension: ... 2 1 | 0 1 2 ... n-1 | n-2 n-3 ...
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n as isize - 1);
+    let i = i.rem_euclid(period);
+    (if i < n as isize { i } else { period - i }) as usize
+}
+
+fn bspline_prefilter(line: &mut [f64]) {
+    // Turn samples into cubic B-spline coefficients, so that sum_k c_k B3(x - k) passes
+    // through the samples: a causal and an anti-causal recursive filter with the pole
+    // z = sqrt(3) - 2 (Unser's algorithm), mirror boundaries
+    let n = line.len();
+    if n < 2 {
+        return;
+    }
+    let z: f64 = 3f64.sqrt() - 2.0;
+    for c in line.iter_mut() {
+        *c *= (1.0 - z) * (1.0 - 1.0 / z);
+    }
+    // Causal initial value: the sum over the mirrored signal, truncated where z^k is negligible
+    let horizon = (f64::EPSILON.ln() / z.abs().ln()).ceil() as usize;
+    let mut sum = 0.0;
+    let mut zk = 1.0;
+    for k in 0..horizon {
+        sum += zk * line[mirror(k as isize, n)];
+        zk *= z;
+    }
+    line[0] = sum;
+    for k in 1..n {
+        line[k] += z * line[k - 1];
+    }
+    line[n - 1] = z / (z * z - 1.0) * (line[n - 1] + z * line[n - 2]);
+    for k in (0..n - 1).rev() {
+        line[k] = z * (line[k + 1] - line[k]);
+    }
+}
+
+// Kernel weight at a distance, and where an index past the border reads from
+type Kernel = fn(f64) -> f64;
+type Boundary = fn(isize, usize) -> usize;
+
+pub struct Interpolator2d {
+    method: Method,
+    rows: usize,
+    cols: usize,
+    values: Array<f64>, // filled image, or B-spline coefficients for Method::Spline
+    mask: Array<bool>,  // true where the original image was NaN
+}
+
+impl Interpolator2d {
+    pub fn new(img: &Array<f64>, method: Method) -> Result<Self, ShapeError> {
+        let (rows, cols) = dims(img)?;
+        let mask = img.map(|v| v.is_nan());
+        let mut values = fill_nan(img)?;
+        if method == Method::Spline {
+            for row in values.as_mut_slice().chunks_mut(cols.max(1)) {
+                bspline_prefilter(row);
+            }
+            let mut column = vec![0.0; rows];
+            for j in 0..cols {
+                for i in 0..rows {
+                    column[i] = values[(i, j)];
+                }
+                bspline_prefilter(&mut column);
+                for i in 0..rows {
+                    values[(i, j)] = column[i];
+                }
+            }
+        }
+        Ok(Interpolator2d { method, rows, cols, values, mask })
+    }
+
+    pub fn sample(&self, y: f64, x: f64) -> f64 {
+        // The interpolated value at row y, column x (fractional pixel coordinates)
+        let (rows, cols) = (self.rows as f64, self.cols as f64);
+        if self.rows == 0 || self.cols == 0 || !(-0.5..=rows - 0.5).contains(&y) || !(-0.5..=cols - 0.5).contains(&x) {
+            return f64::NAN;
+        }
+        let y = y.clamp(0.0, rows - 1.0);
+        let x = x.clamp(0.0, cols - 1.0);
+        let (ni, nj) = (y.round() as usize, x.round() as usize);
+        if self.mask[(ni, nj)] {
+            return f64::NAN;
+        }
+        let (kernel, radius, index): (Kernel, isize, Boundary) = match self.method {
+            Method::Nearest => return self.values[(ni, nj)],
+            Method::Linear => (|d: f64| (1.0 - d.abs()).max(0.0), 1, clamp_index),
+            Method::Cubic => (keys, 2, clamp_index),
+            Method::Spline => (bspline3, 2, mirror),
+        };
+        let (i0, j0) = (y.floor() as isize, x.floor() as isize);
+        let mut total = 0.0;
+        for di in 1 - radius..=radius {
+            let wy = kernel(y - (i0 + di) as f64);
+            if wy == 0.0 {
+                continue;
+            }
+            let i = index(i0 + di, self.rows);
+            let mut row = 0.0;
+            for dj in 1 - radius..=radius {
+                let wx = kernel(x - (j0 + dj) as f64);
+                if wx != 0.0 {
+                    row += wx * self.values[(i, index(j0 + dj, self.cols))];
+                }
+            }
+            total += wy * row;
+        }
+        total
+    }
+}
+
+fn clamp_index(i: isize, n: usize) -> usize {
+    i.clamp(0, n as isize - 1) as usize
+}
+
+pub fn interp2d(img: &Array<f64>, y: &[f64], x: &[f64], method: Method) -> Result<Vec<f64>, ShapeError> {
+    // The image at the points (y[k], x[k])
+    if y.len() != x.len() {
+        return Err(ShapeError::Incompatible { left: vec![y.len()], right: vec![x.len()] });
+    }
+    let interp = Interpolator2d::new(img, method)?;
+    Ok(y.iter().zip(x.iter()).map(|(&y, &x)| interp.sample(y, x)).collect())
+}
+
+pub fn resample(img: &Array<f64>, shape: &[usize], method: Method) -> Result<Array<f64>, ShapeError> {
+    // The same field of view on a grid of `shape` = [rows, cols] pixels. The pixel centres
+    // line up like those of PupilGrid / polar_grid at either resolution: new pixel i sits
+    // at old coordinate (i + 1/2) n_old / n_new - 1/2
+    let (rows, cols) = dims(img)?;
+    let [new_rows, new_cols] = *shape else {
+        return Err(ShapeError::WrongNdim { expected: 2, shape: shape.to_vec() });
+    };
+    let interp = Interpolator2d::new(img, method)?;
+    let scale_y = rows as f64 / new_rows as f64;
+    let scale_x = cols as f64 / new_cols as f64;
+    let mut out = Array::zeros(&[new_rows, new_cols]);
+    for i in 0..new_rows {
+        let y = (i as f64 + 0.5) * scale_y - 0.5;
+        for j in 0..new_cols {
+            out[(i, j)] = interp.sample(y, (j as f64 + 0.5) * scale_x - 0.5);
+        }
+    }
+    Ok(out)
+}
+
+pub fn rotate(img: &Array<f64>, angle: f64, method: Method) -> Result<Array<f64>, ShapeError> {
+    // Rotate the content by `angle` radians about the centre of the image, counterclockwise
+    // with x along the columns and y along the rows (the theta of polar_grid), keeping the
+    // same shape. Corners that come from outside the image are NaN
+    let (rows, cols) = dims(img)?;
+    let interp = Interpolator2d::new(img, method)?;
+    let (cy, cx) = ((rows as f64 - 1.0) / 2.0, (cols as f64 - 1.0) / 2.0);
+    let (sin, cos) = angle.sin_cos();
+    let mut out = Array::zeros(&[rows, cols]);
+    for i in 0..rows {
+        for j in 0..cols {
+            // Each output pixel pulls from where it was before the rotation
+            let (dy, dx) = (i as f64 - cy, j as f64 - cx);
+            out[(i, j)] = interp.sample(cy - sin * dx + cos * dy, cx + cos * dx + sin * dy);
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,662 @@
+// Dense linear algebra on 2D Array<f64> matrices: products, LU, QR, Cholesky,
+// eigenvalues and the SVD. Vectors are plain &[f64] / Vec<f64>.
+//
+// Products with the wrong shapes panic like the element-wise operators do (try_mat_mul
+// returns the error instead). Factorisations return a LinalgError when the matrix has
+// the wrong shape or does not have the property they need (singular, not positive
+// definite, ...).
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::complex::Complex;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinalgError {
+    Shape(ShapeError),                  // Not a matrix, or shapes that do not multiply
+    NotSquare { shape: Vec<usize> },
+    Underdetermined { shape: Vec<usize> }, // Fewer rows than columns for QR / least squares
+    Singular,                           // Zero pivot: no unique solution
+    NotPositiveDefinite,
+    NotSymmetric,
+    NoConvergence,                      // An iterative method ran out of iterations
+}
+
+impl fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinalgError::Shape(e) => write!(f, "{}", e),
+            LinalgError::NotSquare { shape } => write!(f, "matrix of shape {:?} is not square", shape),
+            LinalgError::Underdetermined { shape } => {
+                write!(f, "matrix of shape {:?} has fewer rows than columns", shape)
+            }
+            LinalgError::Singular => write!(f, "matrix is singular"),
+            LinalgError::NotPositiveDefinite => write!(f, "matrix is not positive definite"),
+            LinalgError::NotSymmetric => write!(f, "matrix is not symmetric"),
+            LinalgError::NoConvergence => write!(f, "iteration did not converge"),
+        }
+    }
+}
+
+impl Error for LinalgError {}
+
+impl From<ShapeError> for LinalgError {
+    fn from(e: ShapeError) -> Self {
+        LinalgError::Shape(e)
+    }
+}
+
+fn dims(a: &Array<f64>) -> Result<(usize, usize), LinalgError> {
+    // (rows, columns) of a 2D array
+    match *a.shape() {
+        [m, n] => Ok((m, n)),
+        _ => Err(ShapeError::WrongNdim { expected: 2, shape: a.shape().to_vec() }.into()),
+    }
+}
+
+fn square(a: &Array<f64>) -> Result<usize, LinalgError> {
+    match dims(a)? {
+        (m, n) if m == n => Ok(n),
+        _ => Err(LinalgError::NotSquare { shape: a.shape().to_vec() }),
+    }
+}
+
+fn max_abs(values: &[f64]) -> f64 {
+    values.iter().fold(0.0, |acc, &x| acc.max(x.abs()))
+}
+
+pub fn identity(n: usize) -> Array<f64> {
+    let mut eye = Array::zeros(&[n, n]);
+    for i in 0..n {
+        eye[(i, i)] = 1.0;
+    }
+    eye
+}
+
+pub fn transpose(a: &Array<f64>) -> Array<f64> {
+    a.t().to_owned()
+}
+
+pub fn try_mat_mul(a: &Array<f64>, b: &Array<f64>) -> Result<Array<f64>, LinalgError> {
+    let (m, k) = dims(a)?;
+    let (k2, n) = dims(b)?;
+    if k != k2 {
+        return Err(ShapeError::Incompatible { left: a.shape().to_vec(), right: b.shape().to_vec() }.into());
+    }
+    if m * n == 0 || k == 0 {
+        return Ok(Array::zeros(&[m, n]));
+    }
+    // Row i of the result is sum_p a[i][p] * (row p of b): runs along rows of both, cache friendly
+    let mut out = vec![0.0; m * n];
+    for (out_row, a_row) in out.chunks_mut(n).zip(a.as_slice().chunks(k)) {
+        for (&aip, b_row) in a_row.iter().zip(b.as_slice().chunks(n)) {
+            for (o, &bpj) in out_row.iter_mut().zip(b_row) {
+                *o += aip * bpj;
+            }
+        }
+    }
+    Ok(Array::from_shape_vec(&[m, n], out)?)
+}
+
+pub fn mat_mul(a: &Array<f64>, b: &Array<f64>) -> Array<f64> {
+    try_mat_mul(a, b).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn mat_vec(a: &Array<f64>, x: &[f64]) -> Vec<f64> {
+    let (m, n) = dims(a).unwrap_or_else(|e| panic!("{}", e));
+    assert!(x.len() == n, "matrix of shape {:?} cannot multiply a vector of length {}", a.shape(), x.len());
+    if n == 0 {
+        return vec![0.0; m];
+    }
+    a.as_slice().chunks(n).map(|row| row.iter().zip(x).map(|(aij, xj)| aij * xj).sum()).collect()
+}
+
+// LU with partial pivoting: P A = L U, L unit lower triangular, both stored in one matrix
+pub struct Lu {
+    lu: Array<f64>,
+    perm: Vec<usize>,   // Row i of P A is row perm[i] of A
+    sign: f64,          // Determinant of P
+}
+
+pub fn lu(a: &Array<f64>) -> Result<Lu, LinalgError> {
+    let n = square(a)?;
+    let tol = n as f64 * f64::EPSILON * max_abs(a.as_slice());
+    let mut lu = a.clone();
+    let d = lu.as_mut_slice();
+    let mut perm: Vec<usize> = (0..n).collect();
+    let mut sign = 1.0;
+
+    for k in 0..n {
+        // Largest pivot in column k, for stability
+        let p = (k..n).max_by(|&i, &j| d[i * n + k].abs().total_cmp(&d[j * n + k].abs())).unwrap();
+        if d[p * n + k].abs() <= tol {
+            return Err(LinalgError::Singular);
+        }
+        if p != k {
+            for j in 0..n {
+                d.swap(k * n + j, p * n + j);
+            }
+            perm.swap(k, p);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            let f = d[i * n + k] / d[k * n + k];
+            d[i * n + k] = f;
+            for j in k + 1..n {
+                d[i * n + j] -= f * d[k * n + j];
+            }
+        }
+    }
+    Ok(Lu { lu, perm, sign })
+}
+
+impl Lu {
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        // x with A x = b: forward substitution with L, then back substitution with U
+        let n = self.perm.len();
+        assert!(b.len() == n, "right-hand side of length {} for a {} x {} system", b.len(), n, n);
+        let d = self.lu.as_slice();
+        let mut x: Vec<f64> = self.perm.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            let dot: f64 = (0..i).map(|j| d[i * n + j] * x[j]).sum();
+            x[i] -= dot;
+        }
+        for i in (0..n).rev() {
+            let dot: f64 = (i + 1..n).map(|j| d[i * n + j] * x[j]).sum();
+            x[i] = (x[i] - dot) / d[i * n + i];
+        }
+        x
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let n = self.perm.len();
+        (0..n).fold(self.sign, |acc, i| acc * self.lu[(i, i)])
+    }
+
+    pub fn inverse(&self) -> Array<f64> {
+        // Solve for every column of the identity
+        let n = self.perm.len();
+        let mut inv = Array::zeros(&[n, n]);
+        for j in 0..n {
+            let mut e = vec![0.0; n];
+            e[j] = 1.0;
+            for (i, x) in self.solve(&e).into_iter().enumerate() {
+                inv[(i, j)] = x;
+            }
+        }
+        inv
+    }
+}
+
+pub fn solve(a: &Array<f64>, b: &[f64]) -> Result<Vec<f64>, LinalgError> {
+    Ok(lu(a)?.solve(b))
+}
+
+pub fn determinant(a: &Array<f64>) -> Result<f64, LinalgError> {
+    match lu(a) {
+        Ok(f) => Ok(f.determinant()),
+        Err(LinalgError::Singular) => Ok(0.0),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn inverse(a: &Array<f64>) -> Result<Array<f64>, LinalgError> {
+    Ok(lu(a)?.inverse())
+}
+
+// Householder QR of an m x n matrix with m >= n, in the thin form A = Q R:
+// Q is m x n with orthonormal columns, R is n x n upper triangular
+pub struct Qr {
+    pub q: Array<f64>,
+    pub r: Array<f64>,
+}
+
+fn reflect(v: &[f64], x: &mut [f64], stride: usize) {
+    // x <- (I - 2 v v^T / v^T v) x, for the x[0], x[stride], x[2 stride], ... of a column
+    let v2: f64 = v.iter().map(|a| a * a).sum();
+    let dot: f64 = v.iter().enumerate().map(|(i, a)| a * x[i * stride]).sum();
+    let f = 2.0 * dot / v2;
+    for (i, a) in v.iter().enumerate() {
+        x[i * stride] -= f * a;
+    }
+}
+
+pub fn qr(a: &Array<f64>) -> Result<Qr, LinalgError> {
+    let (m, n) = dims(a)?;
+    if m < n {
+        return Err(LinalgError::Underdetermined { shape: a.shape().to_vec() });
+    }
+    let mut r = a.as_slice().to_vec();
+    let mut reflectors: Vec<Vec<f64>> = Vec::with_capacity(n);
+    for k in 0..n {
+        // Reflect column k below the diagonal onto -sign(x0) |x| e0 (the sign avoids cancellation)
+        let mut v: Vec<f64> = (k..m).map(|i| r[i * n + k]).collect();
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            v[0] += v[0].signum() * norm;
+            for j in k..n {
+                reflect(&v, &mut r[k * n + j..], n);
+            }
+        }
+        reflectors.push(v);
+    }
+
+    // Q = H_0 H_1 ... H_{n-1} applied to the first n columns of the identity
+    let mut q = vec![0.0; m * n];
+    for i in 0..n {
+        q[i * n + i] = 1.0;
+    }
+    for (k, v) in reflectors.iter().enumerate().rev() {
+        if v.iter().any(|&x| x != 0.0) {
+            for j in 0..n {
+                reflect(v, &mut q[k * n + j..], n);
+            }
+        }
+    }
+
+    let r_upper: Vec<f64> = (0..n * n).map(|idx| if idx % n >= idx / n { r[idx] } else { 0.0 }).collect();
+    Ok(Qr { q: Array::from_shape_vec(&[m, n], q)?, r: Array::from_shape_vec(&[n, n], r_upper)? })
+}
+
+impl Qr {
+    fn back_substitute(&self, y: &[f64]) -> Result<Vec<f64>, LinalgError> {
+        // x with R x = y
+        let n = y.len();
+        let r = self.r.as_slice();
+        let tol = n as f64 * f64::EPSILON * (0..n).map(|i| r[i * n + i].abs()).fold(0.0, f64::max);
+        let mut x = vec![0.0; n];
+        for i in (0..n).rev() {
+            if r[i * n + i].abs() <= tol {
+                return Err(LinalgError::Singular);
+            }
+            let dot: f64 = (i + 1..n).map(|j| r[i * n + j] * x[j]).sum();
+            x[i] = (y[i] - dot) / r[i * n + i];
+        }
+        Ok(x)
+    }
+
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, LinalgError> {
+        // Least squares solution of A x = b: R x = Q^T b
+        let qt_b = mat_vec(&transpose(&self.q), b);
+        self.back_substitute(&qt_b)
+    }
+}
+
+pub fn least_squares(a: &Array<f64>, b: &[f64]) -> Result<Vec<f64>, LinalgError> {
+    qr(a)?.solve(b)
+}
+
+pub fn least_squares_matrix(a: &Array<f64>) -> Result<Array<f64>, LinalgError> {
+    // The n x m matrix R^-1 Q^T that maps data b to the least squares solution of A x = b.
+    // Same result as (A^T A)^-1 A^T, but without squaring the condition number
+    let (m, n) = dims(a)?;
+    let f = qr(a)?;
+    let mut out = Array::zeros(&[n, m]);
+    // Column j of Q^T is row j of Q
+    for (j, q_row) in f.q.rows().enumerate() {
+        let q_row: Vec<f64> = q_row.iter().cloned().collect();
+        for (i, x) in f.back_substitute(&q_row)?.into_iter().enumerate() {
+            out[(i, j)] = x;
+        }
+    }
+    Ok(out)
+}
+
+pub fn cholesky(a: &Array<f64>) -> Result<Array<f64>, LinalgError> {
+    // Lower-triangular L with A = L L^T, for symmetric positive definite A
+    let n = square(a)?;
+    let a = a.as_slice();
+    let mut l = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..=i {
+            let dot: f64 = (0..j).map(|k| l[i * n + k] * l[j * n + k]).sum();
+            if i == j {
+                let d = a[i * n + i] - dot;
+                if d <= 0.0 {
+                    return Err(LinalgError::NotPositiveDefinite);
+                }
+                l[i * n + j] = d.sqrt();
+            } else {
+                l[i * n + j] = (a[i * n + j] - dot) / l[j * n + j];
+            }
+        }
+    }
+    Ok(Array::from_shape_vec(&[n, n], l)?)
+}
+
+pub fn cholesky_solve(l: &Array<f64>, b: &[f64]) -> Vec<f64> {
+    // Solve (L L^T) x = b with the factor returned by cholesky()
+    let n = b.len();
+    assert!(l.shape() == [n, n], "Cholesky factor of shape {:?} for a vector of length {}", l.shape(), n);
+    let l = l.as_slice();
+    let mut y = vec![0.0; n];
+    for i in 0..n {
+        let dot: f64 = (0..i).map(|k| l[i * n + k] * y[k]).sum();
+        y[i] = (b[i] - dot) / l[i * n + i];
+    }
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let dot: f64 = (i + 1..n).map(|k| l[k * n + i] * x[k]).sum();
+        x[i] = (y[i] - dot) / l[i * n + i];
+    }
+    x
+}
+
+fn rotate_columns(data: &mut [f64], n_cols: usize, p: usize, q: usize, c: f64, s: f64) {
+    // (col p, col q) <- (c col p - s col q, s col p + c col q)
+    for row in data.chunks_mut(n_cols) {
+        let (x, y) = (row[p], row[q]);
+        row[p] = c * x - s * y;
+        row[q] = s * x + c * y;
+    }
+}
+
+pub fn eigh(a: &Array<f64>) -> Result<(Vec<f64>, Array<f64>), LinalgError> {
+    // Eigenvalues (ascending) and eigenvectors (the columns of V) of a symmetric matrix,
+    // A = V diag(w) V^T, with the cyclic Jacobi method: rotate away the off-diagonal
+    // elements one pair at a time until the matrix is diagonal
+    let n = square(a)?;
+    let scale = max_abs(a.as_slice());
+    let mut w = a.as_slice().to_vec();
+    for i in 0..n {
+        for j in 0..i {
+            if (w[i * n + j] - w[j * n + i]).abs() > 1e-10 * scale {
+                return Err(LinalgError::NotSymmetric);
+            }
+        }
+    }
+    let mut v = identity(n).into_vec();
+
+    for _sweep in 0..100 {
+        let off: f64 = (0..n).flat_map(|i| (0..i).map(move |j| (i, j))).map(|(i, j)| w[i * n + j].powi(2)).sum();
+        if off.sqrt() <= f64::EPSILON * scale {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = w[p * n + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                let theta = (w[q * n + q] - w[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                // W <- J^T W J: the columns, then the rows (the columns of the transpose)
+                rotate_columns(&mut w, n, p, q, c, s);
+                for k in 0..n {
+                    let (x, y) = (w[p * n + k], w[q * n + k]);
+                    w[p * n + k] = c * x - s * y;
+                    w[q * n + k] = s * x + c * y;
+                }
+                rotate_columns(&mut v, n, p, q, c, s);
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| w[i * n + i].total_cmp(&w[j * n + j]));
+    let values = order.iter().map(|&i| w[i * n + i]).collect();
+    let vectors = (0..n * n).map(|idx| v[(idx / n) * n + order[idx % n]]).collect();
+    Ok((values, Array::from_shape_vec(&[n, n], vectors)?))
+}
+
+fn balance(a: &mut Array<f64>, n: usize) {
+    // Scale rows and columns by powers of 2 (exact) until their norms are comparable.
+    // Same eigenvalues, but much smaller rounding errors for matrices like the companion
+    // matrix of a polynomial, where the entries span many orders of magnitude
+    // (Parlett & Reinsch 1969, the balancing step of EISPACK and LAPACK)
+    loop {
+        let mut done = true;
+        for i in 0..n {
+            let c: f64 = (0..n).filter(|&j| j != i).map(|j| a[(j, i)].abs()).sum();
+            let r: f64 = (0..n).filter(|&j| j != i).map(|j| a[(i, j)].abs()).sum();
+            if c == 0.0 || r == 0.0 {
+                continue;
+            }
+            let (mut c_scaled, mut f) = (c, 1.0);
+            while c_scaled < r / 2.0 {
+                f *= 2.0;
+                c_scaled *= 4.0;
+            }
+            while c_scaled > r * 2.0 {
+                f /= 2.0;
+                c_scaled /= 4.0;
+            }
+            if (c_scaled + r) / f < 0.95 * (c + r) {
+                done = false;
+                for j in 0..n {
+                    a[(i, j)] /= f;
+                    a[(j, i)] *= f;
+                }
+            }
+        }
+        if done {
+            break;
+        }
+    }
+}
+
+fn householder(x: &[f64]) -> Option<Vec<f64>> {
+    // v with (I - 2 v v^T / v^T v) x = -sign(x0) |x| e0, None if x is already 0
+    let norm = x.iter().map(|a| a * a).sum::<f64>().sqrt();
+    if norm == 0.0 {
+        return None;
+    }
+    let mut v = x.to_vec();
+    v[0] += v[0].signum() * norm;
+    Some(v)
+}
+
+fn hessenberg(h: &mut [f64], n: usize) {
+    // Zeros below the first subdiagonal with Householder reflections applied from both
+    // sides, P H P, so the eigenvalues stay (Golub & Van Loan, Algorithm 7.4.2)
+    for k in 0..n.saturating_sub(2) {
+        let x: Vec<f64> = (k + 1..n).map(|i| h[i * n + k]).collect();
+        let Some(v) = householder(&x) else { continue };
+        for j in k..n {
+            reflect(&v, &mut h[(k + 1) * n + j..], n);
+        }
+        for i in 0..n {
+            reflect(&v, &mut h[i * n + k + 1..], 1);
+        }
+        for i in k + 2..n {
+            h[i * n + k] = 0.0;
+        }
+    }
+}
+
+fn francis_step(h: &mut [f64], n: usize, lo: usize, hi: usize, s: f64, t: f64) {
+    // One implicit double-shift QR step on the unreduced block h[lo..hi, lo..hi], with the
+    // shifts the roots of x^2 - s x + t (Golub & Van Loan, Algorithm 7.5.1). The first
+    // column of (H - a)(H - b) starts a 3 x 3 bulge that reflections chase down the band
+    let at = |h: &[f64], i: usize, j: usize| h[i * n + j];
+    let mut x = at(h, lo, lo) * at(h, lo, lo) + at(h, lo, lo + 1) * at(h, lo + 1, lo) - s * at(h, lo, lo) + t;
+    let mut y = at(h, lo + 1, lo) * (at(h, lo, lo) + at(h, lo + 1, lo + 1) - s);
+    let mut z = at(h, lo + 1, lo) * at(h, lo + 2, lo + 1);
+    for k in lo..hi - 2 {
+        if let Some(v) = householder(&[x, y, z]) {
+            for j in k.max(lo + 1) - 1..hi {
+                reflect(&v, &mut h[k * n + j..], n);
+            }
+            for i in lo..hi.min(k + 4) {
+                reflect(&v, &mut h[i * n + k..], 1);
+            }
+            if k > lo {
+                h[(k + 1) * n + k - 1] = 0.0;
+                h[(k + 2) * n + k - 1] = 0.0;
+            }
+        }
+        x = at(h, k + 1, k);
+        y = at(h, k + 2, k);
+        if k + 3 < hi {
+            z = at(h, k + 3, k);
+        }
+    }
+    // The last 2 x 2 reflection pushes the bulge off the bottom
+    let k = hi - 2;
+    if let Some(v) = householder(&[x, y]) {
+        for j in k - 1..hi {
+            reflect(&v, &mut h[k * n + j..], n);
+        }
+        for i in lo..hi {
+            reflect(&v, &mut h[i * n + k..], 1);
+        }
+        h[(k + 1) * n + k - 1] = 0.0;
+    }
+}
+
+fn block_eigvals(a: f64, b: f64, c: f64, d: f64) -> [Complex<f64>; 2] {
+    // Eigenvalues of [[a, b], [c, d]]: d + p +- sqrt(p^2 + bc) with p = (a - d) / 2, the
+    // second real one from the product of the two so there is no cancellation
+    let p = 0.5 * (a - d);
+    let q = p * p + b * c;
+    if q >= 0.0 {
+        let z = p + q.sqrt().copysign(p);
+        let second = if z != 0.0 { d - b * c / z } else { d };
+        [Complex::new(d + z, 0.0), Complex::new(second, 0.0)]
+    } else {
+        [Complex::new(d + p, (-q).sqrt()), Complex::new(d + p, -(-q).sqrt())]
+    }
+}
+
+pub fn eigvals(a: &Array<f64>) -> Result<Vec<Complex<f64>>, LinalgError> {
+    // Eigenvalues of a general (non-symmetric) real matrix, complex in conjugate pairs,
+    // sorted by real then imaginary part. Balance, reduce to Hessenberg form, then Francis
+    // double-shift QR steps on the bottom unreduced block until its last subdiagonal
+    // entry is negligible, splitting off one real eigenvalue or a 2 x 2 block at a time
+    let n = square(a)?;
+    let mut balanced = a.clone();
+    balance(&mut balanced, n);
+    let mut h = balanced.as_slice().to_vec();
+    hessenberg(&mut h, n);
+    let norm = max_abs(&h);
+
+    let mut values = Vec::with_capacity(n);
+    let mut hi = n;
+    let mut its = 0;
+    while hi > 0 {
+        // The unreduced block lo..hi ends at the first negligible subdiagonal entry above hi
+        let mut lo = hi - 1;
+        while lo > 0 {
+            let mut scale = h[(lo - 1) * n + lo - 1].abs() + h[lo * n + lo].abs();
+            if scale == 0.0 {
+                scale = norm;
+            }
+            if h[lo * n + lo - 1].abs() <= f64::EPSILON * scale {
+                h[lo * n + lo - 1] = 0.0;
+                break;
+            }
+            lo -= 1;
+        }
+        if lo == hi - 1 {
+            values.push(Complex::new(h[lo * n + lo], 0.0));
+            hi -= 1;
+            its = 0;
+        } else if lo == hi - 2 {
+            let at = |i: usize, j: usize| h[(lo + i) * n + lo + j];
+            values.extend(block_eigvals(at(0, 0), at(0, 1), at(1, 0), at(1, 1)));
+            hi -= 2;
+            its = 0;
+        } else {
+            if its == 60 {
+                return Err(LinalgError::NoConvergence);
+            }
+            its += 1;
+            // Shifts: the eigenvalues of the trailing 2 x 2 block (sum s, product t), and
+            // every 10 iterations an ad hoc pair built from the last subdiagonal entries to
+            // get out of a cycle (the one LAPACK's dlahqr uses)
+            let m = hi - 2;
+            let (s, t) = if its % 10 == 0 {
+                let w = h[(m + 1) * n + m].abs() + h[m * n + m - 1].abs();
+                let a = 0.75 * w + h[(m + 1) * n + m + 1];
+                (2.0 * a, a * a + 0.4375 * w * w)
+            } else {
+                let (a, b, c, d) = (h[m * n + m], h[m * n + m + 1], h[(m + 1) * n + m], h[(m + 1) * n + m + 1]);
+                (a + d, a * d - b * c)
+            };
+            francis_step(&mut h, n, lo, hi, s, t);
+        }
+    }
+    values.sort_by(|u, v| u.re.total_cmp(&v.re).then(u.im.total_cmp(&v.im)));
+    Ok(values)
+}
+
+// Thin SVD A = U diag(s) V^T of an m x n matrix, k = min(m, n): U is m x k, s has k
+// values in decreasing order, V is n x k
+pub struct Svd {
+    pub u: Array<f64>,
+    pub s: Vec<f64>,
+    pub v: Array<f64>,
+}
+
+pub fn svd(a: &Array<f64>) -> Result<Svd, LinalgError> {
+    // One-sided Jacobi: rotate pairs of columns until they are all orthogonal,
+    // then the column norms are the singular values
+    let (m, n) = dims(a)?;
+    if m < n {
+        // Work on the transpose, which has more rows: A^T = V diag(s) U^T
+        let Svd { u, s, v } = svd(&transpose(a))?;
+        return Ok(Svd { u: v, s, v: u });
+    }
+    let mut u = a.as_slice().to_vec();
+    let mut v = identity(n).into_vec();
+
+    for _sweep in 0..60 {
+        let mut rotated = false;
+        for p in 0..n {
+            for q in p + 1..n {
+                let (mut alpha, mut beta, mut gamma) = (0.0, 0.0, 0.0);
+                for row in u.chunks(n) {
+                    alpha += row[p] * row[p];
+                    beta += row[q] * row[q];
+                    gamma += row[p] * row[q];
+                }
+                if gamma.abs() <= 1e-15 * (alpha * beta).sqrt() {
+                    continue;
+                }
+                rotated = true;
+                let zeta = (beta - alpha) / (2.0 * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                rotate_columns(&mut u, n, p, q, c, c * t);
+                rotate_columns(&mut v, n, p, q, c, c * t);
+            }
+        }
+        if !rotated {
+            break;
+        }
+    }
+
+    let norms: Vec<f64> = (0..n).map(|j| u.iter().skip(j).step_by(n.max(1)).map(|x| x * x).sum::<f64>().sqrt()).collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| norms[j].total_cmp(&norms[i]));
+    let s: Vec<f64> = order.iter().map(|&j| norms[j]).collect();
+    let u_sorted = (0..m * n)
+        .map(|idx| {
+            let j = order[idx % n];
+            if norms[j] > 0.0 { u[(idx / n) * n + j] / norms[j] } else { 0.0 }
+        })
+        .collect();
+    let v_sorted = (0..n * n).map(|idx| v[(idx / n) * n + order[idx % n]]).collect();
+    Ok(Svd { u: Array::from_shape_vec(&[m, n], u_sorted)?, s, v: Array::from_shape_vec(&[n, n], v_sorted)? })
+}
+
+pub fn pseudo_inverse(a: &Array<f64>, rcond: f64) -> Result<Array<f64>, LinalgError> {
+    // Moore-Penrose inverse V diag(1/s) U^T, dropping the singular values below rcond * max(s)
+    // (the modes the system can barely produce, which would need huge commands)
+    let Svd { u, s, v } = svd(a)?;
+    let s_max = s.first().cloned().unwrap_or(0.0);
+    let inv_s: Vec<f64> = s.iter().map(|&x| if x > rcond * s_max { 1.0 / x } else { 0.0 }).collect();
+
+    // (V diag(1/s)) U^T
+    let mut v_scaled = v;
+    if !inv_s.is_empty() {
+        for row in v_scaled.as_mut_slice().chunks_mut(inv_s.len()) {
+            for (x, is) in row.iter_mut().zip(inv_s.iter()) {
+                *x *= is;
+            }
+        }
+    }
+    Ok(mat_mul(&v_scaled, &transpose(&u)))
+}
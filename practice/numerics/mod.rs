@@ -0,0 +1,27 @@
+// Small numerics toolbox shared by the practice scripts
+// A script pulls it in with `mod numerics;` and then `use numerics::rng::Rng;`
+// (`#[macro_use] mod numerics;` to also get the s![] slicing macro)
+// Not every script uses every function, so silence the dead code warnings here
+#![allow(dead_code)]
+
+pub mod array;
+pub mod bigfloat;
+pub mod bigint;
+pub mod broadcast;
+pub mod complex;
+pub mod fft;
+pub mod filter;
+pub mod interp;
+pub mod linalg;
+pub mod optimize;
+pub mod polynomial;
+pub mod quadrature;
+pub mod ranges;
+pub mod reduce;
+pub mod rng;
+pub mod scalar;
+pub mod sparse;
+pub mod special;
+pub mod stats;
+#[macro_use]
+pub mod view;
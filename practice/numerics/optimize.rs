@@ -0,0 +1,230 @@
+// Nonlinear least squares: minimise 1/2 sum_i r_i(x)^2 with Levenberg-Marquardt
+//
+// Each step solves the damped normal equations (J^T J + mu D) dx = -J^T r, D the diagonal
+// of J^T J (Marquardt's scaling, so the parameters' units don't matter). A small mu is a
+// Gauss-Newton step, a big one a short gradient step; mu goes down when the step did what
+// the linear model predicted and up when it didn't (Nielsen's update).
+// The Jacobian J[i, j] = dr_i / dx_j comes from a closure, or from forward differences.
+// Bounds are kept by clamping every trial point to the box, so parameters like a radius or
+// a width never go negative while the fit looks for its way. A parameter stuck on a bound
+// is left out of the step until the gradient pulls it back inside.
+// Running out of iterations is not an error: the report says why the solver stopped, and
+// so does a starting point where the residuals aren't finite.
+
+use crate::numerics::array::Array;
+use crate::numerics::linalg;
+
+#[derive(Clone, Debug)]
+pub struct LmOptions {
+    pub max_iter: usize,
+    pub gtol: f64,      // stop when the largest gradient component is below this
+    pub xtol: f64,      // ... or the step is below xtol relative to x
+    pub ftol: f64,      // ... or an accepted step lowers the cost by less than ftol relative
+    pub bounds: Option<Vec<(f64, f64)>>, // (lower, upper) per parameter, +-INFINITY for none
+}
+
+impl Default for LmOptions {
+    fn default() -> Self {
+        LmOptions { max_iter: 200, gtol: 1e-10, xtol: 1e-12, ftol: 1e-14, bounds: None }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LmStatus {
+    SmallGradient,
+    SmallStep,
+    SmallCostChange,
+    MaxIterations,
+    NonFiniteStart, // the residuals at x0 have a NaN or infinity, nothing to descend from
+}
+
+#[derive(Clone, Debug)]
+pub struct LmReport {
+    pub x: Vec<f64>,
+    pub residuals: Vec<f64>,
+    pub cost: f64, // 1/2 sum r^2 at x
+    pub iterations: usize,
+    pub evaluations: usize, // calls to the residual function, finite differences included
+    pub status: LmStatus,
+}
+
+impl LmReport {
+    pub fn converged(&self) -> bool {
+        matches!(self.status, LmStatus::SmallGradient | LmStatus::SmallStep | LmStatus::SmallCostChange)
+    }
+
+    pub fn rms(&self) -> f64 {
+        // Root mean square residual, the typical misfit of one data point
+        (2.0 * self.cost / self.residuals.len() as f64).sqrt()
+    }
+}
+
+pub fn levenberg_marquardt<F>(residuals: F, x0: &[f64], options: &LmOptions) -> LmReport
+where
+    F: Fn(&[f64]) -> Vec<f64>,
+{
+    // Jacobian by forward differences, n extra evaluations per iteration
+    let jacobian = |x: &[f64], r: &[f64], evaluations: &mut usize| {
+        let mut j = Array::zeros(&[r.len(), x.len()]);
+        let mut xh = x.to_vec();
+        for k in 0..x.len() {
+            // sqrt(eps) relative step, taken backwards if forwards leaves the box. In a box
+            // narrower than that, as far as the roomier side allows; a parameter fixed by
+            // lower == upper gets a zero column without evaluating outside the box
+            let mut h = 1.5e-8 * x[k].abs().max(1.0);
+            if let Some(bounds) = &options.bounds {
+                let (up, down) = (bounds[k].1 - x[k], x[k] - bounds[k].0);
+                h = if up >= h {
+                    h
+                } else if down >= h {
+                    -h
+                } else if up >= down {
+                    up
+                } else {
+                    -down
+                };
+                if h == 0.0 {
+                    continue;
+                }
+            }
+            xh[k] = x[k] + h;
+            let rh = residuals(&xh);
+            *evaluations += 1;
+            for i in 0..r.len() {
+                j[(i, k)] = (rh[i] - r[i]) / h;
+            }
+            xh[k] = x[k];
+        }
+        j
+    };
+    solve(&residuals, jacobian, x0, options)
+}
+
+pub fn levenberg_marquardt_with_jacobian<F, J>(residuals: F, jacobian: J, x0: &[f64], options: &LmOptions) -> LmReport
+where
+    F: Fn(&[f64]) -> Vec<f64>,
+    J: Fn(&[f64]) -> Array<f64>,
+{
+    // jacobian(x) is the [residuals, parameters] matrix of dr_i / dx_j
+    solve(&residuals, |x: &[f64], _: &[f64], _: &mut usize| jacobian(x), x0, options)
+}
+
+fn clamp(x: &mut [f64], bounds: &Option<Vec<(f64, f64)>>) {
+    if let Some(bounds) = bounds {
+        for (v, &(lo, hi)) in x.iter_mut().zip(bounds.iter()) {
+            *v = v.clamp(lo, hi);
+        }
+    }
+}
+
+fn half_sum_squares(r: &[f64]) -> f64 {
+    0.5 * r.iter().map(|v| v * v).sum::<f64>()
+}
+
+fn solve<F, J>(residuals: &F, jacobian: J, x0: &[f64], options: &LmOptions) -> LmReport
+where
+    F: Fn(&[f64]) -> Vec<f64>,
+    J: Fn(&[f64], &[f64], &mut usize) -> Array<f64>,
+{
+    let n = x0.len();
+    if let Some(bounds) = &options.bounds {
+        assert_eq!(bounds.len(), n, "one (lower, upper) pair per parameter");
+        assert!(bounds.iter().all(|&(lo, hi)| lo <= hi), "lower bound above the upper one");
+    }
+    let mut x = x0.to_vec();
+    clamp(&mut x, &options.bounds);
+    let mut r = residuals(&x);
+    let mut evaluations = 1;
+    let mut cost = half_sum_squares(&r);
+    if !cost.is_finite() {
+        return LmReport { x, residuals: r, cost, iterations: 0, evaluations, status: LmStatus::NonFiniteStart };
+    }
+    let (mut mu, mut nu) = (0.0, 2.0);
+    let mut status = LmStatus::MaxIterations;
+    let mut iterations = 0;
+    let mut fresh = true; // J needs recomputing (x moved)
+    let (mut a, mut g) = (Array::zeros(&[n, n]), vec![0.0; n]);
+    let mut free = vec![true; n];
+    while iterations < options.max_iter {
+        iterations += 1;
+        if fresh {
+            let j = jacobian(&x, &r, &mut evaluations);
+            assert_eq!(j.shape(), [r.len(), n], "Jacobian shape should be [residuals, parameters]");
+            let jt = linalg::transpose(&j);
+            a = linalg::mat_mul(&jt, &j);
+            g = linalg::mat_vec(&jt, &r);
+            // Parameters sitting on a bound that the gradient pushes outwards are held there
+            // for this step (active set); the others are free to move
+            free = (0..n)
+                .map(|k| match &options.bounds {
+                    Some(b) => !((x[k] <= b[k].0 && g[k] > 0.0) || (x[k] >= b[k].1 && g[k] < 0.0)),
+                    None => true,
+                })
+                .collect();
+            if (0..n).filter(|&k| free[k]).all(|k| g[k].abs() <= options.gtol) {
+                status = LmStatus::SmallGradient;
+                break;
+            }
+            if mu == 0.0 {
+                let largest = (0..n).map(|k| a[(k, k)]).fold(0.0, f64::max);
+                mu = 1e-3 * largest.max(f64::MIN_POSITIVE);
+            }
+            fresh = false;
+        }
+        // Damped step, scaled by the diagonal (with a floor for parameters r doesn't see).
+        // A held parameter gets the equation dx_k = 0
+        let mut damped = a.clone();
+        let mut rhs: Vec<f64> = g.iter().map(|v| -v).collect();
+        for k in 0..n {
+            if free[k] {
+                damped[(k, k)] += mu * a[(k, k)].max(1e-12);
+            } else {
+                for i in 0..n {
+                    damped[(k, i)] = 0.0;
+                    damped[(i, k)] = 0.0;
+                }
+                damped[(k, k)] = 1.0;
+                rhs[k] = 0.0;
+            }
+        }
+        let Ok(l) = linalg::cholesky(&damped) else {
+            mu *= nu;
+            nu *= 2.0;
+            continue;
+        };
+        let dx = linalg::cholesky_solve(&l, &rhs);
+        let mut x_new: Vec<f64> = x.iter().zip(dx.iter()).map(|(a, b)| a + b).collect();
+        clamp(&mut x_new, &options.bounds);
+        let step: Vec<f64> = x_new.iter().zip(x.iter()).map(|(a, b)| a - b).collect();
+        let step_norm = step.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let x_norm = x.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if step_norm <= options.xtol * (x_norm + options.xtol) {
+            status = LmStatus::SmallStep;
+            break;
+        }
+        let r_new = residuals(&x_new);
+        evaluations += 1;
+        let cost_new = half_sum_squares(&r_new);
+        // Decrease predicted by the linear model: -g.d - 1/2 d^T A d
+        let a_step = linalg::mat_vec(&a, &step);
+        let predicted = -(0..n).map(|k| step[k] * (g[k] + 0.5 * a_step[k])).sum::<f64>();
+        let ratio = (cost - cost_new) / predicted;
+        if cost_new.is_finite() && predicted > 0.0 && ratio > 0.0 {
+            let relative_change = (cost - cost_new) / cost;
+            x = x_new;
+            r = r_new;
+            cost = cost_new;
+            fresh = true;
+            mu *= (1.0 - (2.0 * ratio - 1.0).powi(3)).max(1.0 / 3.0);
+            nu = 2.0;
+            if relative_change <= options.ftol {
+                status = LmStatus::SmallCostChange;
+                break;
+            }
+        } else {
+            mu *= nu;
+            nu *= 2.0;
+        }
+    }
+    LmReport { x, residuals: r, cost, iterations, evaluations, status }
+}
@@ -0,0 +1,378 @@
+// Polynomials in one variable, p(x) = c0 + c1 x + c2 x^2 + ...
+//
+// The coefficients are stored in ascending powers (coef[k] multiplies x^k), without
+// trailing zeros, so the zero polynomial has no coefficients at all and degree() = None.
+// Evaluation is Horner's rule: p(x) = c0 + x (c1 + x (c2 + ...)), n multiplications and
+// no powi. At the bottom are the classical orthogonal families (Legendre, Chebyshev,
+// Jacobi), both as Polynomial values and as direct three-term recurrences for evaluating
+// them at a point (the recurrence is the stable way for high degrees, the expanded
+// coefficients grow like 2^n and cancel each other).
+
+use crate::numerics::array::Array;
+use crate::numerics::complex::Complex;
+use crate::numerics::linalg::{self, LinalgError};
+use crate::numerics::scalar::{Float, Scalar};
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial<T> {
+    coef: Vec<T>,
+}
+
+impl<T: Scalar> Polynomial<T> {
+    pub fn new(mut coef: Vec<T>) -> Self {
+        // Coefficients in ascending powers: new(vec![1, 0, 3]) is 1 + 3x^2
+        while coef.last() == Some(&T::zero()) {
+            coef.pop();
+        }
+        Polynomial { coef }
+    }
+
+    pub fn zero() -> Self {
+        Polynomial { coef: Vec::new() }
+    }
+
+    pub fn constant(c: T) -> Self {
+        Polynomial::new(vec![c])
+    }
+
+    pub fn monomial(k: usize) -> Self {
+        // x^k
+        let mut coef = vec![T::zero(); k + 1];
+        coef[k] = T::one();
+        Polynomial { coef }
+    }
+
+    pub fn x() -> Self {
+        Polynomial::monomial(1)
+    }
+
+    pub fn from_roots(roots: &[T]) -> Self {
+        // (x - r1)(x - r2)..., monic
+        roots.iter().fold(Polynomial::constant(T::one()), |p, &r| &p * &Polynomial::new(vec![T::zero() - r, T::one()]))
+    }
+
+    pub fn coefficients(&self) -> &[T] {
+        &self.coef
+    }
+
+    pub fn degree(&self) -> Option<usize> {
+        // None for the zero polynomial (its degree is -infinity by convention)
+        self.coef.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coef.is_empty()
+    }
+
+    pub fn leading(&self) -> T {
+        self.coef.last().copied().unwrap_or_else(T::zero)
+    }
+
+    pub fn eval(&self, x: T) -> T {
+        // Horner's rule, from the highest power down
+        self.coef.iter().rev().fold(T::zero(), |acc, &c| acc * x + c)
+    }
+
+    pub fn eval_slice(&self, x: &[T]) -> Vec<T> {
+        x.iter().map(|&x| self.eval(x)).collect()
+    }
+
+    pub fn eval_array(&self, x: &Array<T>) -> Array<T> {
+        x.map(|&x| self.eval(x))
+    }
+
+    pub fn scale(&self, k: T) -> Self {
+        Polynomial::new(self.coef.iter().map(|&c| c * k).collect())
+    }
+
+    pub fn compose(&self, inner: &Polynomial<T>) -> Self {
+        // p(q(x)), Horner's rule again with polynomials instead of numbers
+        self.coef.iter().rev().fold(Polynomial::zero(), |acc, &c| &(&acc * inner) + &Polynomial::constant(c))
+    }
+
+    pub fn derivative(&self) -> Self {
+        // d/dx sum c_k x^k = sum k c_k x^(k - 1). k is built up by adding ones, so this
+        // works for integer coefficients too
+        let mut k = T::zero();
+        let coef = self
+            .coef
+            .iter()
+            .skip(1)
+            .map(|&c| {
+                k = k + T::one();
+                k * c
+            })
+            .collect();
+        Polynomial::new(coef)
+    }
+}
+
+impl<T: Float> Polynomial<T> {
+    pub fn integral(&self, constant: T) -> Self {
+        // The antiderivative with value `constant` at x = 0
+        let mut coef = vec![constant];
+        coef.extend(self.coef.iter().enumerate().map(|(k, &c)| c / T::from_usize(k + 1)));
+        Polynomial::new(coef)
+    }
+
+    pub fn integrate(&self, a: T, b: T) -> T {
+        // Definite integral from a to b
+        let p = self.integral(T::zero());
+        p.eval(b) - p.eval(a)
+    }
+
+    pub fn div_rem(&self, divisor: &Polynomial<T>) -> (Self, Self) {
+        // Long division: self = quotient * divisor + remainder, deg(remainder) < deg(divisor)
+        let d = divisor.degree().expect("Polynomial division by zero");
+        let mut rem = self.coef.clone();
+        if rem.len() <= d {
+            return (Polynomial::zero(), self.clone());
+        }
+        let lead = divisor.leading();
+        let mut quot = vec![T::zero(); rem.len() - d];
+        for k in (0..quot.len()).rev() {
+            let q = rem[k + d] / lead;
+            quot[k] = q;
+            for (r, &c) in rem[k..=k + d].iter_mut().zip(divisor.coef.iter()) {
+                *r = *r - q * c;
+            }
+        }
+        rem.truncate(d);
+        (Polynomial::new(quot), Polynomial::new(rem))
+    }
+}
+
+impl Polynomial<f64> {
+    pub fn companion(&self) -> Array<f64> {
+        // The matrix whose characteristic polynomial is self / leading coefficient:
+        // -c_(n-1)/c_n ... -c_0/c_n along the first row, ones on the subdiagonal
+        let n = self.degree().unwrap_or(0);
+        let lead = self.leading();
+        let mut c = Array::zeros(&[n, n]);
+        for j in 0..n {
+            c[(0, j)] = -self.coef[n - 1 - j] / lead;
+        }
+        for i in 1..n {
+            c[(i, i - 1)] = 1.0;
+        }
+        c
+    }
+
+    pub fn roots(&self) -> Result<Vec<Complex<f64>>, LinalgError> {
+        // All the complex roots, with multiplicity, as the eigenvalues of the companion
+        // matrix. Constants (and the zero polynomial) give an empty list
+        // Multiple roots are only found to about sqrt(machine epsilon), as usual
+        match self.degree() {
+            None | Some(0) => Ok(Vec::new()),
+            Some(_) => linalg::eigvals(&self.companion()),
+        }
+    }
+
+    pub fn real_roots(&self, tol: f64) -> Result<Vec<f64>, LinalgError> {
+        // The roots whose imaginary part is below tol, ascending
+        Ok(self.roots()?.into_iter().filter(|z| z.im.abs() <= tol).map(|z| z.re).collect())
+    }
+}
+
+impl<T: Float + fmt::Display> fmt::Display for Polynomial<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // 1 - 2x + 0.5x^3, lowest power first like the storage. The precision applies to
+        // every coefficient: format!("{:.2}", p)
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (k, &c) in self.coef.iter().enumerate() {
+            if c == T::zero() {
+                continue;
+            }
+            let negative = c < T::zero();
+            if first {
+                if negative {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", if negative { "-" } else { "+" })?;
+            }
+            first = false;
+            let c = c.abs();
+            match f.precision() {
+                Some(p) => write!(f, "{:.*}", p, c)?,
+                None if c == T::one() && k > 0 => {}        // x^2, not 1x^2
+                None => write!(f, "{}", c)?,
+            }
+            match k {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", k)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: Scalar> Add for &'a Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn add(self, other: &'a Polynomial<T>) -> Polynomial<T> {
+        let n = self.coef.len().max(other.coef.len());
+        let get = |p: &Polynomial<T>, k: usize| p.coef.get(k).copied().unwrap_or_else(T::zero);
+        Polynomial::new((0..n).map(|k| get(self, k) + get(other, k)).collect())
+    }
+}
+
+impl<'a, T: Scalar> Sub for &'a Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn sub(self, other: &'a Polynomial<T>) -> Polynomial<T> {
+        let n = self.coef.len().max(other.coef.len());
+        let get = |p: &Polynomial<T>, k: usize| p.coef.get(k).copied().unwrap_or_else(T::zero);
+        Polynomial::new((0..n).map(|k| get(self, k) - get(other, k)).collect())
+    }
+}
+
+impl<'a, T: Scalar> Mul for &'a Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn mul(self, other: &'a Polynomial<T>) -> Polynomial<T> {
+        // Convolution of the coefficients
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut coef = vec![T::zero(); self.coef.len() + other.coef.len() - 1];
+        for (i, &a) in self.coef.iter().enumerate() {
+            for (j, &b) in other.coef.iter().enumerate() {
+                coef[i + j] = coef[i + j] + a * b;
+            }
+        }
+        Polynomial::new(coef)
+    }
+}
+
+impl<T: Scalar> Neg for &Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn neg(self) -> Polynomial<T> {
+        Polynomial::new(self.coef.iter().map(|&c| T::zero() - c).collect())
+    }
+}
+
+// The same operators on owned values, so p + q works without the &s
+macro_rules! impl_poly_op {
+    ($Op:ident, $op:ident) => {
+        impl<T: Scalar> $Op for Polynomial<T> {
+            type Output = Polynomial<T>;
+
+            fn $op(self, other: Polynomial<T>) -> Polynomial<T> {
+                (&self).$op(&other)
+            }
+        }
+    };
+}
+
+impl_poly_op!(Add, add);
+impl_poly_op!(Sub, sub);
+impl_poly_op!(Mul, mul);
+
+impl<T: Scalar> Neg for Polynomial<T> {
+    type Output = Polynomial<T>;
+
+    fn neg(self) -> Polynomial<T> {
+        -&self
+    }
+}
+
+// Orthogonal families
+//
+// All three follow a three-term recurrence a1 P_(k+1) = (a2 + a3 x) P_k - a4 P_(k-1)
+// from P_0 = 1. The Polynomial versions run it on polynomials, the _eval versions on
+// numbers.
+
+fn recurrence(n: usize, p1: Polynomial<f64>, step: impl Fn(f64) -> (f64, f64, f64, f64)) -> Polynomial<f64> {
+    let mut p_prev = Polynomial::constant(1.0);
+    if n == 0 {
+        return p_prev;
+    }
+    let mut p = p1;
+    for k in 1..n {
+        let (a1, a2, a3, a4) = step(k as f64);
+        let next = &(&Polynomial::new(vec![a2, a3]) * &p) - &p_prev.scale(a4);
+        p_prev = p;
+        p = next.scale(1.0 / a1);
+    }
+    p
+}
+
+fn recurrence_eval(n: usize, x: f64, p1: f64, step: impl Fn(f64) -> (f64, f64, f64, f64)) -> f64 {
+    let mut p_prev = 1.0;
+    if n == 0 {
+        return p_prev;
+    }
+    let mut p = p1;
+    for k in 1..n {
+        let (a1, a2, a3, a4) = step(k as f64);
+        let next = ((a2 + a3 * x) * p - a4 * p_prev) / a1;
+        p_prev = p;
+        p = next;
+    }
+    p
+}
+
+fn legendre_step(k: f64) -> (f64, f64, f64, f64) {
+    // (k + 1) P_(k+1) = (2k + 1) x P_k - k P_(k-1)
+    (k + 1.0, 0.0, 2.0 * k + 1.0, k)
+}
+
+fn chebyshev_step(_k: f64) -> (f64, f64, f64, f64) {
+    // T_(k+1) = 2x T_k - T_(k-1)
+    (1.0, 0.0, 2.0, 1.0)
+}
+
+fn jacobi_p1(a: f64, b: f64) -> (f64, f64) {
+    // P_1^(a, b)(x) = (a + 1) + (a + b + 2)(x - 1)/2, as (constant, slope)
+    let slope = (a + b + 2.0) / 2.0;
+    (a + 1.0 - slope, slope)
+}
+
+fn jacobi_step(a: f64, b: f64) -> impl Fn(f64) -> (f64, f64, f64, f64) {
+    // The standard recurrence, written for P_(k+1) with c = 2k + a + b + 2
+    move |k| {
+        let c = 2.0 * k + a + b + 2.0;
+        let a1 = 2.0 * (k + 1.0) * (k + a + b + 1.0) * (c - 2.0);
+        let a2 = (c - 1.0) * (a * a - b * b);
+        let a3 = (c - 1.0) * c * (c - 2.0);
+        let a4 = 2.0 * (k + a) * (k + b) * c;
+        (a1, a2, a3, a4)
+    }
+}
+
+pub fn legendre(n: usize) -> Polynomial<f64> {
+    // P_n, orthogonal on [-1, 1] with weight 1, P_n(1) = 1
+    recurrence(n, Polynomial::x(), legendre_step)
+}
+
+pub fn chebyshev(n: usize) -> Polynomial<f64> {
+    // T_n of the first kind, T_n(cos t) = cos(n t)
+    recurrence(n, Polynomial::x(), chebyshev_step)
+}
+
+pub fn jacobi(n: usize, a: f64, b: f64) -> Polynomial<f64> {
+    // P_n^(a, b), orthogonal on [-1, 1] with weight (1 - x)^a (1 + x)^b. a = b = 0 is Legendre
+    let (c0, c1) = jacobi_p1(a, b);
+    recurrence(n, Polynomial::new(vec![c0, c1]), jacobi_step(a, b))
+}
+
+pub fn legendre_eval(n: usize, x: f64) -> f64 {
+    recurrence_eval(n, x, x, legendre_step)
+}
+
+pub fn chebyshev_eval(n: usize, x: f64) -> f64 {
+    recurrence_eval(n, x, x, chebyshev_step)
+}
+
+pub fn jacobi_eval(n: usize, a: f64, b: f64, x: f64) -> f64 {
+    let (c0, c1) = jacobi_p1(a, b);
+    recurrence_eval(n, x, c0 + c1 * x, jacobi_step(a, b))
+}
@@ -0,0 +1,182 @@
+// Gaussian quadrature: 1D rules on [-1, 1] and polar rules over the unit disk / annulus
+//
+// An n-point Gauss rule integrates polynomials up to degree 2n - 1 exactly (times its weight
+// function). Gauss-Legendre has weight 1 and comes from Newton's method on P_n; Gauss-Jacobi
+// has weight (1 - x)^a (1 + x)^b and comes from the Golub-Welsch algorithm, the nodes being
+// the eigenvalues of the symmetric tridiagonal matrix of the Jacobi recurrence.
+// Over the disk, the integral of f(rho, theta) rho drho dtheta is a Gauss-Jacobi rule in rho
+// (the rho factor is the weight 1 + x after mapping [0, 1] to [-1, 1]) times the trapezoidal
+// rule in theta, which is exact for trigonometric polynomials.
+
+use crate::numerics::array::Array;
+use crate::numerics::linalg::{self, LinalgError};
+use crate::numerics::polynomial::legendre_eval;
+use crate::numerics::special::beta;
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub nodes: Vec<f64>,
+    pub weights: Vec<f64>,
+}
+
+impl Rule {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn integrate<F: Fn(f64) -> f64>(&self, f: F) -> f64 {
+        // sum_i w_i f(x_i), the integral over [-1, 1] against the rule's weight function
+        self.nodes.iter().zip(self.weights.iter()).map(|(&x, &w)| w * f(x)).sum()
+    }
+
+    pub fn integrate_interval<F: Fn(f64) -> f64>(&self, a: f64, b: f64, f: F) -> f64 {
+        // Integral over [a, b] through x = (a + b)/2 + (b - a)/2 t. For a Jacobi rule the
+        // weight comes along in the t variable: (1 - t)^alpha (1 + t)^beta
+        let (mid, half) = ((a + b) / 2.0, (b - a) / 2.0);
+        half * self.integrate(|t| f(mid + half * t))
+    }
+}
+
+pub fn gauss_legendre(n: usize) -> Rule {
+    // Newton's method on P_n from the asymptotic guess cos(pi (i + 3/4) / (n + 1/2)).
+    // P_n'(x) = n (x P_n - P_(n-1)) / (x^2 - 1), and the weights are 2 / ((1 - x^2) P_n'^2).
+    // The rule is symmetric, so only half the roots are searched for
+    let mut nodes = vec![0.0; n];
+    let mut weights = vec![0.0; n];
+    let nf = n as f64;
+    for i in 0..n.div_ceil(2) {
+        let derivative = |x: f64| nf * (x * legendre_eval(n, x) - legendre_eval(n - 1, x)) / (x * x - 1.0);
+        let mut x = (PI * (i as f64 + 0.75) / (nf + 0.5)).cos();
+        for _ in 0..100 {
+            let dx = legendre_eval(n, x) / derivative(x);
+            x -= dx;
+            if dx.abs() < 1e-15 {
+                break;
+            }
+        }
+        let w = 2.0 / ((1.0 - x * x) * derivative(x).powi(2));
+        nodes[i] = -x;
+        nodes[n - 1 - i] = x;
+        weights[i] = w;
+        weights[n - 1 - i] = w;
+    }
+    if n % 2 == 1 {
+        nodes[n / 2] = 0.0;
+    }
+    Rule { nodes, weights }
+}
+
+pub fn gauss_jacobi(n: usize, a: f64, b: f64) -> Result<Rule, LinalgError> {
+    // Weight (1 - x)^a (1 + x)^b on [-1, 1], a, b > -1. Golub-Welsch: the nodes are the
+    // eigenvalues of the Jacobi matrix J (diagonal alpha_k, off-diagonal sqrt(beta_k)) and
+    // the weights are mu_0 v_0^2, with v_0 the first component of each unit eigenvector and
+    // mu_0 = 2^(a + b + 1) B(a + 1, b + 1) the integral of the weight itself
+    assert!(a > -1.0 && b > -1.0, "Gauss-Jacobi needs a, b > -1");
+    if n == 0 {
+        return Ok(Rule { nodes: Vec::new(), weights: Vec::new() });
+    }
+    let mut j = Array::zeros(&[n, n]);
+    for k in 0..n {
+        let c = 2.0 * k as f64 + a + b;
+        // (b^2 - a^2) / (c (c + 2)), written so that k = 0, a + b = 0 isn't 0 / 0
+        j[(k, k)] = if k == 0 { (b - a) / (a + b + 2.0) } else { (b * b - a * a) / (c * (c + 2.0)) };
+        if k > 0 {
+            let kf = k as f64;
+            let beta_k = if k == 1 {
+                // The general formula is 0 / 0 for a + b = -1
+                4.0 * (1.0 + a) * (1.0 + b) / ((2.0 + a + b).powi(2) * (3.0 + a + b))
+            } else {
+                4.0 * kf * (kf + a) * (kf + b) * (kf + a + b) / (c * c * (c + 1.0) * (c - 1.0))
+            };
+            j[(k, k - 1)] = beta_k.sqrt();
+            j[(k - 1, k)] = beta_k.sqrt();
+        }
+    }
+    let (nodes, vectors) = linalg::eigh(&j)?;
+    let mu0 = 2f64.powf(a + b + 1.0) * beta(a + 1.0, b + 1.0);
+    let weights = (0..n).map(|i| mu0 * vectors[(0, i)].powi(2)).collect();
+    Ok(Rule { nodes, weights })
+}
+
+// Polar rules
+
+#[derive(Clone, Debug)]
+pub struct DiskRule {
+    // Flat lists of points and weights, rho-major: all the angles of the first radius, ...
+    pub rho: Vec<f64>,
+    pub theta: Vec<f64>,
+    pub weights: Vec<f64>,
+}
+
+impl DiskRule {
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    pub fn integrate<F: Fn(f64, f64) -> f64>(&self, f: F) -> f64 {
+        // The integral of f(rho, theta) over the area, rho drho dtheta
+        (0..self.len()).map(|i| self.weights[i] * f(self.rho[i], self.theta[i])).sum()
+    }
+
+    pub fn integrate_samples(&self, values: &[f64]) -> f64 {
+        // Same with f already evaluated at (self.rho, self.theta), e.g. Zernike::z_j output
+        assert_eq!(values.len(), self.len(), "one value per quadrature point");
+        self.weights.iter().zip(values.iter()).map(|(&w, &v)| w * v).sum()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.weights.iter().sum()
+    }
+}
+
+fn polar_rule(radial: &[(f64, f64)], n_theta: usize) -> DiskRule {
+    // radial: (rho, weight) pairs that already include the rho of the area element
+    let h = 2.0 * PI / n_theta as f64;
+    let mut rule = DiskRule { rho: Vec::new(), theta: Vec::new(), weights: Vec::new() };
+    for &(r, w) in radial {
+        for k in 0..n_theta {
+            rule.rho.push(r);
+            rule.theta.push(k as f64 * h);
+            rule.weights.push(w * h);
+        }
+    }
+    rule
+}
+
+pub fn disk(degree: usize) -> DiskRule {
+    // Exact for polynomials in x, y of total degree <= `degree` over the unit disk.
+    // rho^k cos(m theta) with m <= k <= degree: Gauss-Jacobi (0, 1) with degree / 2 + 1
+    // points in rho, and degree + 1 equally spaced angles
+    // int_0^1 g(rho) rho drho = 1/4 int_-1^1 g((1 + x)/2) (1 + x) dx
+    let radial = gauss_jacobi(degree / 2 + 1, 0.0, 1.0).expect("the Jacobi matrix is symmetric");
+    let pairs: Vec<(f64, f64)> =
+        radial.nodes.iter().zip(radial.weights.iter()).map(|(&x, &w)| ((1.0 + x) / 2.0, w / 4.0)).collect();
+    polar_rule(&pairs, degree + 1)
+}
+
+pub fn annulus(inner: f64, outer: f64, degree: usize) -> DiskRule {
+    // inner <= rho <= outer, e.g. a pupil with a central obscuration. Without the rho = 0
+    // end point the rho factor is just one more power for Gauss-Legendre
+    assert!(0.0 <= inner && inner < outer, "annulus needs 0 <= inner < outer");
+    let radial = gauss_legendre(degree / 2 + 1);
+    let (mid, half) = ((inner + outer) / 2.0, (outer - inner) / 2.0);
+    let pairs: Vec<(f64, f64)> = radial
+        .nodes
+        .iter()
+        .zip(radial.weights.iter())
+        .map(|(&x, &w)| {
+            let r = mid + half * x;
+            (r, w * half * r)
+        })
+        .collect();
+    polar_rule(&pairs, degree + 1)
+}
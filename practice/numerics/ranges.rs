@@ -0,0 +1,88 @@
+// Evenly spaced values and coordinate grids: linspace, arange, logspace, geomspace, meshgrid
+// Same behaviour as the numpy functions with the same names
+
+use crate::numerics::array::Array;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indexing {
+    Xy,     // Cartesian: shape [ny, nx], x changes along the columns (numpy's default)
+    Ij,     // Matrix: shape [nx, ny], x changes along the rows
+}
+
+pub fn linspace(start: f64, stop: f64, num: usize) -> Array<f64> {
+    linspace_step(start, stop, num, true).0
+}
+
+pub fn linspace_step(start: f64, stop: f64, num: usize, endpoint: bool) -> (Array<f64>, Option<f64>) {
+    // num points from start to stop (stop included if endpoint), plus the spacing.
+    // num = 0 is empty, num = 1 is just [start], and then there is no spacing to return
+    let intervals = if endpoint { num.saturating_sub(1) } else { num };
+    if intervals == 0 {
+        return (Array::from_vec(vec![start; num]), None);
+    }
+    let step = (stop - start) / intervals as f64;
+    let mut values: Vec<f64> = (0..num).map(|i| start + i as f64 * step).collect();
+    if endpoint {
+        values[num - 1] = stop;     // Exactly stop, whatever the rounding of start + (n - 1) * step
+    }
+    (Array::from_vec(values), Some(step))
+}
+
+pub fn arange(start: f64, stop: f64, step: f64) -> Array<f64> {
+    // start, start + step, ... while < stop (> stop for a negative step)
+    assert!(step != 0.0 && step.is_finite(), "arange needs a finite, non-zero step (got {})", step);
+    assert!(start.is_finite() && stop.is_finite(), "arange needs finite ends (got {} and {})", start, stop);
+    // Like numpy's "Maximum allowed size exceeded", rather than an allocation failure
+    let count = ((stop - start) / step).ceil().max(0.0);
+    assert!(count <= isize::MAX as f64, "arange({}, {}, {}) would have {} elements", start, stop, step, count);
+    let n = count as usize;
+    Array::from_vec((0..n).map(|i| start + i as f64 * step).collect())
+}
+
+pub fn logspace(start: f64, stop: f64, num: usize, base: f64) -> Array<f64> {
+    // base^start ..= base^stop, evenly spaced in the exponent
+    linspace(start, stop, num).map(|&e| base.powf(e))
+}
+
+pub fn geomspace(start: f64, stop: f64, num: usize) -> Array<f64> {
+    // start ..= stop with a constant ratio between neighbours. Both ends must be non-zero
+    // and share a sign (a geometric progression never crosses 0)
+    assert!(
+        start != 0.0 && stop != 0.0 && start.signum() == stop.signum(),
+        "geomspace needs non-zero ends of the same sign (got {} and {})",
+        start,
+        stop
+    );
+    let sign = start.signum();
+    let mut values = logspace(start.abs().log10(), stop.abs().log10(), num, 10.0).into_vec();
+    for v in values.iter_mut() {
+        *v *= sign;
+    }
+    // Hit the ends exactly rather than 10^log10(x)
+    if num > 0 {
+        values[0] = start;
+    }
+    if num > 1 {
+        values[num - 1] = stop;
+    }
+    Array::from_vec(values)
+}
+
+pub fn meshgrid(x: &Array<f64>, y: &Array<f64>, indexing: Indexing) -> (Array<f64>, Array<f64>) {
+    // Coordinate grids from two 1D axes: (X, Y) with X[.] the x of each grid point
+    let (xs, ys) = (x.as_slice(), y.as_slice());
+    let (nx, ny) = (xs.len(), ys.len());
+    let (shape, gx, gy): (Vec<usize>, Vec<f64>, Vec<f64>) = match indexing {
+        Indexing::Xy => (
+            vec![ny, nx],
+            (0..ny).flat_map(|_| xs.iter().cloned()).collect(),
+            ys.iter().flat_map(|&v| std::iter::repeat_n(v, nx)).collect(),
+        ),
+        Indexing::Ij => (
+            vec![nx, ny],
+            xs.iter().flat_map(|&v| std::iter::repeat_n(v, ny)).collect(),
+            (0..nx).flat_map(|_| ys.iter().cloned()).collect(),
+        ),
+    };
+    (Array::from_shape_vec(&shape, gx).unwrap(), Array::from_shape_vec(&shape, gy).unwrap())
+}
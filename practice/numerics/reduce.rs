@@ -0,0 +1,190 @@
+// Reductions of an Array along an axis, like numpy's a.sum(axis=0), a.argmax(axis=1), ...
+//
+// Reducing along `axis` removes that axis from the shape: [2, 3, 4] summed along axis 1
+// gives [2, 4]. Each output element comes from one "lane", the 1D run of elements that
+// only differ in their index along `axis`.
+// The nan* versions skip NaN, which is how pixels outside a masked pupil are stored.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::scalar::{Float, Scalar};
+
+fn lanes<T: Copy, U, F>(arr: &Array<T>, axis: usize, mut f: F) -> Result<Array<U>, ShapeError>
+where
+    F: FnMut(&[T]) -> Result<U, ShapeError>,
+{
+    // Applies f to every lane along axis, the results form the reduced array
+    let shape = arr.shape();
+    if axis >= shape.len() {
+        return Err(ShapeError::AxisOutOfBounds { axis, ndim: shape.len() });
+    }
+    let outer: usize = shape[..axis].iter().product();
+    let n = shape[axis];
+    let inner: usize = shape[axis + 1..].iter().product();
+    let data = arr.as_slice();
+
+    let mut lane = Vec::with_capacity(n);
+    let mut out = Vec::with_capacity(outer * inner);
+    for o in 0..outer {
+        for i in 0..inner {
+            lane.clear();
+            lane.extend((0..n).map(|k| data[(o * n + k) * inner + i]));
+            out.push(f(&lane)?);
+        }
+    }
+
+    let mut reduced_shape = shape.to_vec();
+    reduced_shape.remove(axis);
+    Array::from_shape_vec(&reduced_shape, out)
+}
+
+fn arg_best<T: PartialOrd + Copy>(lane: &[T], better: fn(T, T) -> bool, skip_nan: bool, axis: usize) -> Result<usize, ShapeError> {
+    // Index of the min / max of a lane. A NaN (x != x) wins straight away unless we skip them,
+    // like numpy where NaN propagates through min / max
+    let mut best: Option<usize> = None;
+    for (k, &x) in lane.iter().enumerate() {
+        #[allow(clippy::eq_op)]
+        let is_nan = x != x;
+        if is_nan {
+            if skip_nan {
+                continue;
+            }
+            return Ok(k);
+        }
+        match best {
+            Some(b) if !better(x, lane[b]) => {}
+            _ => best = Some(k),
+        }
+    }
+    best.ok_or(ShapeError::EmptyLane { axis })
+}
+
+fn mean_var<T: Float>(lane: &[T], ddof: usize) -> (T, T) {
+    // Two passes (mean first, then the squared deviations), which avoids the
+    // cancellation of sum(x^2) - n mean^2 when the mean is large
+    let n = lane.len();
+    if n == 0 {
+        return (T::nan(), T::nan());
+    }
+    let mean = lane.iter().fold(T::zero(), |acc, &x| acc + x) / T::from_usize(n);
+    if n <= ddof {
+        return (mean, T::nan());
+    }
+    let ss = lane.iter().fold(T::zero(), |acc, &x| acc + (x - mean) * (x - mean));
+    (mean, ss / T::from_usize(n - ddof))
+}
+
+fn without_nan<T: Float>(lane: &[T]) -> Vec<T> {
+    lane.iter().cloned().filter(|x| !x.is_nan()).collect()
+}
+
+impl<T: Scalar> Array<T> {
+    pub fn sum_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| Ok(lane.iter().fold(T::zero(), |acc, &x| acc + x)))
+    }
+
+    pub fn product_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| Ok(lane.iter().fold(T::one(), |acc, &x| acc * x)))
+    }
+
+    pub fn cumsum_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        // Running sum along axis, same shape as the input
+        let shape = self.shape();
+        if axis >= shape.len() {
+            return Err(ShapeError::AxisOutOfBounds { axis, ndim: shape.len() });
+        }
+        let n = shape[axis];
+        let inner: usize = shape[axis + 1..].iter().product();
+        let mut out = self.clone();
+        let data = out.as_mut_slice();
+        for start in (0..data.len()).step_by((n * inner).max(1)) {
+            for i in 0..inner {
+                for k in 1..n {
+                    let prev = data[start + (k - 1) * inner + i];
+                    data[start + k * inner + i] = data[start + k * inner + i] + prev;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn sum(&self) -> T {
+        self.iter().fold(T::zero(), |acc, &x| acc + x)
+    }
+}
+
+impl<T: Scalar + PartialOrd> Array<T> {
+    pub fn min_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a < b, false, axis).map(|k| lane[k]))
+    }
+
+    pub fn max_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a > b, false, axis).map(|k| lane[k]))
+    }
+
+    pub fn argmin_axis(&self, axis: usize) -> Result<Array<usize>, ShapeError> {
+        // Index of the first minimum of each lane
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a < b, false, axis))
+    }
+
+    pub fn argmax_axis(&self, axis: usize) -> Result<Array<usize>, ShapeError> {
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a > b, false, axis))
+    }
+}
+
+impl<T: Float> Array<T> {
+    pub fn mean_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| Ok(mean_var(lane, 0).0))
+    }
+
+    pub fn var_axis(&self, axis: usize, ddof: usize) -> Result<Array<T>, ShapeError> {
+        // Variance with n - ddof in the denominator (ddof = 1 for the unbiased estimate)
+        lanes(self, axis, |lane| Ok(mean_var(lane, ddof).1))
+    }
+
+    pub fn std_axis(&self, axis: usize, ddof: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| Ok(mean_var(lane, ddof).1.sqrt()))
+    }
+
+    pub fn mean(&self) -> T {
+        mean_var(self.as_slice(), 0).0
+    }
+
+    // NaN-ignoring versions
+
+    pub fn nansum_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| Ok(without_nan(lane).iter().fold(T::zero(), |acc, &x| acc + x)))
+    }
+
+    pub fn nanmean_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        // An all-NaN lane gives NaN
+        lanes(self, axis, |lane| Ok(mean_var(&without_nan(lane), 0).0))
+    }
+
+    pub fn nanvar_axis(&self, axis: usize, ddof: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| Ok(mean_var(&without_nan(lane), ddof).1))
+    }
+
+    pub fn nanstd_axis(&self, axis: usize, ddof: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| Ok(mean_var(&without_nan(lane), ddof).1.sqrt()))
+    }
+
+    pub fn nanmin_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a < b, true, axis).map(|k| lane[k]))
+    }
+
+    pub fn nanmax_axis(&self, axis: usize) -> Result<Array<T>, ShapeError> {
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a > b, true, axis).map(|k| lane[k]))
+    }
+
+    pub fn nanargmin_axis(&self, axis: usize) -> Result<Array<usize>, ShapeError> {
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a < b, true, axis))
+    }
+
+    pub fn nanargmax_axis(&self, axis: usize) -> Result<Array<usize>, ShapeError> {
+        lanes(self, axis, |lane| arg_best(lane, |a, b| a > b, true, axis))
+    }
+
+    pub fn nanmean(&self) -> T {
+        mean_var(&without_nan(self.as_slice()), 0).0
+    }
+}
@@ -0,0 +1,245 @@
+// Seeded random numbers without external crates
+// The generator is xoshiro256** (https://prng.di.unimi.it/), seeded through SplitMix64
+// so that any u64 (even 0) gives a good starting state. Same seed -> same stream,
+// which is what we want for reproducible simulations
+// For threads, Rng::streams(seed, n) hands out n generators 2^128 draws apart (the
+// xoshiro jump), so thread k always gets the same numbers whatever the scheduling
+
+use crate::numerics::array::Array;
+use crate::numerics::special::ln_gamma;
+
+// Precomputed by the xoshiro authors: jump() is the same as 2^128 calls to next_u64()
+const JUMP: [u64; 4] = [0x180E_C6D3_3CFD_0ABA, 0xD5A6_1266_F0C9_392C, 0xA958_2618_E03F_C9AA, 0x39AB_DC45_29B1_661C];
+
+#[derive(Clone, Debug)]
+pub struct Rng {
+    s: [u64; 4],
+    spare_normal: Option<f64>,     // Box-Muller gives 2 normals at a time, keep the second one
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut sm = seed;
+        let s = [splitmix64(&mut sm), splitmix64(&mut sm), splitmix64(&mut sm), splitmix64(&mut sm)];
+        Rng { s, spare_normal: None }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+
+    pub fn uniform(&mut self) -> f64 {
+        // Uniform in [0, 1) using the top 53 bits (the size of the f64 mantissa)
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    pub fn uniform_range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.uniform()
+    }
+
+    pub fn normal(&mut self) -> f64 {
+        // Standard normal N(0, 1) with the polar (Marsaglia) version of Box-Muller
+        if let Some(z) = self.spare_normal.take() {
+            return z;
+        }
+        loop {
+            let u = 2.0 * self.uniform() - 1.0;
+            let v = 2.0 * self.uniform() - 1.0;
+            let s = u * u + v * v;
+            if s > 0.0 && s < 1.0 {
+                let factor = (-2.0 * s.ln() / s).sqrt();
+                self.spare_normal = Some(v * factor);
+                return u * factor;
+            }
+        }
+    }
+
+    pub fn normal_vec(&mut self, n: usize) -> Vec<f64> {
+        (0..n).map(|_| self.normal()).collect()
+    }
+
+    pub fn jump(&mut self) {
+        // Skip ahead 2^128 draws: each jump starts a new sequence that the previous one
+        // won't reach in any simulation
+        let mut s = [0u64; 4];
+        for &word in JUMP.iter() {
+            for bit in 0..64 {
+                if word & (1u64 << bit) != 0 {
+                    for (acc, &x) in s.iter_mut().zip(self.s.iter()) {
+                        *acc ^= x;
+                    }
+                }
+                self.next_u64();
+            }
+        }
+        self.s = s;
+        self.spare_normal = None;
+    }
+
+    pub fn stream(seed: u64, index: usize) -> Self {
+        // Generator number `index` of the seed: Rng::new(seed) jumped `index` times
+        let mut rng = Rng::new(seed);
+        for _ in 0..index {
+            rng.jump();
+        }
+        rng
+    }
+
+    pub fn streams(seed: u64, n: usize) -> Vec<Self> {
+        // Rng::stream(seed, 0), ..., Rng::stream(seed, n - 1), e.g. one per thread
+        let mut rng = Rng::new(seed);
+        (0..n)
+            .map(|_| {
+                let current = rng.clone();
+                rng.jump();
+                current
+            })
+            .collect()
+    }
+
+    pub fn below(&mut self, n: u64) -> u64 {
+        // Uniform integer in 0..n without the bias of next_u64() % n (Lemire's method):
+        // the high half of x * n, rejecting the few x that would favour small values
+        assert!(n > 0, "below(0) has nothing to choose from");
+        let mut m = self.next_u64() as u128 * n as u128;
+        if (m as u64) < n {
+            let threshold = n.wrapping_neg() % n;
+            while (m as u64) < threshold {
+                m = self.next_u64() as u128 * n as u128;
+            }
+        }
+        (m >> 64) as u64
+    }
+
+    pub fn bernoulli(&mut self, p: f64) -> bool {
+        // true with probability p
+        self.uniform() < p
+    }
+
+    pub fn normal_with(&mut self, mean: f64, std: f64) -> f64 {
+        mean + std * self.normal()
+    }
+
+    pub fn exponential(&mut self, rate: f64) -> f64 {
+        // Waiting time of a process with `rate` events per unit time (mean 1 / rate),
+        // by inverting the CDF. 1 - u is in (0, 1], so the log is finite
+        assert!(rate > 0.0, "exponential rate must be positive");
+        -(1.0 - self.uniform()).ln() / rate
+    }
+
+    pub fn poisson(&mut self, lambda: f64) -> u64 {
+        // Number of events for a mean of lambda, e.g. photons on a detector pixel.
+        // Small lambda: multiply uniforms until the product drops below e^-lambda (Knuth).
+        // That takes lambda steps, so above 10 use Hoermann's transformed rejection (PTRS)
+        assert!(lambda >= 0.0, "Poisson mean must be non-negative");
+        if lambda < 10.0 {
+            let limit = (-lambda).exp();
+            let mut k = 0;
+            let mut product = self.uniform();
+            while product > limit {
+                k += 1;
+                product *= self.uniform();
+            }
+            return k;
+        }
+        let (slam, loglam) = (lambda.sqrt(), lambda.ln());
+        let b = 0.931 + 2.53 * slam;
+        let a = -0.059 + 0.02483 * b;
+        let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+        let vr = 0.9277 - 3.6224 / (b - 2.0);
+        loop {
+            let u = self.uniform() - 0.5;
+            let v = self.uniform();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + lambda + 0.43).floor();
+            if us >= 0.07 && v <= vr {
+                return k as u64;
+            }
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+            if v.ln() + inv_alpha.ln() - (a / (us * us) + b).ln() <= -lambda + k * loglam - ln_gamma(k + 1.0) {
+                return k as u64;
+            }
+        }
+    }
+
+    // Filling arrays
+
+    pub fn uniform_vec(&mut self, n: usize) -> Vec<f64> {
+        (0..n).map(|_| self.uniform()).collect()
+    }
+
+    pub fn fill_uniform(&mut self, x: &mut [f64]) {
+        for v in x.iter_mut() {
+            *v = self.uniform();
+        }
+    }
+
+    pub fn fill_normal(&mut self, x: &mut [f64]) {
+        for v in x.iter_mut() {
+            *v = self.normal();
+        }
+    }
+
+    pub fn array_with<F: FnMut(&mut Rng) -> f64>(&mut self, shape: &[usize], mut f: F) -> Array<f64> {
+        // Any distribution, e.g. rng.array_with(&[64, 64], |r| r.poisson(5.0) as f64)
+        let n = shape.iter().product();
+        let data = (0..n).map(|_| f(self)).collect();
+        Array::from_shape_vec(shape, data).expect("the data has the size of the shape")
+    }
+
+    pub fn uniform_array(&mut self, shape: &[usize]) -> Array<f64> {
+        self.array_with(shape, |r| r.uniform())
+    }
+
+    pub fn normal_array(&mut self, shape: &[usize]) -> Array<f64> {
+        self.array_with(shape, |r| r.normal())
+    }
+
+    // Shuffling and picking
+
+    pub fn shuffle<T>(&mut self, x: &mut [T]) {
+        // Fisher-Yates: every permutation equally likely
+        for i in (1..x.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            x.swap(i, j);
+        }
+    }
+
+    pub fn choose<'a, T>(&mut self, x: &'a [T]) -> Option<&'a T> {
+        if x.is_empty() {
+            return None;
+        }
+        Some(&x[self.below(x.len() as u64) as usize])
+    }
+
+    pub fn sample_indices(&mut self, n: usize, k: usize) -> Vec<usize> {
+        // k distinct indices out of 0..n, in random order (a partial Fisher-Yates)
+        assert!(k <= n, "can't pick {} distinct indices out of {}", k, n);
+        let mut indices: Vec<usize> = (0..n).collect();
+        for i in 0..k {
+            let j = i + self.below((n - i) as u64) as usize;
+            indices.swap(i, j);
+        }
+        indices.truncate(k);
+        indices
+    }
+}
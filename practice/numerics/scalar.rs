@@ -0,0 +1,73 @@
+// The numeric element types the arrays, polynomials, etc. can hold
+// Rust has no built-in "number" trait (Python just duck-types), so we write our own:
+// Scalar is anything with + - * / and a 0 and a 1
+
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Sub};
+
+pub trait Scalar:
+    Copy + PartialEq + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+}
+
+// The same impl for every primitive, written once with a macro (see 2-macros/2_macros.rs)
+macro_rules! impl_scalar {
+    ($($t:ty, $zero:expr, $one:expr);*) => {
+        $(
+            impl Scalar for $t {
+                fn zero() -> Self { $zero }
+                fn one() -> Self { $one }
+            }
+        )*
+    };
+}
+
+impl_scalar!(f64, 0.0, 1.0; f32, 0.0, 1.0; i32, 0, 1; i64, 0, 1; u32, 0, 1; u64, 0, 1; usize, 0, 1);
+
+pub trait Float: Scalar + PartialOrd {
+    // The extra bits only floating point types have (NaN, square roots, sin, exp, ...)
+    fn nan() -> Self;
+    fn infinity() -> Self;
+    fn is_nan(self) -> bool;
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn atan2(self, x: Self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn powf(self, p: Self) -> Self;
+    fn from_f64(x: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn from_usize(n: usize) -> Self {
+        Self::from_f64(n as f64)
+    }
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl Float for $t {
+                fn nan() -> Self { <$t>::NAN }
+                fn infinity() -> Self { <$t>::INFINITY }
+                fn is_nan(self) -> bool { <$t>::is_nan(self) }
+                fn sqrt(self) -> Self { <$t>::sqrt(self) }
+                fn abs(self) -> Self { <$t>::abs(self) }
+                fn sin(self) -> Self { <$t>::sin(self) }
+                fn cos(self) -> Self { <$t>::cos(self) }
+                fn exp(self) -> Self { <$t>::exp(self) }
+                fn ln(self) -> Self { <$t>::ln(self) }
+                fn atan2(self, x: Self) -> Self { <$t>::atan2(self, x) }
+                fn hypot(self, other: Self) -> Self { <$t>::hypot(self, other) }
+                fn powf(self, p: Self) -> Self { <$t>::powf(self, p) }
+                fn from_f64(x: f64) -> Self { x as $t }
+                fn to_f64(self) -> f64 { self as f64 }
+            }
+        )*
+    };
+}
+
+impl_float!(f64, f32);
@@ -0,0 +1,453 @@
+// Sparse matrices (CSR / CSC) and iterative solvers that only need matrix-vector products
+//
+// CSR keeps the nonzeros row by row: row i is values[indptr[i]..indptr[i + 1]] at the
+// columns indices[indptr[i]..indptr[i + 1]], sorted. CSC is the same by columns. Storage
+// is nnz + rows instead of rows x cols, and A x costs nnz multiplications, which is what
+// makes influence functions (a few actuators per pixel) or slope geometry matrices
+// affordable for big pupils. The CSR of A read by columns is the CSC of A^T, so both share
+// one compressed storage and transposing just re-sorts the entries.
+// conjugate_gradient (symmetric positive definite systems) and lsqr (least squares, any
+// shape) work on anything that implements LinearOperator: the sparse types and the dense
+// 2D Array<f64>.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::linalg::LinalgError;
+
+type Triplet = (usize, usize, f64); // (row, column, value)
+
+#[derive(Clone, Debug, PartialEq)]
+struct Compressed {
+    n_major: usize, // rows for CSR, columns for CSC
+    n_minor: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl Compressed {
+    fn from_entries(n_major: usize, n_minor: usize, mut entries: Vec<Triplet>) -> Self {
+        // (major, minor, value) in any order; repeated positions are added up
+        entries.sort_by_key(|&(i, j, _)| (i, j));
+        let mut indptr = vec![0; n_major + 1];
+        let mut indices: Vec<usize> = Vec::with_capacity(entries.len());
+        let mut values: Vec<f64> = Vec::with_capacity(entries.len());
+        let mut last = None;
+        for (i, j, v) in entries {
+            if last == Some((i, j)) {
+                *values.last_mut().unwrap() += v;
+                continue;
+            }
+            last = Some((i, j));
+            indptr[i + 1] += 1;
+            indices.push(j);
+            values.push(v);
+        }
+        for i in 0..n_major {
+            indptr[i + 1] += indptr[i];
+        }
+        Compressed { n_major, n_minor, indptr, indices, values }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = Triplet> + '_ {
+        (0..self.n_major).flat_map(move |i| {
+            let range = self.indptr[i]..self.indptr[i + 1];
+            self.indices[range.clone()].iter().zip(self.values[range].iter()).map(move |(&j, &v)| (i, j, v))
+        })
+    }
+
+    fn swap_axes(&self) -> Self {
+        Compressed::from_entries(self.n_minor, self.n_major, self.entries().map(|(i, j, v)| (j, i, v)).collect())
+    }
+
+    fn lane(&self, i: usize) -> (&[usize], &[f64]) {
+        let range = self.indptr[i]..self.indptr[i + 1];
+        (&self.indices[range.clone()], &self.values[range])
+    }
+
+    fn get(&self, i: usize, j: usize) -> f64 {
+        let (indices, values) = self.lane(i);
+        indices.binary_search(&j).map(|k| values[k]).unwrap_or(0.0)
+    }
+
+    fn gather(&self, x: &[f64]) -> Vec<f64> {
+        // y[major] = sum over the lane of value * x[minor]
+        assert_eq!(x.len(), self.n_minor, "vector of length {} for {} columns", x.len(), self.n_minor);
+        (0..self.n_major)
+            .map(|i| {
+                let (indices, values) = self.lane(i);
+                indices.iter().zip(values.iter()).map(|(&j, &v)| v * x[j]).sum()
+            })
+            .collect()
+    }
+
+    fn scatter(&self, x: &[f64]) -> Vec<f64> {
+        // y[minor] += value * x[major], the product with the other orientation
+        assert_eq!(x.len(), self.n_major, "vector of length {} for {} rows", x.len(), self.n_major);
+        let mut y = vec![0.0; self.n_minor];
+        for (i, j, v) in self.entries() {
+            y[j] += v * x[i];
+        }
+        y
+    }
+}
+
+fn check_triplets(rows: usize, cols: usize, triplets: &[Triplet]) -> Result<(), ShapeError> {
+    for &(i, j, _) in triplets {
+        if i >= rows {
+            return Err(ShapeError::OutOfBounds { axis: 0, index: i, len: rows });
+        }
+        if j >= cols {
+            return Err(ShapeError::OutOfBounds { axis: 1, index: j, len: cols });
+        }
+    }
+    Ok(())
+}
+
+fn dense_triplets(a: &Array<f64>, threshold: f64) -> Result<(usize, usize, Vec<Triplet>), ShapeError> {
+    // The entries of a dense matrix with |value| > threshold
+    let (rows, cols) = match *a.shape() {
+        [rows, cols] => (rows, cols),
+        _ => return Err(ShapeError::WrongNdim { expected: 2, shape: a.shape().to_vec() }),
+    };
+    let triplets = a
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.abs() > threshold)
+        .map(|(k, &v)| (k / cols, k % cols, v))
+        .collect();
+    Ok((rows, cols, triplets))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix(Compressed);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CscMatrix(Compressed);
+
+impl CsrMatrix {
+    pub fn from_triplets(rows: usize, cols: usize, triplets: &[Triplet]) -> Result<Self, ShapeError> {
+        // (row, column, value) entries in any order, duplicates summed (like scipy's coo_matrix)
+        check_triplets(rows, cols, triplets)?;
+        Ok(CsrMatrix(Compressed::from_entries(rows, cols, triplets.to_vec())))
+    }
+
+    pub fn from_dense(a: &Array<f64>, threshold: f64) -> Result<Self, ShapeError> {
+        // Keeps the entries with |value| > threshold (0.0 keeps every nonzero)
+        let (rows, cols, triplets) = dense_triplets(a, threshold)?;
+        CsrMatrix::from_triplets(rows, cols, &triplets)
+    }
+
+    pub fn identity(n: usize) -> Self {
+        CsrMatrix(Compressed::from_entries(n, n, (0..n).map(|i| (i, i, 1.0)).collect()))
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.0.n_major, self.0.n_minor)
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.0.values.len()
+    }
+
+    pub fn density(&self) -> f64 {
+        // Fraction of the entries that are stored
+        let (rows, cols) = self.shape();
+        self.nnz() as f64 / (rows * cols) as f64
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.0.get(row, col)
+    }
+
+    pub fn row(&self, i: usize) -> (&[usize], &[f64]) {
+        // (column indices, values) of the nonzeros of row i
+        self.0.lane(i)
+    }
+
+    pub fn triplets(&self) -> Vec<Triplet> {
+        self.0.entries().collect()
+    }
+
+    pub fn mat_vec(&self, x: &[f64]) -> Vec<f64> {
+        self.0.gather(x)
+    }
+
+    pub fn transpose_mat_vec(&self, y: &[f64]) -> Vec<f64> {
+        // A^T y without building A^T
+        self.0.scatter(y)
+    }
+
+    pub fn transpose(&self) -> CsrMatrix {
+        CsrMatrix(self.0.swap_axes())
+    }
+
+    pub fn to_csc(&self) -> CscMatrix {
+        CscMatrix(self.0.swap_axes())
+    }
+
+    pub fn to_dense(&self) -> Array<f64> {
+        let (rows, cols) = self.shape();
+        let mut a = Array::zeros(&[rows, cols]);
+        for (i, j, v) in self.0.entries() {
+            a[(i, j)] = v;
+        }
+        a
+    }
+}
+
+impl CscMatrix {
+    pub fn from_triplets(rows: usize, cols: usize, triplets: &[Triplet]) -> Result<Self, ShapeError> {
+        check_triplets(rows, cols, triplets)?;
+        let entries = triplets.iter().map(|&(i, j, v)| (j, i, v)).collect();
+        Ok(CscMatrix(Compressed::from_entries(cols, rows, entries)))
+    }
+
+    pub fn from_dense(a: &Array<f64>, threshold: f64) -> Result<Self, ShapeError> {
+        let (rows, cols, triplets) = dense_triplets(a, threshold)?;
+        CscMatrix::from_triplets(rows, cols, &triplets)
+    }
+
+    pub fn shape(&self) -> (usize, usize) {
+        (self.0.n_minor, self.0.n_major)
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.0.values.len()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.0.get(col, row)
+    }
+
+    pub fn column(&self, j: usize) -> (&[usize], &[f64]) {
+        // (row indices, values) of the nonzeros of column j
+        self.0.lane(j)
+    }
+
+    pub fn mat_vec(&self, x: &[f64]) -> Vec<f64> {
+        self.0.scatter(x)
+    }
+
+    pub fn transpose_mat_vec(&self, y: &[f64]) -> Vec<f64> {
+        self.0.gather(y)
+    }
+
+    pub fn transpose(&self) -> CscMatrix {
+        CscMatrix(self.0.swap_axes())
+    }
+
+    pub fn to_csr(&self) -> CsrMatrix {
+        CsrMatrix(self.0.swap_axes())
+    }
+
+    pub fn to_dense(&self) -> Array<f64> {
+        self.to_csr().to_dense()
+    }
+}
+
+// Iterative solvers
+
+pub trait LinearOperator {
+    fn shape(&self) -> (usize, usize);
+    fn apply(&self, x: &[f64]) -> Vec<f64>; // A x
+    fn apply_transpose(&self, y: &[f64]) -> Vec<f64>; // A^T y
+}
+
+impl LinearOperator for CsrMatrix {
+    fn shape(&self) -> (usize, usize) {
+        CsrMatrix::shape(self)
+    }
+
+    fn apply(&self, x: &[f64]) -> Vec<f64> {
+        self.mat_vec(x)
+    }
+
+    fn apply_transpose(&self, y: &[f64]) -> Vec<f64> {
+        self.transpose_mat_vec(y)
+    }
+}
+
+impl LinearOperator for CscMatrix {
+    fn shape(&self) -> (usize, usize) {
+        CscMatrix::shape(self)
+    }
+
+    fn apply(&self, x: &[f64]) -> Vec<f64> {
+        self.mat_vec(x)
+    }
+
+    fn apply_transpose(&self, y: &[f64]) -> Vec<f64> {
+        self.transpose_mat_vec(y)
+    }
+}
+
+impl LinearOperator for Array<f64> {
+    // A dense 2D matrix
+    fn shape(&self) -> (usize, usize) {
+        match *Array::shape(self) {
+            [rows, cols] => (rows, cols),
+            _ => panic!("a linear operator needs a 2D array, got shape {:?}", Array::shape(self)),
+        }
+    }
+
+    fn apply(&self, x: &[f64]) -> Vec<f64> {
+        let (_, cols) = LinearOperator::shape(self);
+        assert_eq!(x.len(), cols, "vector of length {} for {} columns", x.len(), cols);
+        self.as_slice().chunks(cols).map(|row| row.iter().zip(x.iter()).map(|(a, b)| a * b).sum()).collect()
+    }
+
+    fn apply_transpose(&self, y: &[f64]) -> Vec<f64> {
+        let (rows, cols) = LinearOperator::shape(self);
+        assert_eq!(y.len(), rows, "vector of length {} for {} rows", y.len(), rows);
+        let mut out = vec![0.0; cols];
+        for (row, &yi) in self.as_slice().chunks(cols).zip(y.iter()) {
+            for (o, &a) in out.iter_mut().zip(row.iter()) {
+                *o += a * yi;
+            }
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IterativeSolution {
+    pub x: Vec<f64>,
+    pub iterations: usize,
+    pub residual_norm: f64, // |b - A x| (with the damping term for lsqr)
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+    // y += alpha x
+    for (yi, &xi) in y.iter_mut().zip(x.iter()) {
+        *yi += alpha * xi;
+    }
+}
+
+pub fn conjugate_gradient<A: LinearOperator>(
+    a: &A,
+    b: &[f64],
+    tol: f64,
+    max_iter: usize,
+) -> Result<IterativeSolution, LinalgError> {
+    // A x = b for symmetric positive definite A, e.g. the normal equations of a fit or a
+    // Laplacian. Stops when |b - A x| <= tol |b|; in exact arithmetic it takes at most n
+    // iterations, in practice ~ sqrt(condition number) of them
+    let (rows, cols) = a.shape();
+    if rows != cols {
+        return Err(LinalgError::NotSquare { shape: vec![rows, cols] });
+    }
+    assert_eq!(b.len(), rows, "right hand side of length {} for {} rows", b.len(), rows);
+    let mut x = vec![0.0; cols];
+    let mut r = b.to_vec();
+    let mut p = r.clone();
+    let mut rr = dot(&r, &r);
+    let target = tol * norm(b);
+    for iteration in 0..max_iter {
+        if rr.sqrt() <= target {
+            return Ok(IterativeSolution { x, iterations: iteration, residual_norm: rr.sqrt() });
+        }
+        let ap = a.apply(&p);
+        let pap = dot(&p, &ap);
+        if pap <= 0.0 {
+            return Err(LinalgError::NotPositiveDefinite);
+        }
+        let alpha = rr / pap;
+        axpy(alpha, &p, &mut x);
+        axpy(-alpha, &ap, &mut r);
+        let rr_new = dot(&r, &r);
+        for (pi, &ri) in p.iter_mut().zip(r.iter()) {
+            *pi = ri + rr_new / rr * *pi;
+        }
+        rr = rr_new;
+    }
+    if rr.sqrt() <= target {
+        return Ok(IterativeSolution { x, iterations: max_iter, residual_norm: rr.sqrt() });
+    }
+    Err(LinalgError::NoConvergence)
+}
+
+pub fn lsqr<A: LinearOperator>(
+    a: &A,
+    b: &[f64],
+    damp: f64,
+    tol: f64,
+    max_iter: usize,
+) -> Result<IterativeSolution, LinalgError> {
+    // Minimises |A x - b|^2 + damp^2 |x|^2 for any shape of A (Paige and Saunders' LSQR):
+    // the same answer as CG on the normal equations, but without squaring the condition
+    // number. damp > 0 is Tikhonov regularisation, for the modes A barely sees.
+    // Stops when A^T r is small next to |A| |r| (the least squares optimum) or when
+    // |r| <= tol |b| (a consistent system)
+    let (rows, cols) = a.shape();
+    assert_eq!(b.len(), rows, "right hand side of length {} for {} rows", b.len(), rows);
+    let mut x = vec![0.0; cols];
+    let mut u = b.to_vec();
+    let mut beta = norm(&u);
+    if beta == 0.0 {
+        return Ok(IterativeSolution { x, iterations: 0, residual_norm: 0.0 });
+    }
+    u.iter_mut().for_each(|v| *v /= beta);
+    let mut v = a.apply_transpose(&u);
+    let mut alpha = norm(&v);
+    if alpha == 0.0 {
+        // b is orthogonal to the range of A: x = 0 is already the answer
+        return Ok(IterativeSolution { x, iterations: 0, residual_norm: beta });
+    }
+    v.iter_mut().for_each(|e| *e /= alpha);
+    let mut w = v.clone();
+    let b_norm = beta;
+    let (mut phi_bar, mut rho_bar) = (beta, alpha);
+    let mut a_norm2 = 0.0;
+    let mut damp_residual2 = 0.0; // the damp |x| part of the residual
+    for iteration in 1..=max_iter {
+        // Golub-Kahan bidiagonalisation: beta u = A v - alpha u, alpha v = A^T u - beta v
+        let av = a.apply(&v);
+        for (ui, &avi) in u.iter_mut().zip(av.iter()) {
+            *ui = avi - alpha * *ui;
+        }
+        beta = norm(&u);
+        if beta > 0.0 {
+            u.iter_mut().for_each(|e| *e /= beta);
+        }
+        a_norm2 += alpha * alpha + beta * beta + damp * damp;
+        let atu = a.apply_transpose(&u);
+        for (vi, &atui) in v.iter_mut().zip(atu.iter()) {
+            *vi = atui - beta * *vi;
+        }
+        alpha = norm(&v);
+        if alpha > 0.0 {
+            v.iter_mut().for_each(|e| *e /= alpha);
+        }
+        // Rotation that removes the damping from the lower bidiagonal matrix, then the one
+        // that makes it upper bidiagonal
+        let rho_bar1 = rho_bar.hypot(damp);
+        let (c1, s1) = (rho_bar / rho_bar1, damp / rho_bar1);
+        damp_residual2 += (s1 * phi_bar).powi(2);
+        phi_bar *= c1;
+        let rho = rho_bar1.hypot(beta);
+        let (c, s) = (rho_bar1 / rho, beta / rho);
+        let theta = s * alpha;
+        rho_bar = -c * alpha;
+        let phi = c * phi_bar;
+        phi_bar *= s;
+        // x and the search direction w
+        axpy(phi / rho, &w, &mut x);
+        for (wi, &vi) in w.iter_mut().zip(v.iter()) {
+            *wi = vi - theta / rho * *wi;
+        }
+        let r_norm = (phi_bar * phi_bar + damp_residual2).sqrt();
+        let ar_norm = (phi_bar * alpha * c).abs();
+        if r_norm <= tol * b_norm || ar_norm <= tol * a_norm2.sqrt() * r_norm || alpha == 0.0 {
+            return Ok(IterativeSolution { x, iterations: iteration, residual_norm: r_norm });
+        }
+    }
+    Err(LinalgError::NoConvergence)
+}
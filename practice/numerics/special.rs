@@ -0,0 +1,191 @@
+// Special functions
+// Factorials and binomials come exact (u128 while they fit, BigInt past that) or as f64.
+// Gamma uses the Lanczos approximation (g = 7, 9 terms) which is good to ~15 digits
+// and the reflection formula for x < 0.5 so negative non-integer arguments also work
+
+use crate::numerics::bigint::BigInt;
+use std::f64::consts::PI;
+
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS_COEF: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+pub fn factorial(n: u32) -> Option<u128> {
+    // n! exactly, None once it no longer fits (34! is the last one that does)
+    (1..=n as u128).try_fold(1u128, |acc, k| acc.checked_mul(k))
+}
+
+pub fn factorial_big(n: u32) -> BigInt {
+    (1..=n).fold(BigInt::one(), |acc, k| acc.mul_small(k))
+}
+
+pub fn factorial_f64(n: u32) -> f64 {
+    // Exact up to 22!, correctly rounded products after that, infinity past 170!
+    (1..=n).map(|k| k as f64).product()
+}
+
+pub fn ln_factorial(n: u32) -> f64 {
+    ln_gamma(n as f64 + 1.0)
+}
+
+pub fn binomial(n: u32, k: u32) -> Option<u128> {
+    // C(n, k) exactly, None on overflow. Built as C(n, i + 1) = C(n, i) (n - i) / (i + 1),
+    // dividing out the common factor first so nothing overflows before the result does
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    let mut c: u128 = 1;
+    for i in 0..k as u128 {
+        let (top, bottom) = (n as u128 - i, i + 1);
+        let g = gcd(c, bottom);
+        c = (c / g).checked_mul(top / (bottom / g))?;
+    }
+    Some(c)
+}
+
+pub fn binomial_big(n: u32, k: u32) -> BigInt {
+    if k > n {
+        return BigInt::zero();
+    }
+    let k = k.min(n - k);
+    let mut c = BigInt::one();
+    for i in 0..k {
+        // Each step divides exactly: C(n, i) (n - i) = C(n, i + 1) (i + 1)
+        let (q, r) = c.mul_small(n - i).div_small(i + 1);
+        debug_assert_eq!(r, 0);
+        c = q;
+    }
+    c
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+pub fn gamma(x: f64) -> f64 {
+    if (1.0..=171.0).contains(&x) && x.fract() == 0.0 {
+        // Gamma(n + 1) = n!, exactly where Lanczos would be off in the last digits
+        factorial_f64(x as u32 - 1)
+    } else if x < 0.5 {
+        // Reflection: Gamma(x) Gamma(1 - x) = pi / sin(pi x)
+        PI / ((PI * x).sin() * gamma(1.0 - x))
+    } else {
+        let x = x - 1.0;
+        let mut a = LANCZOS_COEF[0];
+        let t = x + LANCZOS_G + 0.5;
+        for (i, &c) in LANCZOS_COEF.iter().enumerate().skip(1) {
+            a += c / (x + i as f64);
+        }
+        (2.0 * PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * a
+    }
+}
+
+pub fn ln_gamma(x: f64) -> f64 {
+    // ln|Gamma(x)|, useful when Gamma itself would overflow (x > 171)
+    if x < 0.5 {
+        (PI / (PI * x).sin()).abs().ln() - ln_gamma(1.0 - x)
+    } else {
+        let x = x - 1.0;
+        let mut a = LANCZOS_COEF[0];
+        let t = x + LANCZOS_G + 0.5;
+        for (i, &c) in LANCZOS_COEF.iter().enumerate().skip(1) {
+            a += c / (x + i as f64);
+        }
+        0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+    }
+}
+
+pub fn beta(a: f64, b: f64) -> f64 {
+    // B(a, b) = Gamma(a) Gamma(b) / Gamma(a + b)
+    if a > 0.0 && b > 0.0 {
+        // Through the logs, the gammas on their own overflow long before B does
+        ln_beta(a, b).exp()
+    } else {
+        gamma(a) * gamma(b) / gamma(a + b)
+    }
+}
+
+pub fn ln_beta(a: f64, b: f64) -> f64 {
+    ln_gamma(a) + ln_gamma(b) - ln_gamma(a + b)
+}
+
+fn bessel_j_hankel(n: u32, x: f64) -> f64 {
+    // Hankel's asymptotic expansion for x >> n^2, x > 0:
+    //     J_n(x) ~ sqrt(2 / (pi x)) (P cos(chi) - Q sin(chi)),  chi = x - n pi / 2 - pi / 4
+    // P and Q take the even and odd terms of sum_k (+-) a_k / x^k with
+    // a_k = (mu - 1)(mu - 9) ... (mu - (2k - 1)^2) / (k! 8^k), mu = 4 n^2. The series
+    // diverges, so it stops at the first term that no longer shrinks
+    let mu = 4.0 * (n as f64).powi(2);
+    let (mut p, mut q) = (1.0, 0.0);
+    let mut term: f64 = 1.0;
+    for k in 1..60 {
+        let next = term * (mu - ((2 * k - 1) as f64).powi(2)) / (k as f64 * 8.0 * x);
+        if next.abs() >= term.abs() || next.abs() < 1e-17 {
+            break;
+        }
+        term = next;
+        let sign = if (k / 2) % 2 == 0 { 1.0 } else { -1.0 };
+        if k % 2 == 0 { p += sign * term } else { q += sign * term }
+    }
+    // cos and sin of x - phi from the angle sum, as x - phi rounds badly for big x
+    let phi = (2 * (n % 4) + 1) as f64 * PI / 4.0;
+    let (cos_chi, sin_chi) = (x.cos() * phi.cos() + x.sin() * phi.sin(), x.sin() * phi.cos() - x.cos() * phi.sin());
+    (2.0 / (PI * x)).sqrt() * (p * cos_chi - q * sin_chi)
+}
+
+pub fn bessel_j(n: i32, x: f64) -> f64 {
+    // Bessel function of the first kind J_n(x) for integer order, from the integral
+    //     J_n(x) = 1/pi * int_0^pi cos(n t - x sin t) dt
+    // The integrand is smooth and periodic so the trapezoidal rule converges exponentially
+    // once we have more points than the "bandwidth" |x| + |n|. Past |x| = n^2 + 30 that
+    // would be slow for nothing: Hankel's expansion is already at full precision there
+    if !x.is_finite() {
+        return f64::NAN;
+    }
+    let order = n.unsigned_abs();
+    if x.abs() > (order as f64).powi(2) + 30.0 {
+        // J_(-n)(x) = J_n(-x) = (-1)^n J_n(x)
+        let flips = usize::from(n < 0) + usize::from(x < 0.0);
+        let sign = if n % 2 != 0 && flips == 1 { -1.0 } else { 1.0 };
+        return sign * bessel_j_hankel(order, x.abs());
+    }
+    let n_pts = (x.abs() + order as f64) as usize + 32;
+    let h = PI / n_pts as f64;
+    let f = |t: f64| (n as f64 * t - x * t.sin()).cos();
+
+    let mut sum = 0.5 * (f(0.0) + f(PI));
+    for k in 1..n_pts {
+        sum += f(k as f64 * h);
+    }
+    sum * h / PI
+}
+
+pub fn jinc(x: f64) -> f64 {
+    // 2 J_1(x) / x, the amplitude of the Airy pattern, 1 at x = 0
+    if x.abs() < 1e-8 {
+        1.0 - x * x / 8.0
+    } else {
+        2.0 * bessel_j(1, x) / x
+    }
+}
+
+pub fn airy(x: f64) -> f64 {
+    // Airy pattern intensity normalised to 1 at the centre. For a circular pupil of
+    // diameter D, x = pi D sin(angle) / wavelength; the first dark ring is at x = 3.8317
+    jinc(x).powi(2)
+}
@@ -0,0 +1,335 @@
+// Descriptive statistics on slices of numbers (pass an Array with a.as_slice())
+//
+// Every result is an f64, whatever the input type, so the mean of [1, 2] is 1.5 and not
+// the 1 an integer `sum / len` gives. Sums are compensated (Neumaier's variant of Kahan
+// summation) and variances are taken around the mean, never as sum(x^2) - n mean^2, which
+// loses every digit when the mean is large compared to the spread.
+// Welford keeps a running mean and variance without storing the data, and two of them can
+// be merged, e.g. one per thread. Empty input gives NaN, like the reductions in reduce.rs.
+
+use std::fmt::Debug;
+
+pub trait Sample: Copy + Debug {
+    // Anything that converts to f64: the integer and float primitives
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_sample {
+    ($($t:ty),*) => {
+        $(
+            impl Sample for $t {
+                fn to_f64(self) -> f64 { self as f64 }
+            }
+        )*
+    };
+}
+
+impl_sample!(f64, f32, i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Neumaier {
+    sum: f64,
+    compensation: f64,
+}
+
+impl Neumaier {
+    fn add(&mut self, x: f64) {
+        // Keep the low-order bits that sum + x rounds away, whichever of the two is bigger
+        let t = self.sum + x;
+        if self.sum.abs() >= x.abs() {
+            self.compensation += (self.sum - t) + x;
+        } else {
+            self.compensation += (x - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    fn total(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+pub fn sum<T: Sample>(x: &[T]) -> f64 {
+    let mut acc = Neumaier::default();
+    for &v in x {
+        acc.add(v.to_f64());
+    }
+    acc.total()
+}
+
+pub fn mean<T: Sample>(x: &[T]) -> f64 {
+    if x.is_empty() {
+        return f64::NAN;
+    }
+    sum(x) / x.len() as f64
+}
+
+pub fn variance<T: Sample>(x: &[T], ddof: usize) -> f64 {
+    // ddof = 0 for the population variance, 1 for the unbiased sample variance
+    if x.len() <= ddof {
+        return f64::NAN;
+    }
+    let m = mean(x);
+    let mut ss = Neumaier::default();
+    let mut dev = Neumaier::default();
+    for &v in x {
+        let d = v.to_f64() - m;
+        ss.add(d * d);
+        dev.add(d);
+    }
+    // The deviations should sum to 0; what's left is the rounding error of the mean, and
+    // this correction removes its effect (the "corrected two-pass" algorithm)
+    let n = x.len() as f64;
+    (ss.total() - dev.total() * dev.total() / n) / (n - ddof as f64)
+}
+
+pub fn std<T: Sample>(x: &[T], ddof: usize) -> f64 {
+    variance(x, ddof).sqrt()
+}
+
+fn sorted<T: Sample>(x: &[T]) -> Vec<f64> {
+    let mut v: Vec<f64> = x.iter().map(|v| v.to_f64()).collect();
+    v.sort_by(|a, b| a.total_cmp(b));
+    v
+}
+
+fn quantile_sorted(v: &[f64], q: f64) -> f64 {
+    // Linear interpolation between the closest ranks, numpy's default: position q (n - 1)
+    assert!((0.0..=1.0).contains(&q), "quantile must be in [0, 1], got {}", q);
+    if v.is_empty() {
+        return f64::NAN;
+    }
+    let pos = q * (v.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    v[lo] + (pos - lo as f64) * (v[hi] - v[lo])
+}
+
+pub fn quantile<T: Sample>(x: &[T], q: f64) -> f64 {
+    quantile_sorted(&sorted(x), q)
+}
+
+pub fn quantiles<T: Sample>(x: &[T], qs: &[f64]) -> Vec<f64> {
+    // Several quantiles with a single sort
+    let v = sorted(x);
+    qs.iter().map(|&q| quantile_sorted(&v, q)).collect()
+}
+
+pub fn median<T: Sample>(x: &[T]) -> f64 {
+    // The middle value, or the average of the two middle ones
+    quantile(x, 0.5)
+}
+
+pub fn min_max<T: Sample>(x: &[T]) -> Option<(f64, f64)> {
+    let v: Vec<f64> = x.iter().map(|v| v.to_f64()).collect();
+    let first = *v.first()?;
+    Some(v.iter().fold((first, first), |(lo, hi), &a| (lo.min(a), hi.max(a))))
+}
+
+// Weighted statistics. The weights are frequency-like (non-negative, any scale)
+
+pub fn weighted_mean<T: Sample, W: Sample>(x: &[T], w: &[W]) -> f64 {
+    assert_eq!(x.len(), w.len(), "one weight per value");
+    let mut top = Neumaier::default();
+    let mut bottom = Neumaier::default();
+    for (&v, &wi) in x.iter().zip(w.iter()) {
+        top.add(wi.to_f64() * v.to_f64());
+        bottom.add(wi.to_f64());
+    }
+    top.total() / bottom.total()
+}
+
+pub fn weighted_variance<T: Sample, W: Sample>(x: &[T], w: &[W]) -> f64 {
+    // sum w (x - mean)^2 / sum w, the weighted population variance
+    let m = weighted_mean(x, w);
+    let mut top = Neumaier::default();
+    let mut bottom = Neumaier::default();
+    for (&v, &wi) in x.iter().zip(w.iter()) {
+        let d = v.to_f64() - m;
+        top.add(wi.to_f64() * d * d);
+        bottom.add(wi.to_f64());
+    }
+    top.total() / bottom.total()
+}
+
+pub fn weighted_std<T: Sample, W: Sample>(x: &[T], w: &[W]) -> f64 {
+    weighted_variance(x, w).sqrt()
+}
+
+// Histograms
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    pub edges: Vec<f64>, // bins + 1 edges, ascending
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn centers(&self) -> Vec<f64> {
+        self.edges.windows(2).map(|e| 0.5 * (e[0] + e[1])).collect()
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    pub fn density(&self) -> Vec<f64> {
+        // Counts normalised so that the histogram integrates to 1
+        let total = self.total() as f64;
+        self.counts.iter().zip(self.edges.windows(2)).map(|(&c, e)| c as f64 / (total * (e[1] - e[0]))).collect()
+    }
+}
+
+pub fn histogram<T: Sample>(x: &[T], bins: usize, range: Option<(f64, f64)>) -> Histogram {
+    // `bins` equal bins over `range` (default: min to max of the data). Like numpy, each
+    // bin is [left, right) except the last one which includes its right edge; values
+    // outside the range and NaNs are not counted
+    assert!(bins > 0, "a histogram needs at least one bin");
+    let (lo, hi) = range.or_else(|| min_max(x)).unwrap_or((0.0, 1.0));
+    // All the data equal: widen to a unit range around it, as numpy does
+    let (lo, hi) = if lo == hi { (lo - 0.5, hi + 0.5) } else { (lo, hi) };
+    let width = (hi - lo) / bins as f64;
+    let edges: Vec<f64> = (0..=bins).map(|k| if k == bins { hi } else { lo + k as f64 * width }).collect();
+    let mut counts = vec![0; bins];
+    for &v in x {
+        let v = v.to_f64();
+        if !(lo..=hi).contains(&v) {
+            continue;
+        }
+        let mut k = (((v - lo) / width) as usize).min(bins - 1);
+        // The division can land one bin off next to an edge
+        if v < edges[k] {
+            k -= 1;
+        } else if v >= edges[k + 1] && k + 1 < bins {
+            k += 1;
+        }
+        counts[k] += 1;
+    }
+    Histogram { edges, counts }
+}
+
+// Online accumulators
+
+#[derive(Clone, Copy, Debug)]
+pub struct Welford {
+    count: usize,
+    mean: f64,
+    m2: f64, // sum of squared deviations from the current mean
+    min: f64,
+    max: f64,
+}
+
+impl Default for Welford {
+    fn default() -> Self {
+        Welford::new()
+    }
+}
+
+impl Welford {
+    pub fn new() -> Self {
+        Welford { count: 0, mean: 0.0, m2: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+
+    pub fn push<T: Sample>(&mut self, x: T) {
+        // Each new value moves the mean by delta / n, and adds delta * (x - new mean) to m2
+        let x = x.to_f64();
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    pub fn extend<T: Sample>(&mut self, xs: &[T]) {
+        for &x in xs {
+            self.push(x);
+        }
+    }
+
+    pub fn merge(&self, other: &Welford) -> Welford {
+        // Combine two accumulators as if all the values went through one (Chan et al.)
+        if self.count == 0 {
+            return *other;
+        }
+        if other.count == 0 {
+            return *self;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let (na, nb, n) = (self.count as f64, other.count as f64, count as f64);
+        Welford {
+            count,
+            mean: self.mean + delta * nb / n,
+            m2: self.m2 + other.m2 + delta * delta * na * nb / n,
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.mean }
+    }
+
+    pub fn variance(&self, ddof: usize) -> f64 {
+        if self.count <= ddof { f64::NAN } else { self.m2 / (self.count - ddof) as f64 }
+    }
+
+    pub fn std(&self, ddof: usize) -> f64 {
+        self.variance(ddof).sqrt()
+    }
+
+    pub fn min(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.min }
+    }
+
+    pub fn max(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.max }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WeightedWelford {
+    weight_sum: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl WeightedWelford {
+    pub fn new() -> Self {
+        WeightedWelford::default()
+    }
+
+    pub fn push<T: Sample>(&mut self, x: T, w: f64) {
+        // West's weighted version of the same update
+        if w == 0.0 {
+            return;
+        }
+        let x = x.to_f64();
+        self.weight_sum += w;
+        let delta = x - self.mean;
+        self.mean += delta * w / self.weight_sum;
+        self.m2 += w * delta * (x - self.mean);
+    }
+
+    pub fn weight_sum(&self) -> f64 {
+        self.weight_sum
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.weight_sum == 0.0 { f64::NAN } else { self.mean }
+    }
+
+    pub fn variance(&self) -> f64 {
+        // Weighted population variance, matches weighted_variance
+        if self.weight_sum == 0.0 { f64::NAN } else { self.m2 / self.weight_sum }
+    }
+
+    pub fn std(&self) -> f64 {
+        self.variance().sqrt()
+    }
+}
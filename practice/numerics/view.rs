@@ -0,0 +1,574 @@
+// Borrowed views of an Array: slices with steps, transposes and row / column iterators
+// without copying the data, like numpy's a[:, 1::2] or a.T
+//
+// A view is a shape and strides over a borrowed slice of the array data, so a transpose
+// only swaps the strides and a[.., 1..;2] only doubles the column stride. It is the
+// N-dimensional &arr[0..2] of 2-arrays/21_arrays.rs: indexing past the end of the view is
+// an error (or None from get), never a read of whatever comes next in memory.
+//
+// The s![] macro writes the slice arguments, one per axis (missing axes are taken whole):
+//     s![.., 1..;2]    all the rows, every other column from column 1   (numpy a[:, 1::2])
+//     s![2, ..]        row 2 as a 1D view, the indexed axis disappears  (numpy a[2, :])
+//     s![1..=3, ..;3]  rows 1 to 3, every third column
+// Ranges past the end are clipped like in numpy, single indices past the end are errors.
+
+use crate::numerics::array::{row_major_strides, Array, ShapeError};
+use std::ops::{Index, IndexMut, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SliceArg {
+    Index(usize),                                       // Pick one element and drop the axis
+    Range { start: usize, end: Option<usize>, step: usize }, // end = None is "to the end"
+}
+
+impl SliceArg {
+    pub fn with_step(self, new_step: usize) -> SliceArg {
+        assert!(new_step > 0, "slice step must be positive (got 0)");
+        match self {
+            SliceArg::Range { start, end, .. } => SliceArg::Range { start, end, step: new_step },
+            SliceArg::Index(i) => panic!("a step needs a range, not the single index {}", i),
+        }
+    }
+}
+
+// What can go in s![]: a single index or any kind of range
+impl From<usize> for SliceArg {
+    fn from(i: usize) -> Self {
+        SliceArg::Index(i)
+    }
+}
+
+impl From<Range<usize>> for SliceArg {
+    fn from(r: Range<usize>) -> Self {
+        SliceArg::Range { start: r.start, end: Some(r.end), step: 1 }
+    }
+}
+
+impl From<RangeInclusive<usize>> for SliceArg {
+    fn from(r: RangeInclusive<usize>) -> Self {
+        SliceArg::Range { start: *r.start(), end: Some(*r.end() + 1), step: 1 }
+    }
+}
+
+impl From<RangeFrom<usize>> for SliceArg {
+    fn from(r: RangeFrom<usize>) -> Self {
+        SliceArg::Range { start: r.start, end: None, step: 1 }
+    }
+}
+
+impl From<RangeTo<usize>> for SliceArg {
+    fn from(r: RangeTo<usize>) -> Self {
+        SliceArg::Range { start: 0, end: Some(r.end), step: 1 }
+    }
+}
+
+impl From<RangeToInclusive<usize>> for SliceArg {
+    fn from(r: RangeToInclusive<usize>) -> Self {
+        SliceArg::Range { start: 0, end: Some(r.end + 1), step: 1 }
+    }
+}
+
+impl From<RangeFull> for SliceArg {
+    fn from(_: RangeFull) -> Self {
+        SliceArg::Range { start: 0, end: None, step: 1 }
+    }
+}
+
+#[macro_export]
+macro_rules! s {
+    ($($r:expr $(; $step:expr)?),* $(,)?) => {
+        [$($crate::numerics::view::SliceArg::from($r)$(.with_step($step))?),*]
+    };
+}
+
+fn extent(shape: &[usize], strides: &[usize]) -> usize {
+    // How many elements of the data slice a layout spans, from the first to the last one
+    if shape.contains(&0) {
+        return 0;
+    }
+    1 + shape.iter().zip(strides).map(|(&n, &s)| (n - 1) * s).sum::<usize>()
+}
+
+fn slice_layout(shape: &[usize], strides: &[usize], args: &[SliceArg]) -> Result<(usize, Vec<usize>, Vec<usize>), ShapeError> {
+    // Offset of the first element, shape and strides of a sliced layout
+    if args.len() > shape.len() {
+        return Err(ShapeError::AxisOutOfBounds { axis: args.len() - 1, ndim: shape.len() });
+    }
+    let mut offset = 0;
+    let mut new_shape = Vec::new();
+    let mut new_strides = Vec::new();
+    for (axis, (&n, &s)) in shape.iter().zip(strides).enumerate() {
+        match args.get(axis) {
+            Some(&SliceArg::Index(i)) => {
+                if i >= n {
+                    return Err(ShapeError::OutOfBounds { axis, index: i, len: n });
+                }
+                offset += i * s;
+            }
+            Some(&SliceArg::Range { start, end, step }) => {
+                let end = end.unwrap_or(n).min(n);
+                let start = start.min(end);
+                offset += start * s;
+                new_shape.push((end - start).div_ceil(step));
+                new_strides.push(s * step);
+            }
+            None => {
+                new_shape.push(n);
+                new_strides.push(s);
+            }
+        }
+    }
+    Ok((offset, new_shape, new_strides))
+}
+
+fn permuted(shape: &[usize], strides: &[usize], axes: &[usize]) -> Result<(Vec<usize>, Vec<usize>), ShapeError> {
+    // axes must list every axis exactly once
+    let mut seen = vec![false; shape.len()];
+    for &a in axes {
+        if a >= shape.len() || seen[a] {
+            return Err(ShapeError::Incompatible { left: shape.to_vec(), right: axes.to_vec() });
+        }
+        seen[a] = true;
+    }
+    if axes.len() != shape.len() {
+        return Err(ShapeError::Incompatible { left: shape.to_vec(), right: axes.to_vec() });
+    }
+    Ok((axes.iter().map(|&a| shape[a]).collect(), axes.iter().map(|&a| strides[a]).collect()))
+}
+
+fn layout_offset(shape: &[usize], strides: &[usize], index: &[usize]) -> Option<usize> {
+    if index.len() != shape.len() || index.iter().zip(shape).any(|(&i, &n)| i >= n) {
+        return None;
+    }
+    Some(index.iter().zip(strides).map(|(&i, &s)| i * s).sum())
+}
+
+// Data offsets of a layout, visited in row-major order of the view (last axis fastest)
+#[derive(Clone, Debug)]
+pub struct Offsets {
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    index: Vec<usize>,
+    offset: usize,
+    remaining: usize,
+}
+
+impl Offsets {
+    fn new(shape: &[usize], strides: &[usize]) -> Self {
+        Offsets {
+            shape: shape.to_vec(),
+            strides: strides.to_vec(),
+            index: vec![0; shape.len()],
+            offset: 0,
+            remaining: shape.iter().product(),
+        }
+    }
+}
+
+impl Iterator for Offsets {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let current = self.offset;
+        self.remaining -= 1;
+        // Odometer increment, like broadcast_offsets
+        for k in (0..self.shape.len()).rev() {
+            self.index[k] += 1;
+            self.offset += self.strides[k];
+            if self.index[k] < self.shape[k] {
+                break;
+            }
+            self.offset -= self.strides[k] * self.index[k];
+            self.index[k] = 0;
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Offsets {}
+
+#[derive(Debug)]
+pub struct ArrayView<'a, T> {
+    data: &'a [T],          // Starts at the first element of the view
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+}
+
+// Written by hand: #[derive(Clone)] would ask for T: Clone, but we only copy a reference
+impl<T> Clone for ArrayView<'_, T> {
+    fn clone(&self) -> Self {
+        ArrayView { data: self.data, shape: self.shape.clone(), strides: self.strides.clone() }
+    }
+}
+
+impl<'a, T> ArrayView<'a, T> {
+    fn from_layout(data: &'a [T], offset: usize, shape: Vec<usize>, strides: Vec<usize>) -> Self {
+        let n = extent(&shape, &strides);
+        let data = if n == 0 { &data[..0] } else { &data[offset..offset + n] };
+        ArrayView { data, shape, strides }
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: &[usize]) -> Option<&'a T> {
+        layout_offset(&self.shape, &self.strides, index).map(|k| &self.data[k])
+    }
+
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        // The elements as one slice, only if they are contiguous and in row-major order
+        // (axes of length 1 can have any stride, they are never stepped along)
+        let expected = row_major_strides(&self.shape);
+        let contiguous = (0..self.ndim()).all(|k| self.shape[k] <= 1 || self.strides[k] == expected[k]);
+        if contiguous {
+            Some(&self.data[..self.len()])
+        } else {
+            None
+        }
+    }
+
+    pub fn slice(&self, args: &[SliceArg]) -> Result<ArrayView<'a, T>, ShapeError> {
+        let (offset, shape, strides) = slice_layout(&self.shape, &self.strides, args)?;
+        Ok(ArrayView::from_layout(self.data, offset, shape, strides))
+    }
+
+    pub fn t(&self) -> ArrayView<'a, T> {
+        // Transpose: the axes in reverse order, [n, m] -> [m, n]
+        let shape = self.shape.iter().rev().cloned().collect();
+        let strides = self.strides.iter().rev().cloned().collect();
+        ArrayView { data: self.data, shape, strides }
+    }
+
+    pub fn permuted_axes(&self, axes: &[usize]) -> Result<ArrayView<'a, T>, ShapeError> {
+        // Axis k of the result is axis axes[k] of self, numpy's a.transpose(axes)
+        let (shape, strides) = permuted(&self.shape, &self.strides, axes)?;
+        Ok(ArrayView { data: self.data, shape, strides })
+    }
+
+    pub fn index_axis(&self, axis: usize, index: usize) -> Result<ArrayView<'a, T>, ShapeError> {
+        // The subview at `index` along `axis`, one dimension less (a row of a matrix, ...)
+        if axis >= self.ndim() {
+            return Err(ShapeError::AxisOutOfBounds { axis, ndim: self.ndim() });
+        }
+        let mut args = vec![SliceArg::from(..); axis];
+        args.push(SliceArg::Index(index));
+        self.slice(&args)
+    }
+
+    pub fn iter(&self) -> Iter<'a, T> {
+        Iter { data: self.data, offsets: Offsets::new(&self.shape, &self.strides) }
+    }
+
+    pub fn axis_iter(&self, axis: usize) -> Result<AxisIter<'a, T>, ShapeError> {
+        // Subviews along `axis`, one per index: axis_iter(0) of a matrix gives its rows
+        if axis >= self.ndim() {
+            return Err(ShapeError::AxisOutOfBounds { axis, ndim: self.ndim() });
+        }
+        Ok(AxisIter { view: self.clone(), axis, next: 0 })
+    }
+
+    pub fn rows(&self) -> AxisIter<'a, T> {
+        self.axis_iter(0).expect("rows() needs at least one dimension")
+    }
+
+    pub fn columns(&self) -> AxisIter<'a, T> {
+        assert!(self.ndim() == 2, "columns() needs a 2D view, this one has shape {:?}", self.shape);
+        self.axis_iter(1).unwrap()
+    }
+}
+
+impl<T: Clone> ArrayView<'_, T> {
+    pub fn to_owned(&self) -> Array<T> {
+        // A contiguous copy, in the row-major order of the view
+        Array::from_shape_vec(&self.shape, self.iter().cloned().collect()).unwrap()
+    }
+}
+
+pub struct Iter<'a, T> {
+    data: &'a [T],
+    offsets: Offsets,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.offsets.next().map(|k| &self.data[k])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.offsets.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct AxisIter<'a, T> {
+    view: ArrayView<'a, T>,
+    axis: usize,
+    next: usize,
+}
+
+impl<'a, T> Iterator for AxisIter<'a, T> {
+    type Item = ArrayView<'a, T>;
+
+    fn next(&mut self) -> Option<ArrayView<'a, T>> {
+        let sub = self.view.index_axis(self.axis, self.next).ok()?;
+        self.next += 1;
+        Some(sub)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.view.shape[self.axis] - self.next;
+        (n, Some(n))
+    }
+}
+
+impl<T> ExactSizeIterator for AxisIter<'_, T> {}
+
+impl<T> Index<&[usize]> for ArrayView<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: &[usize]) -> &T {
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {:?} is out of bounds for a view of shape {:?}", index, self.shape))
+    }
+}
+
+impl<T> Index<(usize, usize)> for ArrayView<'_, T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self[&[i, j][..]]
+    }
+}
+
+// Mutable views. They borrow the data mutably, so there is only ever one of them over a
+// given element; split_at cuts one in two non-overlapping halves that can go to different
+// threads (see p13_views.rs)
+#[derive(Debug)]
+pub struct ArrayViewMut<'a, T> {
+    data: &'a mut [T],
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+}
+
+impl<'a, T> ArrayViewMut<'a, T> {
+    fn from_layout(data: &'a mut [T], offset: usize, shape: Vec<usize>, strides: Vec<usize>) -> Self {
+        let n = extent(&shape, &strides);
+        let data = if n == 0 { &mut data[..0] } else { &mut data[offset..offset + n] };
+        ArrayViewMut { data, shape, strides }
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.shape.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn view(&self) -> ArrayView<'_, T> {
+        ArrayView { data: self.data, shape: self.shape.clone(), strides: self.strides.clone() }
+    }
+
+    pub fn get(&self, index: &[usize]) -> Option<&T> {
+        layout_offset(&self.shape, &self.strides, index).map(|k| &self.data[k])
+    }
+
+    pub fn get_mut(&mut self, index: &[usize]) -> Option<&mut T> {
+        layout_offset(&self.shape, &self.strides, index).map(move |k| &mut self.data[k])
+    }
+
+    pub fn slice_mut(&mut self, args: &[SliceArg]) -> Result<ArrayViewMut<'_, T>, ShapeError> {
+        let (offset, shape, strides) = slice_layout(&self.shape, &self.strides, args)?;
+        Ok(ArrayViewMut::from_layout(self.data, offset, shape, strides))
+    }
+
+    pub fn into_slice(self, args: &[SliceArg]) -> Result<ArrayViewMut<'a, T>, ShapeError> {
+        // Same as slice_mut, but keeps the original borrow
+        let (offset, shape, strides) = slice_layout(&self.shape, &self.strides, args)?;
+        Ok(ArrayViewMut::from_layout(self.data, offset, shape, strides))
+    }
+
+    pub fn t(self) -> ArrayViewMut<'a, T> {
+        let shape = self.shape.iter().rev().cloned().collect();
+        let strides = self.strides.iter().rev().cloned().collect();
+        ArrayViewMut { data: self.data, shape, strides }
+    }
+
+    pub fn map_inplace<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        for k in Offsets::new(&self.shape, &self.strides) {
+            f(&mut self.data[k]);
+        }
+    }
+
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        self.map_inplace(|x| *x = value.clone());
+    }
+
+    pub fn assign(&mut self, other: &ArrayView<'_, T>) -> Result<(), ShapeError>
+    where
+        T: Clone,
+    {
+        // Copy the elements of a view with the same shape into this one
+        if self.shape != other.shape {
+            return Err(ShapeError::Incompatible { left: self.shape.clone(), right: other.shape.clone() });
+        }
+        let mut values = other.iter();
+        self.map_inplace(|x| *x = values.next().unwrap().clone());
+        Ok(())
+    }
+
+    pub fn split_at(self, axis: usize, index: usize) -> Result<(ArrayViewMut<'a, T>, ArrayViewMut<'a, T>), ShapeError> {
+        // [..index] and [index..] along axis, as two views that own their half of the borrow.
+        // This only works when the halves are separate runs of memory, i.e. when the other
+        // axes fit inside one step along `axis`: a matrix splits by rows, its transpose by
+        // columns, and a view with every other row split along the columns gets Overlapping
+        if axis >= self.ndim() {
+            return Err(ShapeError::AxisOutOfBounds { axis, ndim: self.ndim() });
+        }
+        let n = self.shape[axis];
+        if index > n {
+            return Err(ShapeError::OutOfBounds { axis, index, len: n });
+        }
+        let step = self.strides[axis];
+        let others: usize =
+            (0..self.ndim()).filter(|&k| k != axis).map(|k| self.shape[k].saturating_sub(1) * self.strides[k]).sum();
+        if others >= step && !self.is_empty() {
+            return Err(ShapeError::Overlapping { axis });
+        }
+        let mut first_shape = self.shape.clone();
+        first_shape[axis] = index;
+        let mut second_shape = self.shape.clone();
+        second_shape[axis] = n - index;
+        // An empty half gets no memory and the other one all of it, whatever the step
+        let cut = if index == n { self.data.len() } else { index * step };
+        let (head, tail) = self.data.split_at_mut(cut);
+        Ok((
+            ArrayViewMut::from_layout(head, 0, first_shape, self.strides.clone()),
+            ArrayViewMut::from_layout(tail, 0, second_shape, self.strides),
+        ))
+    }
+
+    pub fn split_chunks(self, axis: usize, n_chunks: usize) -> Result<Vec<ArrayViewMut<'a, T>>, ShapeError> {
+        // n_chunks views of (nearly) equal size along axis, e.g. one per thread
+        assert!(n_chunks > 0, "split_chunks needs at least one chunk");
+        if axis >= self.ndim() {
+            return Err(ShapeError::AxisOutOfBounds { axis, ndim: self.ndim() });
+        }
+        let n = self.shape[axis];
+        let mut chunks = Vec::with_capacity(n_chunks);
+        let mut rest = self;
+        for k in 0..n_chunks - 1 {
+            // Sizes n / n_chunks, rounded up for the first n % n_chunks chunks
+            let size = n / n_chunks + usize::from(k < n % n_chunks);
+            let (chunk, tail) = rest.split_at(axis, size)?;
+            chunks.push(chunk);
+            rest = tail;
+        }
+        chunks.push(rest);
+        Ok(chunks)
+    }
+}
+
+impl<T> Index<&[usize]> for ArrayViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: &[usize]) -> &T {
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {:?} is out of bounds for a view of shape {:?}", index, self.shape))
+    }
+}
+
+impl<T> IndexMut<&[usize]> for ArrayViewMut<'_, T> {
+    fn index_mut(&mut self, index: &[usize]) -> &mut T {
+        let shape = self.shape.clone();
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("index {:?} is out of bounds for a view of shape {:?}", index, shape))
+    }
+}
+
+impl<T> Index<(usize, usize)> for ArrayViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self[&[i, j][..]]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for ArrayViewMut<'_, T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        &mut self[&[i, j][..]]
+    }
+}
+
+impl<T> Array<T> {
+    pub fn view(&self) -> ArrayView<'_, T> {
+        ArrayView { data: self.as_slice(), shape: self.shape().to_vec(), strides: self.strides().to_vec() }
+    }
+
+    pub fn view_mut(&mut self) -> ArrayViewMut<'_, T> {
+        let (shape, strides) = (self.shape().to_vec(), self.strides().to_vec());
+        ArrayViewMut { data: self.as_mut_slice(), shape, strides }
+    }
+
+    pub fn slice(&self, args: &[SliceArg]) -> Result<ArrayView<'_, T>, ShapeError> {
+        self.view().slice(args)
+    }
+
+    pub fn slice_mut(&mut self, args: &[SliceArg]) -> Result<ArrayViewMut<'_, T>, ShapeError> {
+        self.view_mut().into_slice(args)
+    }
+
+    pub fn t(&self) -> ArrayView<'_, T> {
+        self.view().t()
+    }
+
+    pub fn rows(&self) -> AxisIter<'_, T> {
+        self.view().rows()
+    }
+
+    pub fn columns(&self) -> AxisIter<'_, T> {
+        self.view().columns()
+    }
+}
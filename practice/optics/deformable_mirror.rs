@@ -0,0 +1,126 @@
+// Deformable mirror: actuators, influence functions and the control matrix
+//
+// Each actuator pushes the surface with a Gaussian influence function
+//     IF(d) = coupling^((d / pitch)^2)
+// where d is the distance to the actuator, so the neighbouring actuator sees `coupling`
+// (0.1 - 0.2 for most continuous face-sheet mirrors). The surface is sampled on the
+// in-pupil pixels of a PupilGrid (in the order of its Aperture) and is expressed
+// in the same units as the Zernike coefficients (radians of phase).
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::linalg::{self, LinalgError};
+use crate::numerics::sparse::{self, CsrMatrix};
+use crate::optics::pupil::PupilGrid;
+use crate::optics::zernike::Zernike;
+
+pub struct ActuatorLayout {
+    pub positions: Vec<(f64, f64)>,     // In pupil radii
+    pub pitch: f64,
+}
+
+impl ActuatorLayout {
+    pub fn square(n_across: usize) -> Self {
+        // n_across actuators along the diameter, corners of the lenslets in Fried geometry.
+        // Keeps the ones within half a pitch of the pupil edge, they still shape the edge
+        assert!(n_across >= 2, "A square layout needs at least 2 actuators across, got {}", n_across);
+        let pitch = 2.0 / (n_across - 1) as f64;
+        let mut positions = Vec::new();
+        for iy in 0..n_across {
+            for ix in 0..n_across {
+                let (x, y) = (-1.0 + ix as f64 * pitch, -1.0 + iy as f64 * pitch);
+                if x.hypot(y) <= 1.0 + pitch / 2.0 {
+                    positions.push((x, y));
+                }
+            }
+        }
+        ActuatorLayout { positions, pitch }
+    }
+
+    pub fn hexagonal(n_rings: usize) -> Self {
+        // A central actuator plus n_rings hexagonal rings (6k actuators in ring k),
+        // the outer ring sits on the pupil edge
+        assert!(n_rings >= 1, "A hexagonal layout needs at least 1 ring around the centre");
+        let pitch = 1.0 / n_rings as f64;
+        let n = n_rings as i32;
+        let mut positions = Vec::new();
+        for q in -n..=n {
+            for r in -n..=n {
+                if (q + r).abs() <= n {
+                    let x = pitch * (q as f64 + r as f64 / 2.0);
+                    let y = pitch * r as f64 * 3f64.sqrt() / 2.0;
+                    positions.push((x, y));
+                }
+            }
+        }
+        ActuatorLayout { positions, pitch }
+    }
+
+    pub fn n_actuators(&self) -> usize {
+        self.positions.len()
+    }
+}
+
+pub struct DeformableMirror {
+    pub layout: ActuatorLayout,
+    pub coupling: f64,
+    pub influence: Array<f64>,      // [pixel, actuator]
+    pub commands: Vec<f64>,
+}
+
+impl DeformableMirror {
+    pub fn new(layout: ActuatorLayout, coupling: f64, grid: &PupilGrid) -> Self {
+        let (rho, theta) = (&grid.aperture.rho, &grid.aperture.theta);
+        let log_c = coupling.ln() / (layout.pitch * layout.pitch);
+        let influence = rho
+            .iter()
+            .zip(theta.iter())
+            .flat_map(|(&r, &t)| {
+                let (x, y) = (r * t.cos(), r * t.sin());
+                layout.positions.iter().map(move |&(ax, ay)| (log_c * ((x - ax).powi(2) + (y - ay).powi(2))).exp())
+            })
+            .collect();
+        let influence = Array::from_shape_vec(&[rho.len(), layout.n_actuators()], influence).unwrap();
+        let commands = vec![0.0; layout.n_actuators()];
+        DeformableMirror { layout, coupling, influence, commands }
+    }
+
+    pub fn surface(&self) -> Vec<f64> {
+        linalg::mat_vec(&self.influence, &self.commands)
+    }
+
+    pub fn zernike_projection(&self, grid: &PupilGrid, n_zern: usize) -> Array<f64> {
+        // Zernike content of every influence function: [mode, actuator], a least squares fit
+        // of the first n_zern Noll modes to each column of the influence matrix
+        let (rho, theta) = (&grid.aperture.rho, &grid.aperture.theta);
+        let zern = Zernike::new();
+        let columns: Vec<Vec<f64>> = (1..=n_zern).map(|j| zern.z_j(j, rho, theta, "Standard")).collect();
+        let basis = linalg::transpose(&Array::from_rows(columns).unwrap());
+        let fit = linalg::least_squares_matrix(&basis)
+            .expect("Not enough pupil pixels for that many modes");
+        linalg::mat_mul(&fit, &self.influence)
+    }
+
+    pub fn control_matrix(&self, grid: &PupilGrid, n_zern: usize, rcond: f64) -> Array<f64> {
+        // Maps a Zernike coefficient vector to the actuator commands that produce it:
+        // [actuator, mode], the truncated pseudo-inverse of the projection
+        linalg::pseudo_inverse(&self.zernike_projection(grid, n_zern), rcond).unwrap()
+    }
+
+    pub fn sparse_influence(&self, threshold: f64) -> CsrMatrix {
+        // The influence matrix without the values below threshold (the peak is 1): a pixel
+        // only feels the few actuators around it, so most of the dense matrix is ~0
+        CsrMatrix::from_dense(&self.influence, threshold).unwrap()
+    }
+
+    pub fn fit_commands(&self, surface: &[f64], threshold: f64, damp: f64) -> Result<Vec<f64>, LinalgError> {
+        // Commands whose surface is closest to `surface` (in-pupil pixels) in the least
+        // squares sense, with LSQR on the sparse influence matrix. damp > 0 keeps the
+        // actuators outside the pupil, which barely show in it, from running away
+        let pixels = self.influence.shape()[0];
+        if surface.len() != pixels {
+            return Err(ShapeError::SizeMismatch { shape: vec![pixels], len: surface.len() }.into());
+        }
+        let n = self.layout.n_actuators();
+        Ok(sparse::lsqr(&self.sparse_influence(threshold), surface, damp, 1e-10, 20 * n)?.x)
+    }
+}
@@ -0,0 +1,239 @@
+// Interferograms: simulation, phase-shifting demodulation and phase unwrapping
+//
+// Images are 2D Arrays indexed [(row, col)] on a PupilGrid (see pupil.rs).
+// Wavefront coefficients are in radians of phase, like in turbulence.rs, so a fringe is 2 pi.
+// Outside the pupil the intensity is 0 and the phase maps are NaN.
+
+use crate::numerics::array::Array;
+use crate::numerics::rng::Rng;
+use crate::optics::pupil::PupilGrid;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::PI;
+
+pub struct InterferogramSimulator {
+    // I = background + modulation * cos(W + carrier + shift) + noise
+    pub grid: PupilGrid,
+    pub carrier: (f64, f64),    // Tilt carrier, in fringes across the pupil diameter along x and y
+    pub background: f64,
+    pub modulation: f64,
+    pub noise_rms: f64,
+    rng: Rng,
+}
+
+impl InterferogramSimulator {
+    pub fn new(n_pix: usize, seed: u64) -> Self {
+        InterferogramSimulator {
+            grid: PupilGrid::new(n_pix),
+            carrier: (0.0, 0.0),
+            background: 1.0,
+            modulation: 0.8,
+            noise_rms: 0.0,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn carrier_coefficients(&self) -> Vec<f64> {
+        // The carrier as Zernike tilts: f fringes over the diameter is a phase pi f x,
+        // and Z2 = 2 x, Z3 = 2 y with the Noll normalisation
+        vec![0.0, PI * self.carrier.0 / 2.0, PI * self.carrier.1 / 2.0]
+    }
+
+    pub fn render(&mut self, coef: &[f64], phase_shift: f64) -> Array<f64> {
+        let mut total = coef.to_vec();
+        for (k, c) in self.carrier_coefficients().into_iter().enumerate() {
+            if k >= total.len() {
+                total.push(0.0);
+            }
+            total[k] += c;
+        }
+        let phase = self.grid.wavefront(&total);
+
+        let mut image = Array::zeros(phase.shape());
+        for (pix, &p) in image.iter_mut().zip(phase.iter()) {
+            if !p.is_nan() {
+                *pix = self.background + self.modulation * (p + phase_shift).cos() + self.noise_rms * self.rng.normal();
+            }
+        }
+        image
+    }
+
+    pub fn phase_shifted(&mut self, coef: &[f64], shifts: &[f64]) -> Vec<Array<f64>> {
+        shifts.iter().map(|&s| self.render(coef, s)).collect()
+    }
+}
+
+pub fn four_step_shifts() -> Vec<f64> {
+    vec![0.0, PI / 2.0, PI, 3.0 * PI / 2.0]
+}
+
+pub fn hariharan_shifts() -> Vec<f64> {
+    vec![-PI, -PI / 2.0, 0.0, PI / 2.0, PI]
+}
+
+fn demodulate<F>(frames: &[Array<f64>], mask: &Array<bool>, sin_cos: F) -> (Array<f64>, Array<f64>)
+where
+    F: Fn(&[f64]) -> (f64, f64),
+{
+    // Wrapped phase atan2(S, C) and fringe modulation from per-pixel (S, C) estimates
+    for frame in frames {
+        assert_eq!(frame.shape(), mask.shape(), "Every frame must have the shape of the mask");
+    }
+    let mut phase = Array::full(mask.shape(), f64::NAN);
+    let mut modulation = Array::zeros(mask.shape());
+    let mut values = vec![0.0; frames.len()];
+
+    for (k, &inside) in mask.iter().enumerate() {
+        if !inside {
+            continue;
+        }
+        for (v, frame) in values.iter_mut().zip(frames.iter()) {
+            *v = frame.as_slice()[k];
+        }
+        let (s, c) = sin_cos(&values);
+        phase.as_mut_slice()[k] = s.atan2(c);
+        modulation.as_mut_slice()[k] = s.hypot(c);
+    }
+    (phase, modulation)
+}
+
+pub fn four_step(frames: &[Array<f64>], mask: &Array<bool>) -> (Array<f64>, Array<f64>) {
+    // Shifts 0, pi/2, pi, 3pi/2: I3 - I1 = 2B sin(phi), I0 - I2 = 2B cos(phi)
+    assert_eq!(frames.len(), 4, "The 4-step algorithm needs 4 frames");
+    demodulate(frames, mask, |i| ((i[3] - i[1]) / 2.0, (i[0] - i[2]) / 2.0))
+}
+
+pub fn hariharan(frames: &[Array<f64>], mask: &Array<bool>) -> (Array<f64>, Array<f64>) {
+    // Shifts -pi, -pi/2, 0, pi/2, pi: phi = atan2(2 (I1 - I3), 2 I2 - I4 - I0)
+    // Insensitive to a linear miscalibration of the phase shifter
+    assert_eq!(frames.len(), 5, "Hariharan's algorithm needs 5 frames");
+    demodulate(frames, mask, |i| ((i[1] - i[3]) / 2.0, (2.0 * i[2] - i[4] - i[0]) / 4.0))
+}
+
+fn wrap(p: f64) -> f64 {
+    // Into (-pi, pi]
+    p - 2.0 * PI * ((p - PI) / (2.0 * PI)).ceil()
+}
+
+pub fn second_difference_quality(wrapped: &Array<f64>) -> Array<f64> {
+    // Quality = minus the largest wrapped second difference around each pixel.
+    // Smooth areas score close to 0, noisy areas and residues are very negative
+    let (n_rows, n_cols) = dims(wrapped);
+    let mut quality = Array::full(wrapped.shape(), f64::NEG_INFINITY);
+    for i in 0..n_rows {
+        for j in 0..n_cols {
+            let p = wrapped[(i, j)];
+            if p.is_nan() {
+                continue;
+            }
+            let mut worst: f64 = 0.0;
+            let pairs = [((0, 1), (0, -1)), ((1, 0), (-1, 0)), ((1, 1), (-1, -1)), ((1, -1), (-1, 1))];
+            for ((di_a, dj_a), (di_b, dj_b)) in pairs {
+                let a = neighbour(wrapped, i, j, di_a, dj_a);
+                let b = neighbour(wrapped, i, j, di_b, dj_b);
+                if let (Some(a), Some(b)) = (a, b) {
+                    worst = worst.max((wrap(a - p) - wrap(p - b)).abs());
+                }
+            }
+            quality[(i, j)] = -worst;
+        }
+    }
+    quality
+}
+
+fn dims(map: &Array<f64>) -> (usize, usize) {
+    assert_eq!(map.ndim(), 2, "Phase maps are 2D");
+    (map.shape()[0], map.shape()[1])
+}
+
+fn neighbour(map: &Array<f64>, i: usize, j: usize, di: i32, dj: i32) -> Option<f64> {
+    let ni = i as i32 + di;
+    let nj = j as i32 + dj;
+    let (n_rows, n_cols) = dims(map);
+    if ni < 0 || nj < 0 || ni as usize >= n_rows || nj as usize >= n_cols {
+        return None;
+    }
+    let v = map[(ni as usize, nj as usize)];
+    if v.is_nan() { None } else { Some(v) }
+}
+
+struct Candidate {
+    // A pixel waiting to be unwrapped, from an already unwrapped neighbour
+    quality: f64,
+    pixel: (usize, usize),
+    from: (usize, usize),
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.quality.total_cmp(&other.quality)
+    }
+}
+
+pub fn unwrap_quality_guided(wrapped: &Array<f64>, quality: &Array<f64>) -> Array<f64> {
+    // Flood fill that always unwraps the best-quality pixel next to the unwrapped region,
+    // so noisy pixels (and the errors they cause) are left until the end.
+    // NaN pixels are skipped; every disconnected region starts from its own best pixel
+    assert_eq!(wrapped.shape(), quality.shape(), "The quality map must have the shape of the phase");
+    let (n_rows, n_cols) = dims(wrapped);
+    let mut unwrapped = Array::full(wrapped.shape(), f64::NAN);
+    let mut done = Array::full(wrapped.shape(), false);
+
+    let mut seeds: Vec<(usize, usize)> = (0..n_rows)
+        .flat_map(|i| (0..n_cols).map(move |j| (i, j)))
+        .filter(|&(i, j)| !wrapped[(i, j)].is_nan())
+        .collect();
+    seeds.sort_by(|&a, &b| quality[b].total_cmp(&quality[a]));
+
+    let mut heap = BinaryHeap::new();
+    for seed in seeds {
+        if done[seed] {
+            continue;
+        }
+        unwrapped[seed] = wrapped[seed];
+        done[seed] = true;
+        push_neighbours(seed, wrapped, quality, &done, &mut heap);
+
+        while let Some(Candidate { pixel: (i, j), from: (fi, fj), .. }) = heap.pop() {
+            if done[(i, j)] {
+                continue;
+            }
+            let reference = unwrapped[(fi, fj)];
+            unwrapped[(i, j)] = reference + wrap(wrapped[(i, j)] - reference);
+            done[(i, j)] = true;
+            push_neighbours((i, j), wrapped, quality, &done, &mut heap);
+        }
+    }
+    unwrapped
+}
+
+fn push_neighbours(
+    (i, j): (usize, usize),
+    wrapped: &Array<f64>,
+    quality: &Array<f64>,
+    done: &Array<bool>,
+    heap: &mut BinaryHeap<Candidate>,
+) {
+    for (di, dj) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
+        if neighbour(wrapped, i, j, di, dj).is_some() {
+            let (ni, nj) = ((i as i32 + di) as usize, (j as i32 + dj) as usize);
+            if !done[(ni, nj)] {
+                heap.push(Candidate { quality: quality[(ni, nj)], pixel: (ni, nj), from: (i, j) });
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+// Optics on top of the numerics toolbox: Zernike polynomials and adaptive optics pieces
+// Scripts need both `mod numerics;` and `mod optics;` since optics uses numerics
+#![allow(dead_code)]
+
+pub mod deformable_mirror;
+pub mod interferogram;
+pub mod pupil;
+pub mod reference;
+pub mod shack_hartmann;
+pub mod turbulence;
+pub mod zernike;
@@ -0,0 +1,137 @@
+// Sampling of the unit pupil on a square grid of pixels
+//
+// Maps are n_pix x n_pix Arrays indexed [(row, col)] on a grid that covers the
+// square [-1, 1]^2 around the unit pupil (row 0 is y = -1, col 0 is x = -1).
+//
+// Built in steps: a meshgrid goes through cart_to_polar (polar_grid), a circular or
+// annular mask picks the pixels, and an Aperture keeps the compressed (rho, theta) lists
+// of those pixels to feed z_nm / fit and put results back. PupilGrid is that for the
+// unit disk, the grid the fitting, interferogram and DM code share.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::numerics::complex::Complex;
+use crate::numerics::ranges::{linspace, meshgrid, Indexing};
+use crate::optics::zernike::Zernike;
+use std::f64::consts::PI;
+
+pub struct PupilGrid {
+    pub n_pix: usize,
+    pub rho: Array<f64>,
+    pub theta: Array<f64>,
+    pub mask: Array<bool>,
+    pub aperture: Aperture,     // The pixels of mask, in the order of in_pupil
+}
+
+impl PupilGrid {
+    pub fn new(n_pix: usize) -> Self {
+        // The unit disk on polar_grid
+        let (rho, theta) = polar_grid(n_pix);
+        let mask = circular_mask(&rho, 1.0);
+        let aperture = Aperture::new(&mask, &rho, &theta).unwrap();
+        PupilGrid { n_pix, rho, theta, mask, aperture }
+    }
+
+    pub fn in_pupil(&self, map: &Array<f64>) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        // (rho, theta, value) of the pixels inside the pupil, ready for Zernike::fit
+        let values = self.aperture.gather(map).expect("The map must be n_pix x n_pix");
+        (self.aperture.rho.clone(), self.aperture.theta.clone(), values)
+    }
+
+    pub fn scatter(&self, values: &[f64]) -> Array<f64> {
+        // Inverse of in_pupil: put per-pixel values back on the grid, NaN outside the pupil
+        self.aperture.scatter(values, f64::NAN).expect("One value per pixel in the pupil")
+    }
+
+    pub fn wavefront(&self, coef: &[f64]) -> Array<f64> {
+        // Evaluate the Zernike wavefront on the grid, NaN outside the pupil
+        self.scatter(&Zernike::new().evaluate(coef, &self.aperture.rho, &self.aperture.theta, "Standard"))
+    }
+}
+
+pub fn cart_to_polar(x: &Array<f64>, y: &Array<f64>) -> Result<(Array<f64>, Array<f64>), ShapeError> {
+    // (rho, theta) of every point of a meshgrid, theta in (-pi, pi] from the +x axis
+    Ok((x.zip_with(y, |x, y| x.hypot(y))?, x.zip_with(y, |x, y| y.atan2(x))?))
+}
+
+pub fn polar_grid(n_pix: usize) -> (Array<f64>, Array<f64>) {
+    // rho and theta at the pixel centres of an n_pix x n_pix grid over [-1, 1]^2
+    let half_step = 1.0 / n_pix as f64;
+    let axis = linspace(-1.0 + half_step, 1.0 - half_step, n_pix);
+    let (x, y) = meshgrid(&axis, &axis, Indexing::Xy);
+    cart_to_polar(&x, &y).unwrap()
+}
+
+pub fn circular_mask(rho: &Array<f64>, radius: f64) -> Array<bool> {
+    rho.map(|&r| r <= radius)
+}
+
+pub fn annular_mask(rho: &Array<f64>, inner: f64, outer: f64) -> Array<bool> {
+    // inner < rho <= outer, e.g. a pupil with a central obscuration
+    rho.map(|&r| r > inner && r <= outer)
+}
+
+pub struct Aperture {
+    pub shape: Vec<usize>,
+    pub indices: Vec<usize>,    // Position in the full grid of each pixel inside the mask
+    pub rho: Vec<f64>,
+    pub theta: Vec<f64>,
+}
+
+impl Aperture {
+    pub fn new(mask: &Array<bool>, rho: &Array<f64>, theta: &Array<f64>) -> Result<Self, ShapeError> {
+        // Compressed coordinates of the pixels where mask is true, in row-major order
+        for other in [rho.shape(), theta.shape()] {
+            if other != mask.shape() {
+                return Err(ShapeError::Incompatible { left: mask.shape().to_vec(), right: other.to_vec() });
+            }
+        }
+        let indices: Vec<usize> = mask.iter().enumerate().filter(|(_, &inside)| inside).map(|(k, _)| k).collect();
+        Ok(Aperture {
+            shape: mask.shape().to_vec(),
+            rho: indices.iter().map(|&k| rho.as_slice()[k]).collect(),
+            theta: indices.iter().map(|&k| theta.as_slice()[k]).collect(),
+            indices,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn gather(&self, map: &Array<f64>) -> Result<Vec<f64>, ShapeError> {
+        // The values of a full map at the pixels inside the aperture
+        if map.shape() != self.shape.as_slice() {
+            return Err(ShapeError::Incompatible { left: self.shape.clone(), right: map.shape().to_vec() });
+        }
+        Ok(self.indices.iter().map(|&k| map.as_slice()[k]).collect())
+    }
+
+    pub fn scatter(&self, values: &[f64], fill: f64) -> Result<Array<f64>, ShapeError> {
+        // Inverse of gather: a full map with `fill` (usually NaN) outside the aperture
+        if values.len() != self.len() {
+            return Err(ShapeError::SizeMismatch { shape: vec![self.len()], len: values.len() });
+        }
+        let mut map = Array::full(&self.shape, fill);
+        let data = map.as_mut_slice();
+        for (&k, &v) in self.indices.iter().zip(values) {
+            data[k] = v;
+        }
+        Ok(map)
+    }
+}
+
+pub fn pupil_field_from_phase(amplitude: &Array<f64>, phase: &Array<f64>) -> Result<Array<Complex<f64>>, ShapeError> {
+    // Complex pupil function A exp(i phase), phase in radians. NaN phase (outside the
+    // aperture, as Aperture::scatter leaves it) gives a field of 0 there
+    amplitude.zip_with(phase, |a, phi| if phi.is_nan() { Complex::new(0.0, 0.0) } else { Complex::from_polar(a, phi) })
+}
+
+pub fn pupil_field(amplitude: &Array<f64>, wavefront: &Array<f64>, wavelength: f64) -> Result<Array<Complex<f64>>, ShapeError> {
+    // Same from a wavefront error W in length units: A exp(i 2 pi W / lambda),
+    // W and lambda in the same units (nm, um, ...)
+    pupil_field_from_phase(amplitude, &wavefront.map(|&w| 2.0 * PI * w / wavelength))
+}
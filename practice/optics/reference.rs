@@ -0,0 +1,119 @@
+// Exact reference for the Zernike radial polynomials
+//
+// R_nm = sum_j c_j rho^(n - 2j) has integer coefficients
+//     c_j = (-1)^j (n - j)! / (j! ((n + m)/2 - j)! ((n - m)/2 - j)!)
+// (the formula from the comment in p2_array_pw.rs). With BigInt coefficients and the f64
+// rho taken exactly as a BigFloat, the whole sum is exact and only the final conversion
+// rounds. That's the yardstick for the fast modes of Zernike::r_nm, whose f64 sums
+// cancel catastrophically at high orders, and the source of golden data files.
+
+use crate::numerics::bigfloat::BigFloat;
+use crate::numerics::bigint::BigInt;
+use crate::numerics::special::binomial_big;
+use crate::optics::zernike::Zernike;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+pub struct ModeError {
+    pub n: i32,
+    pub m: i32,
+    pub mode: String,
+    pub max_abs: f64,
+}
+
+pub fn radial_coefficients_exact(n: i32, m: i32) -> Vec<BigInt> {
+    // c_0 = n! / (a! b!) with a = (n + m)/2, b = (n - m)/2, i.e. the binomial C(n, b),
+    // then c_(j+1) = -c_j (a - j)(b - j) / ((j + 1)(n - j)). Every division is exact
+    let (n, m) = (n.unsigned_abs(), m.unsigned_abs());
+    if m > n || (n - m) % 2 != 0 {
+        return Vec::new();
+    }
+    let (a, b) = ((n + m) / 2, (n - m) / 2);
+
+    let mut c = binomial_big(n, b);
+    let mut coefs = vec![c.clone()];
+    for j in 0..b {
+        let (q1, r1) = c.mul_small(a - j).mul_small(b - j).div_small(j + 1);
+        let (q2, r2) = q1.div_small(n - j);
+        assert!(r1 == 0 && r2 == 0, "Zernike coefficients should be integers");
+        c = -q2;
+        coefs.push(c.clone());
+    }
+    coefs
+}
+
+pub fn r_nm_exact(n: i32, m: i32, rho: f64) -> BigFloat {
+    let n_abs = n.abs();
+    let x = BigFloat::from_f64(rho);
+    let mut sum = BigFloat::zero();
+    for (j, c) in radial_coefficients_exact(n, m).into_iter().enumerate() {
+        let term = &BigFloat::from_bigint(c) * &x.powi((n_abs - 2 * j as i32) as u32);
+        sum = &sum + &term;
+    }
+    sum
+}
+
+pub fn r_nm_reference(n: i32, m: i32, rho: &[f64]) -> Vec<f64> {
+    // The exact R_nm(rho) rounded to the nearest f64
+    rho.iter().map(|&x| r_nm_exact(n, m, x).to_f64()).collect()
+}
+
+pub fn mode_errors(n_max: i32, rho: &[f64], modes: &[&str]) -> Vec<ModeError> {
+    // Largest absolute error of each fast mode for every R_nm with n <= n_max, m >= 0.
+    // The modes are "Standard" and "Jacobi"; anything else panics
+    for mode in modes {
+        assert!(["Standard", "Jacobi"].contains(mode), "Unknown mode {:?}, expected \"Standard\" or \"Jacobi\"", mode);
+    }
+    let zern = Zernike::new();
+    let mut errors = Vec::new();
+    for n in 0..=n_max {
+        for m in (n % 2..=n).step_by(2) {
+            let reference = r_nm_reference(n, m, rho);
+            for &mode in modes {
+                let fast = match mode {
+                    "Standard" => zern.r_nm(n, m, rho),
+                    _ => zern.r_nm_jacobi(n, m, rho),
+                };
+                let max_abs = fast.iter().zip(reference.iter()).map(|(f, r)| (f - r).abs()).fold(0.0, f64::max);
+                errors.push(ModeError { n, m, mode: mode.to_string(), max_abs });
+            }
+        }
+    }
+    errors
+}
+
+pub fn write_golden(path: &str, n_max: i32, rho: &[f64]) -> io::Result<()> {
+    // CSV of n, m, rho, R_nm(rho). f64s are written in the shortest form that reads back
+    // to the same bits, so the file is as exact as the reference itself
+    let mut file = File::create(path)?;
+    writeln!(file, "# n,m,rho,r_nm (exact values rounded to f64)")?;
+    for n in 0..=n_max {
+        for m in (n % 2..=n).step_by(2) {
+            for (x, r) in rho.iter().zip(r_nm_reference(n, m, rho)) {
+                writeln!(file, "{},{},{:e},{:e}", n, m, x, r)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn read_golden(path: &str) -> io::Result<Vec<(i32, i32, f64, f64)>> {
+    let bad = |line: &str| io::Error::new(io::ErrorKind::InvalidData, format!("Bad golden line: {}", line));
+    let mut rows = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != 4 {
+            return Err(bad(&line));
+        }
+        let n = fields[0].parse().map_err(|_| bad(&line))?;
+        let m = fields[1].parse().map_err(|_| bad(&line))?;
+        let rho = fields[2].parse().map_err(|_| bad(&line))?;
+        let r = fields[3].parse().map_err(|_| bad(&line))?;
+        rows.push((n, m, rho, r));
+    }
+    Ok(rows)
+}
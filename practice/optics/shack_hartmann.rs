@@ -0,0 +1,139 @@
+// Shack-Hartmann wavefront sensing
+//
+// A square grid of lenslets covers the unit pupil. Each lenslet measures the average
+// x / y slope of the wavefront over the part of the pupil it sees (that's what the
+// centroid of its spot tracks). Slopes are in wavefront units per pupil radius, e.g.
+// radians of phase per unit rho if the Zernike coefficients are in radians.
+//
+// The modal reconstructor goes the other way: it builds the slopes that each Zernike
+// mode produces on the same lenslets (the "gradient basis") and solves for the
+// coefficients in the least squares sense. Piston gives no slope so coef[0] = 0.
+
+use crate::numerics::array::Array;
+use crate::numerics::linalg;
+use crate::numerics::rng::Rng;
+use crate::optics::zernike::Zernike;
+
+pub struct LensletArray {
+    pub n_across: usize,
+    pub pitch: f64,                         // Lenslet side, in pupil radii
+    pub centers: Vec<(f64, f64)>,           // Only the lenslets that see enough light
+    samples: Vec<(Vec<f64>, Vec<f64>)>,     // (rho, theta) of the sampling points of each lenslet
+}
+
+impl LensletArray {
+    pub fn square(n_across: usize, samples_per_side: usize, min_fill: f64) -> Self {
+        // n_across x n_across lenslets over the square [-1, 1]^2. Each one is sampled on
+        // a samples_per_side^2 grid, and kept if at least min_fill of its points are inside the pupil
+        let pitch = 2.0 / n_across as f64;
+        let step = pitch / samples_per_side as f64;
+        let mut centers = Vec::new();
+        let mut samples = Vec::new();
+
+        for iy in 0..n_across {
+            for ix in 0..n_across {
+                let cx = -1.0 + (ix as f64 + 0.5) * pitch;
+                let cy = -1.0 + (iy as f64 + 0.5) * pitch;
+
+                let mut rho = Vec::new();
+                let mut theta = Vec::new();
+                for sy in 0..samples_per_side {
+                    for sx in 0..samples_per_side {
+                        let x = cx - pitch / 2.0 + (sx as f64 + 0.5) * step;
+                        let y = cy - pitch / 2.0 + (sy as f64 + 0.5) * step;
+                        let r = x.hypot(y);
+                        if r <= 1.0 {
+                            rho.push(r);
+                            theta.push(y.atan2(x));
+                        }
+                    }
+                }
+
+                let fill = rho.len() as f64 / (samples_per_side * samples_per_side) as f64;
+                if !rho.is_empty() && fill >= min_fill {
+                    centers.push((cx, cy));
+                    samples.push((rho, theta));
+                }
+            }
+        }
+        LensletArray { n_across, pitch, centers, samples }
+    }
+
+    pub fn n_lenslets(&self) -> usize {
+        self.centers.len()
+    }
+
+    pub fn average_slopes(&self, coef: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        // Noise-free slopes: the gradient of the wavefront averaged over each lenslet
+        let zern = Zernike::new();
+        let mut sx = Vec::with_capacity(self.n_lenslets());
+        let mut sy = Vec::with_capacity(self.n_lenslets());
+        for (rho, theta) in &self.samples {
+            let (dx, dy) = zern.gradient(coef, rho, theta);
+            let n = rho.len() as f64;
+            sx.push(dx.iter().sum::<f64>() / n);
+            sy.push(dy.iter().sum::<f64>() / n);
+        }
+        (sx, sy)
+    }
+}
+
+pub struct ShackHartmann {
+    // Sensor simulator: average slopes plus Gaussian centroid noise
+    pub lenslets: LensletArray,
+    pub noise_rms: f64,
+    rng: Rng,
+}
+
+impl ShackHartmann {
+    pub fn new(lenslets: LensletArray, noise_rms: f64, seed: u64) -> Self {
+        ShackHartmann { lenslets, noise_rms, rng: Rng::new(seed) }
+    }
+
+    pub fn measure(&mut self, coef: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let (mut sx, mut sy) = self.lenslets.average_slopes(coef);
+        if self.noise_rms > 0.0 {
+            for s in sx.iter_mut().chain(sy.iter_mut()) {
+                *s += self.noise_rms * self.rng.normal();
+            }
+        }
+        (sx, sy)
+    }
+}
+
+pub fn interaction_matrix(lenslets: &LensletArray, n_zern: usize) -> Array<f64> {
+    // Rows: all the x slopes then all the y slopes. Columns: Noll modes j = 2 ..= n_zern
+    let n_lens = lenslets.n_lenslets();
+    let mut matrix = Array::zeros(&[2 * n_lens, n_zern - 1]);
+    for k in 1..n_zern {
+        let mut coef = vec![0.0; k + 1];
+        coef[k] = 1.0;
+        let (sx, sy) = lenslets.average_slopes(&coef);
+        for (i, s) in sx.iter().chain(sy.iter()).enumerate() {
+            matrix[(i, k - 1)] = *s;
+        }
+    }
+    matrix
+}
+
+pub struct ModalReconstructor {
+    pub n_zern: usize,
+    matrix: Array<f64>,         // Least squares inverse of the interaction matrix
+}
+
+impl ModalReconstructor {
+    pub fn new(lenslets: &LensletArray, n_zern: usize) -> Self {
+        assert!(n_zern >= 2, "Need at least the tilts to reconstruct anything");
+        let interaction = interaction_matrix(lenslets, n_zern);
+        let matrix = linalg::least_squares_matrix(&interaction)
+            .expect("Too few lenslets for the number of modes (singular interaction matrix)");
+        ModalReconstructor { n_zern, matrix }
+    }
+
+    pub fn reconstruct(&self, sx: &[f64], sy: &[f64]) -> Vec<f64> {
+        let slopes: Vec<f64> = sx.iter().chain(sy.iter()).cloned().collect();
+        let mut coef = vec![0.0];       // Piston is invisible to the sensor
+        coef.extend(linalg::mat_vec(&self.matrix, &slopes));
+        coef
+    }
+}
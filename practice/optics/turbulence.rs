@@ -0,0 +1,160 @@
+// Atmospheric turbulence in Zernike space
+//
+// Random Zernike coefficient vectors (Noll ordering, see zernike.rs) whose covariance is
+// the one produced by Kolmogorov or von Karman turbulence over a circular pupil of
+// diameter D (Noll 1976, "Zernike polynomials and atmospheric turbulence").
+// The coefficients are in radians of phase at the wavelength where r0 is quoted.
+// Piston has infinite variance for Kolmogorov and does not matter for imaging,
+// so coef[0] is always 0 and is left out of the covariance.
+
+use crate::numerics::array::Array;
+use crate::numerics::linalg;
+use crate::numerics::view::SliceArg;
+use crate::numerics::rng::Rng;
+use crate::numerics::special::{bessel_j, gamma};
+use crate::optics::zernike::noll_to_nm;
+use std::f64::consts::PI;
+
+pub struct Turbulence {
+    pub d_over_r0: f64,
+    pub l0_over_d: Option<f64>,     // Outer scale L0 / D. None means Kolmogorov (L0 = infinity)
+}
+
+// Upper limit and number of intervals for the von Karman radial integral
+// The integrand decays like u^(-17/3) so nothing is left beyond u = 150
+const VK_U_MAX: f64 = 150.0;
+const VK_N_INTERVALS: usize = 3000;
+
+fn spectrum_constant() -> f64 {
+    // Prefactor of the phase spectrum Phi(k) = c r0^(-5/3) k^(-11/3), the "0.023"
+    let c = gamma(11.0 / 6.0).powi(2) / (2.0 * PI.powf(11.0 / 3.0)) * (24.0 / 5.0 * gamma(6.0 / 5.0)).powf(5.0 / 6.0);
+    // Angular integral (2 pi), the 1 / (pi k)^2 of the Zernike transforms and the change
+    // of variable u = 2 pi k R with R = D / 2
+    2.0 * c / PI * 2f64.powf(-5.0 / 3.0) * (2.0 * PI).powf(11.0 / 3.0)
+}
+
+fn coupled(j1: usize, j2: usize) -> Option<(i32, i32)> {
+    // Two modes only correlate if they share |m| and (for m != 0) are both cos or both sin
+    // Returns their radial orders (n1, n2) if they do; the sign goes in sign_and_norm
+    let (n1, m1) = noll_to_nm(j1);
+    let (n2, m2) = noll_to_nm(j2);
+    if m1 != m2 || j1 == 1 || j2 == 1 {
+        return None;
+    }
+    Some((n1, n2))
+}
+
+fn sign_and_norm(n1: i32, n2: i32, m: i32) -> f64 {
+    // (-1)^((n1 + n2 - 2|m|)/2) sqrt((n1 + 1)(n2 + 1))
+    let sign = if ((n1 + n2 - 2 * m.abs()) / 2) % 2 == 0 { 1.0 } else { -1.0 };
+    sign * (((n1 + 1) * (n2 + 1)) as f64).sqrt()
+}
+
+pub fn kolmogorov_covariance(j1: usize, j2: usize) -> f64 {
+    // Closed form of Noll's covariance for (D/r0)^(5/3) = 1
+    // The radial integral of J_(n1+1) J_(n2+1) u^(-14/3) has a Weber-Schafheitlin closed form
+    let (n1, n2) = match coupled(j1, j2) {
+        Some(n) => n,
+        None => return 0.0,
+    };
+    let (_, m) = noll_to_nm(j1);
+    let (a, b) = (n1 as f64, n2 as f64);
+    let radial = gamma(14.0 / 3.0) * gamma((a + b - 5.0 / 3.0) / 2.0)
+        / (2f64.powf(14.0 / 3.0)
+            * gamma((a - b + 17.0 / 3.0) / 2.0)
+            * gamma((b - a + 17.0 / 3.0) / 2.0)
+            * gamma((a + b + 23.0 / 3.0) / 2.0));
+    spectrum_constant() * sign_and_norm(n1, n2, m) * radial
+}
+
+struct RadialQuadrature {
+    // Quadrature of int_0^inf J_a(u) J_b(u) u^(-1) (u^2 + u0^2)^(-11/6) du
+    // with u = t^3 (smooth near 0) and Simpson's rule in t. bessel[a][i] = J_a(u_i)
+    weights: Vec<f64>,
+    bessel: Vec<Vec<f64>>,
+}
+
+impl RadialQuadrature {
+    fn new(max_order: i32, u0: f64) -> Self {
+        let t_max = VK_U_MAX.cbrt();
+        let h = t_max / VK_N_INTERVALS as f64;
+        let mut weights = vec![0.0; VK_N_INTERVALS + 1];
+        let mut u = vec![0.0; VK_N_INTERVALS + 1];
+
+        // i = 0 is u = 0 where the integrand vanishes (u0 > 0, orders >= 1)
+        for i in 1..=VK_N_INTERVALS {
+            let t = i as f64 * h;
+            let simpson = if i == VK_N_INTERVALS { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+            u[i] = t * t * t;
+            weights[i] = simpson * h / 3.0 * 3.0 * t * t / u[i] * (u[i] * u[i] + u0 * u0).powf(-11.0 / 6.0);
+        }
+
+        let bessel = (0..=max_order)
+            .map(|a| u.iter().map(|&x| bessel_j(a, x)).collect())
+            .collect();
+        RadialQuadrature { weights, bessel }
+    }
+
+    fn integral(&self, a: i32, b: i32) -> f64 {
+        let (ja, jb) = (&self.bessel[a as usize], &self.bessel[b as usize]);
+        self.weights.iter().zip(ja.iter().zip(jb.iter())).map(|(w, (x, y))| w * x * y).sum()
+    }
+}
+
+impl Turbulence {
+    pub fn kolmogorov(d_over_r0: f64) -> Self {
+        Turbulence { d_over_r0, l0_over_d: None }
+    }
+
+    pub fn von_karman(d_over_r0: f64, l0_over_d: f64) -> Self {
+        Turbulence { d_over_r0, l0_over_d: Some(l0_over_d) }
+    }
+
+    pub fn covariance_matrix(&self, n_zern: usize) -> Array<f64> {
+        // Covariance <coef[k1] coef[k2]> in rad^2 for the first n_zern Noll modes
+        let scale = self.d_over_r0.powf(5.0 / 3.0);
+        let quadrature = self.l0_over_d.map(|l0| {
+            let max_order = noll_to_nm(n_zern.max(1)).0 + 1;
+            RadialQuadrature::new(max_order, PI / l0)
+        });
+
+        let element = |j1: usize, j2: usize| -> f64 {
+            match &quadrature {
+                None => kolmogorov_covariance(j1, j2),
+                Some(q) => match coupled(j1, j2) {
+                    Some((n1, n2)) => {
+                        let (_, m) = noll_to_nm(j1);
+                        spectrum_constant() * sign_and_norm(n1, n2, m) * q.integral(n1 + 1, n2 + 1)
+                    }
+                    None => 0.0,
+                },
+            }
+        };
+
+        let rows = (1..=n_zern).map(|j1| (1..=n_zern).map(|j2| scale * element(j1, j2)).collect()).collect();
+        Array::from_rows(rows).unwrap()
+    }
+}
+
+pub struct PhaseScreenGenerator {
+    // Draws coefficient vectors a = L z with z ~ N(0, I) and L the Cholesky factor of the covariance
+    chol: Array<f64>,
+    rng: Rng,
+}
+
+impl PhaseScreenGenerator {
+    pub fn new(turbulence: &Turbulence, n_zern: usize, seed: u64) -> Self {
+        // Drop the piston row and column (all zeros) before factorising
+        let cov = turbulence.covariance_matrix(n_zern);
+        let reduced = cov.slice(&[SliceArg::from(1..), SliceArg::from(1..)]).unwrap().to_owned();
+        let chol = linalg::cholesky(&reduced).expect("Turbulence covariance is not positive definite");
+        PhaseScreenGenerator { chol, rng: Rng::new(seed) }
+    }
+
+    pub fn sample(&mut self) -> Vec<f64> {
+        let z = self.rng.normal_vec(self.chol.shape()[0]);
+        let mut coef = vec![0.0];       // Piston
+        coef.extend(linalg::mat_vec(&self.chol, &z));
+        coef
+    }
+}
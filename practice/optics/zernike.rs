@@ -0,0 +1,245 @@
+// Zernike polynomials, the working version of the practice in p1.rs
+//
+// Coefficient vectors follow the Noll ordering: coef[k] multiplies Z_j with j = k + 1
+// (j = 1 piston, j = 2, 3 tilts, j = 4 defocus, ...). Each Z_j is normalised so that
+// its RMS over the unit disk is 1, which is what the Noll covariance assumes.
+// Positive m means cos(m theta), negative m means sin(|m| theta)
+
+use crate::numerics::array::Array;
+use crate::numerics::linalg;
+use crate::numerics::polynomial::{self, Polynomial};
+use crate::numerics::special::factorial_f64;
+
+pub struct Zernike {
+    pub n_zern: usize,
+    pub n_lim: i32,
+}
+
+pub fn noll_to_nm(j: usize) -> (i32, i32) {
+    // Noll index (starting at 1) to the radial order n and the signed azimuthal order m
+    assert!(j >= 1, "Noll indices start at 1");
+    let mut n = 0;
+    let mut j1 = j - 1;
+    while j1 > n {
+        n += 1;
+        j1 -= n;
+    }
+    let m = (n % 2) + 2 * ((j1 + (n + 1) % 2) / 2);
+    let m = if m != 0 && j % 2 == 1 { -(m as i32) } else { m as i32 };
+    (n as i32, m)
+}
+
+pub fn nm_to_noll(n: i32, m: i32) -> usize {
+    // Inverse of noll_to_nm. Radial order n holds the Noll indices n(n+1)/2 + 1 ..= (n+1)(n+2)/2
+    let first = (n * (n + 1) / 2 + 1) as usize;
+    let last = ((n + 1) * (n + 2) / 2) as usize;
+    (first..=last)
+        .find(|&j| noll_to_nm(j) == (n, m))
+        .unwrap_or_else(|| panic!("({}, {}) is not a valid Zernike index", n, m))
+}
+
+pub fn zeros_like(arr: &[f64]) -> Vec<f64> {
+    // A function that mimics Python np.zeros_like(x)
+    vec![0.0; arr.len()]
+}
+
+impl Default for Zernike {
+    fn default() -> Self {
+        Zernike::new()
+    }
+}
+
+impl Zernike {
+    pub fn new() -> Self {
+        Zernike { n_zern: 0, n_lim: 0 }
+    }
+
+    pub fn get_n_zern(&mut self, coef: &[f64]) {
+        self.n_zern = coef.len();
+    }
+
+    pub fn get_limit_index(&mut self) {
+        // Maximum radial order needed to hold n_zern polynomials
+        let y = (1 + 8 * self.n_zern) as f64;
+        self.n_lim = (0.5 * (y.sqrt() - 3.0)).ceil() as i32;
+    }
+
+    pub fn normalisation(n: i32, m: i32) -> f64 {
+        if m == 0 {
+            ((n + 1) as f64).sqrt()
+        } else {
+            (2.0 * (n + 1) as f64).sqrt()
+        }
+    }
+
+    pub fn z_nm(&self, n: i32, m: i32, rho: &[f64], theta: &[f64], mode: &str) -> Vec<f64> {
+        // The radial part by mode, "Standard" or "Jacobi"; anything else panics
+        let r: Vec<f64> = match mode {
+            "Standard" => self.r_nm(n, m, rho),
+            "Jacobi" => self.r_nm_jacobi(n, m, rho),
+            _ => panic!("Unknown mode {:?}, expected \"Standard\" or \"Jacobi\"", mode),
+        };
+
+        let norm = Zernike::normalisation(n, m);
+        let m_abs = m.abs() as f64;
+        r.iter()
+            .zip(theta.iter())
+            .map(|(&r, &t)| {
+                let angular = if m > 0 {
+                    (m_abs * t).cos()
+                } else if m < 0 {
+                    (m_abs * t).sin()
+                } else {
+                    1.0
+                };
+                norm * r * angular
+            })
+            .collect()
+    }
+
+    pub fn z_j(&self, j: usize, rho: &[f64], theta: &[f64], mode: &str) -> Vec<f64> {
+        let (n, m) = noll_to_nm(j);
+        self.z_nm(n, m, rho, theta, mode)
+    }
+
+    pub fn r_nm(&self, n: i32, m: i32, rho: &[f64]) -> Vec<f64> {
+        // Explicit sum with factorials
+        // R_nm = sum_j (-1)^j (n - j)! / (j! ((n + m)/2 - j)! ((n - m)/2 - j)!) rho^(n - 2j)
+        radial_polynomial(n, m).eval_slice(rho)
+    }
+
+    pub fn r_nm_jacobi(&self, n: i32, m: i32, rho: &[f64]) -> Vec<f64> {
+        // Through the Jacobi polynomials, which avoids the big factorials
+        // R_nm(rho) = (-1)^k rho^m P_k^(m, 0)(1 - 2 rho^2) with k = (n - m) / 2
+        let n_abs = n.abs();
+        let m_abs = m.abs();
+        if (n_abs - m_abs) % 2 != 0 || m_abs > n_abs {
+            return zeros_like(rho);
+        }
+
+        let k = (n_abs - m_abs) / 2;
+        let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+        rho.iter()
+            .map(|&x| sign * x.powi(m_abs) * polynomial::jacobi_eval(k as usize, m_abs as f64, 0.0, 1.0 - 2.0 * x * x))
+            .collect()
+    }
+
+    pub fn grad_z_nm(&self, n: i32, m: i32, rho: &[f64], theta: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        // Cartesian derivatives (dZ/dx, dZ/dy) of the normalised Z_nm on the unit disk
+        //     dZ/dx = dZ/drho cos(theta) - dZ/dtheta sin(theta) / rho
+        //     dZ/dy = dZ/drho sin(theta) + dZ/dtheta cos(theta) / rho
+        // R_nm / rho is evaluated term by term (every power is >= m) so rho = 0 is fine
+        let terms = radial_terms(n, m);
+        let norm = Zernike::normalisation(n, m);
+        let m_abs = m.abs();
+        let m_f = m_abs as f64;
+
+        let mut dx = zeros_like(rho);
+        let mut dy = zeros_like(rho);
+        for (i, (&r, &t)) in rho.iter().zip(theta.iter()).enumerate() {
+            let mut dr = 0.0;
+            let mut r_over_rho = 0.0;
+            for &(coef, exp) in &terms {
+                if exp > 0 {
+                    let p = r.powi(exp - 1);
+                    dr += coef * exp as f64 * p;
+                    r_over_rho += coef * p;
+                }
+            }
+            // angular part and its derivative
+            let (ang, d_ang) = if m > 0 {
+                ((m_f * t).cos(), -m_f * (m_f * t).sin())
+            } else if m < 0 {
+                ((m_f * t).sin(), m_f * (m_f * t).cos())
+            } else {
+                (1.0, 0.0)
+            };
+            let d_rho = norm * dr * ang;
+            let d_theta_over_rho = if m_abs == 0 { 0.0 } else { norm * r_over_rho * d_ang };
+            dx[i] = d_rho * t.cos() - d_theta_over_rho * t.sin();
+            dy[i] = d_rho * t.sin() + d_theta_over_rho * t.cos();
+        }
+        (dx, dy)
+    }
+
+    pub fn grad_z_j(&self, j: usize, rho: &[f64], theta: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let (n, m) = noll_to_nm(j);
+        self.grad_z_nm(n, m, rho, theta)
+    }
+
+    pub fn gradient(&self, coef: &[f64], rho: &[f64], theta: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        // (dW/dx, dW/dy) of the wavefront W = sum_j coef[j - 1] Z_j
+        let mut dx = zeros_like(rho);
+        let mut dy = zeros_like(rho);
+        for (k, &c) in coef.iter().enumerate() {
+            if c == 0.0 {
+                continue;
+            }
+            let (zx, zy) = self.grad_z_j(k + 1, rho, theta);
+            for i in 0..rho.len() {
+                dx[i] += c * zx[i];
+                dy[i] += c * zy[i];
+            }
+        }
+        (dx, dy)
+    }
+
+    pub fn evaluate(&self, coef: &[f64], rho: &[f64], theta: &[f64], mode: &str) -> Vec<f64> {
+        // Wavefront W = sum_j coef[j - 1] Z_j at every (rho, theta)
+        let mut w = zeros_like(rho);
+        for (k, &c) in coef.iter().enumerate() {
+            if c == 0.0 {
+                continue;
+            }
+            let z = self.z_j(k + 1, rho, theta, mode);
+            for (w_i, z_i) in w.iter_mut().zip(z.iter()) {
+                *w_i += c * z_i;
+            }
+        }
+        w
+    }
+
+    pub fn fit(&self, rho: &[f64], theta: &[f64], values: &[f64], n_zern: usize, mode: &str) -> Vec<f64> {
+        // Least squares fit of the first n_zern Noll modes (piston included) to sampled values
+        // Points that are NaN (outside the pupil, unmeasured) are skipped
+        let valid: Vec<usize> = (0..values.len()).filter(|&i| !values[i].is_nan()).collect();
+        let rho_v: Vec<f64> = valid.iter().map(|&i| rho[i]).collect();
+        let theta_v: Vec<f64> = valid.iter().map(|&i| theta[i]).collect();
+        let values_v: Vec<f64> = valid.iter().map(|&i| values[i]).collect();
+
+        let columns: Vec<Vec<f64>> = (1..=n_zern).map(|j| self.z_j(j, &rho_v, &theta_v, mode)).collect();
+        let basis = linalg::transpose(&Array::from_rows(columns).unwrap());
+        let pinv = linalg::least_squares_matrix(&basis).expect("Not enough points to fit that many modes");
+        linalg::mat_vec(&pinv, &values_v)
+    }
+}
+
+pub fn radial_polynomial(n: i32, m: i32) -> Polynomial<f64> {
+    // R_nm as a Polynomial in rho (unnormalised), zero if (n, m) is not a valid pair
+    let mut coef = vec![0.0; n.unsigned_abs() as usize + 1];
+    for (c, exp) in radial_terms(n, m) {
+        coef[exp as usize] = c;
+    }
+    Polynomial::new(coef)
+}
+
+fn radial_terms(n: i32, m: i32) -> Vec<(f64, i32)> {
+    // The (coefficient, power of rho) pairs of R_nm. Empty if (n, m) is not a valid pair
+    let n_abs = n.abs();
+    let m_abs = m.abs();
+    if (n_abs - m_abs) % 2 != 0 || m_abs > n_abs {
+        return Vec::new();
+    }
+
+    let max_idx = (n_abs - m_abs) / 2 + 1;
+    (0..max_idx)
+        .map(|j| {
+            let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+            let coef = sign * factorial_f64((n_abs - j) as u32)
+                / (factorial_f64(j as u32)
+                    * factorial_f64(((n_abs + m_abs) / 2 - j) as u32)
+                    * factorial_f64(((n_abs - m_abs) / 2 - j) as u32));
+            (coef, n_abs - 2 * j)
+        })
+        .collect()
+}
@@ -1,6 +1,11 @@
 #![allow(dead_code, unused_variables)]
 // Some practice scripts in Rust
 // Trying to do the Zernike calculations
+// (the complete version, with the coefficients of each power, is optics/zernike.rs)
+
+mod numerics;
+
+use numerics::array::Array;
 
 struct Zernike {
     n_zern : i32,
@@ -43,19 +48,19 @@ impl Zernike {
 
         let n_abs = n.abs();
         let m_abs = m.abs();
-        let mut r = zeros_like(rho);
+        let mut r: Array<f64> = Array::zeros(&[rho.len()]);
 
         if (n_abs - m_abs) % 2 != 0 {
-            r
+            r.into_vec()
         } else{
             // for j in range(int((n - m) / 2) + 1)
             let max_idx = (n_abs - m_abs) / 2 + 1;
             for j in 0..max_idx{
                 let exp = n_abs - 2 * j;
-                let poly: &[f64] = rho.iter().map(|&x| x.powei(exp)).collect();
-                r += poly;
+                let poly = Array::from_vec(rho.iter().map(|&x| x.powi(exp)).collect());
+                r += &poly;         // Element-wise, thanks to the operators in numerics/broadcast.rs
             }
-            r
+            r.into_vec()
         }
         
     }
@@ -0,0 +1,58 @@
+// Practice script for element-wise arithmetic with broadcasting (numerics/broadcast.rs)
+// Compile from this folder with: rustc p10_broadcasting.rs
+
+mod numerics;
+
+use numerics::array::{Array, ShapeError};
+use numerics::broadcast::broadcast_shape;
+
+fn main(){
+    // Same shapes: plain element-wise
+    let a = Array::from_rows(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]).unwrap();
+    let b = Array::from_rows(vec![vec![10.0, 20.0, 30.0], vec![40.0, 50.0, 60.0]]).unwrap();
+    let c = &a + &b;
+    println!("a + b = {:?}", c.to_rows().unwrap());
+    assert_eq!(c.as_slice(), &[11.0, 22.0, 33.0, 44.0, 55.0, 66.0]);
+    assert_eq!((&b / &a).as_slice(), &[10.0; 6]);
+
+    // Scalars on either side
+    assert_eq!((&a * 2.0).as_slice(), &[2.0, 4.0, 6.0, 8.0, 10.0, 12.0]);
+    assert_eq!((1.0_f64 - &a).as_slice(), &[0.0, -1.0, -2.0, -3.0, -4.0, -5.0]);
+
+    // A row is added to every row, a column to every column
+    let row = Array::from_vec(vec![100.0, 200.0, 300.0]);
+    let col = Array::from_shape_vec(&[2, 1], vec![1000.0, 2000.0]).unwrap();
+    assert_eq!((&a + &row).to_rows().unwrap()[1], vec![104.0, 205.0, 306.0]);
+    assert_eq!((&a - &col).to_rows().unwrap()[1], vec![-1996.0, -1995.0, -1994.0]);
+
+    // Outer product through broadcasting: [3, 1] * [1, 4] -> [3, 4]
+    let x = Array::from_shape_vec(&[3, 1], vec![1, 2, 3]).unwrap();
+    let y = Array::from_shape_vec(&[1, 4], vec![1, 10, 100, 1000]).unwrap();
+    let outer = &x * &y;
+    println!("Outer product shape {:?}: {:?}", outer.shape(), outer.to_rows().unwrap());
+    assert_eq!(outer[(2, 3)], 3000);
+
+    // In place, like the `r += poly` of p1.rs
+    let mut r: Array<f64> = Array::zeros(&[4]);
+    let rho = [0.0, 0.5, 0.75, 1.0];
+    for exp in [2, 4] {
+        let poly = Array::from_vec(rho.iter().map(|x: &f64| x.powi(exp)).collect());
+        r += &poly;
+    }
+    println!("rho^2 + rho^4 = {:?}", r.as_slice());
+    assert_eq!(r[1], 0.25 + 0.0625);
+    r *= 2.0;
+    r -= &Array::from_vec(vec![1.0; 4]);
+    assert_eq!(r[3], 3.0);
+
+    // Mismatches are errors with both shapes in the message
+    let bad = Array::from_vec(vec![1.0, 2.0]);
+    let err = a.try_add(&bad).unwrap_err();
+    println!("[2, 3] + [2]: {}", err);
+    assert_eq!(err, ShapeError::Incompatible { left: vec![2, 3], right: vec![2] });
+    assert_eq!(broadcast_shape(&[8, 1, 6, 1], &[7, 1, 5]).unwrap(), vec![8, 7, 6, 5]);
+
+    // broadcast_to makes the stretched copy explicitly
+    let tiled = row.broadcast_to(&[2, 3]).unwrap();
+    assert_eq!(tiled.to_rows().unwrap(), vec![vec![100.0, 200.0, 300.0]; 2]);
+}
@@ -0,0 +1,55 @@
+// Practice script for linspace & co. and coordinate grids (numerics/ranges.rs)
+// Compile from this folder with: rustc p11_ranges.rs
+
+mod numerics;
+
+use numerics::ranges::{arange, geomspace, linspace, linspace_step, logspace, meshgrid, Indexing};
+
+fn main(){
+    // The corner cases that broke the linspace of p2_array_pw.rs
+    assert!(linspace(0.0, 1.0, 0).is_empty());
+    assert_eq!(linspace(0.5, 1.0, 1).as_slice(), &[0.5]);
+    let (x, step) = linspace_step(0.0, 1.0, 5, true);
+    println!("linspace(0, 1, 5) = {:?} with step {:?}", x.as_slice(), step);
+    assert_eq!(step, Some(0.25));
+    assert_eq!(linspace_step(3.0, 3.0, 1, true).1, None);
+
+    // Without the endpoint the step is (stop - start) / num
+    let (x, step) = linspace_step(0.0, 1.0, 4, false);
+    assert_eq!(x.as_slice(), &[0.0, 0.25, 0.5, 0.75]);
+    assert_eq!(step, Some(0.25));
+
+    // The last point is exactly stop even when the steps don't add up exactly
+    let x = linspace(0.1, 0.7, 7);
+    assert_eq!(x[6], 0.7);
+
+    // arange: stop excluded, negative steps fine, empty when going the wrong way
+    assert_eq!(arange(0.0, 1.0, 0.25).as_slice(), &[0.0, 0.25, 0.5, 0.75]);
+    assert_eq!(arange(2.0, 0.0, -0.5).as_slice(), &[2.0, 1.5, 1.0, 0.5]);
+    assert!(arange(1.0, 0.0, 0.5).is_empty());
+
+    // logspace / geomspace
+    assert_eq!(logspace(0.0, 3.0, 4, 10.0).as_slice(), &[1.0, 10.0, 100.0, 1000.0]);
+    let g = geomspace(-1.0, -16.0, 5);
+    println!("geomspace(-1, -16, 5) = {:?}", g.as_slice());
+    for (a, b) in g.iter().zip([-1.0, -2.0, -4.0, -8.0, -16.0]) {
+        assert!((a - b).abs() < 1e-12);
+    }
+
+    // meshgrid: pupil coordinates on a 5 x 4 grid
+    let x = linspace(-1.0, 1.0, 5);
+    let y = linspace(-1.0, 1.0, 4);
+    let (gx, gy) = meshgrid(&x, &y, Indexing::Xy);
+    println!("xy grids have shape {:?}", gx.shape());
+    assert_eq!(gx.shape(), &[4, 5]);
+    assert_eq!(gx[(3, 4)], 1.0);            // x grows along the columns
+    assert_eq!(gy[(3, 0)], 1.0);            // y grows along the rows
+    let (ix, iy) = meshgrid(&x, &y, Indexing::Ij);
+    assert_eq!(ix.shape(), &[5, 4]);
+    assert_eq!(ix[(4, 0)], 1.0);
+    assert_eq!(iy[(0, 3)], 1.0);
+
+    // And the grids work with the element-wise operators: rho^2 = x^2 + y^2
+    let rho2 = &(&gx * &gx) + &(&gy * &gy);
+    assert_eq!(rho2[(0, 0)], 2.0);
+}
@@ -0,0 +1,58 @@
+// Practice script for polar grids, pupil masks and compressed in-aperture coordinates
+// Compile from this folder with: rustc p12_pupil_coordinates.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use numerics::ranges::{linspace, meshgrid, Indexing};
+use optics::pupil::{annular_mask, cart_to_polar, circular_mask, polar_grid, Aperture, PupilGrid};
+use optics::zernike::Zernike;
+
+fn main(){
+    // cart_to_polar on a small meshgrid: the corners sit at rho = sqrt(2)
+    let axis = linspace(-1.0, 1.0, 3);
+    let (x, y) = meshgrid(&axis, &axis, Indexing::Xy);
+    let (rho, theta) = cart_to_polar(&x, &y).unwrap();
+    assert!((rho[(0, 0)] - 2.0_f64.sqrt()).abs() < 1e-15);
+    assert_eq!(rho[(1, 1)], 0.0);
+    assert!((theta[(1, 2)] - 0.0).abs() < 1e-15);                           // +x
+    assert!((theta[(2, 1)] - std::f64::consts::FRAC_PI_2).abs() < 1e-15);   // +y
+    assert!(cart_to_polar(&x, &Array::zeros(&[2, 2])).is_err());
+
+    // PupilGrid is polar_grid with the unit disk as its Aperture
+    let n_pix = 32;
+    let grid = PupilGrid::new(n_pix);
+    let (rho, theta) = polar_grid(n_pix);
+    assert!(grid.rho == rho && grid.theta == theta && grid.mask == circular_mask(&rho, 1.0));
+    let (r, _, values) = grid.in_pupil(&rho);
+    assert_eq!((r.len(), &r), (grid.aperture.len(), &values));
+    assert!(grid.scatter(&values).iter().zip(rho.iter()).all(|(a, b)| a == b || (a.is_nan() && *b > 1.0)));
+
+    // Masks: the full disk holds about pi/4 of the pixels, an annulus with a 30 %
+    // obscuration about (1 - 0.3^2) of that
+    let disk = Aperture::new(&circular_mask(&rho, 1.0), &rho, &theta).unwrap();
+    let ring = Aperture::new(&annular_mask(&rho, 0.3, 1.0), &rho, &theta).unwrap();
+    let total = (n_pix * n_pix) as f64;
+    println!("{} pixels in the disk, {} in the annulus", disk.len(), ring.len());
+    assert!((disk.len() as f64 / total - std::f64::consts::FRAC_PI_4).abs() < 0.02);
+    assert!((ring.len() as f64 / disk.len() as f64 - (1.0 - 0.09)).abs() < 0.03);
+    assert!(ring.rho.iter().all(|&r| r > 0.3 && r <= 1.0));
+
+    // The compressed lists go straight into the Zernike code...
+    let zern = Zernike::new();
+    let defocus = zern.z_nm(2, 0, &ring.rho, &ring.theta, "Standard");
+    let coma = zern.z_nm(3, 1, &ring.rho, &ring.theta, "Standard");
+    let values: Vec<f64> = defocus.iter().zip(coma.iter()).map(|(d, c)| 0.5 * d - 0.2 * c).collect();
+    let coef = zern.fit(&ring.rho, &ring.theta, &values, 10, "Standard");
+    println!("Fit on the annulus: Z4 = {:.6}, Z8 = {:.6}", coef[3], coef[7]);
+    assert!((coef[3] - 0.5).abs() < 1e-9 && (coef[7] + 0.2).abs() < 1e-9);
+
+    // ...and scatter / gather move values between the lists and the full map
+    let map = ring.scatter(&values, f64::NAN).unwrap();
+    assert_eq!(map.shape(), &[n_pix, n_pix]);
+    assert!(map[(n_pix / 2, n_pix / 2)].is_nan());     // Inside the obscuration
+    assert_eq!(ring.gather(&map).unwrap(), values);
+    assert!(ring.scatter(&values[1..], 0.0).is_err());
+    assert!(ring.gather(&Array::zeros(&[4, 4])).is_err());
+}
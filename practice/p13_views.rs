@@ -0,0 +1,101 @@
+// Practice script for array views: s![] slicing with steps, transposes, row / column
+// iterators and mutable views split across threads
+// Compile from this folder with: rustc p13_views.rs
+
+#[macro_use]
+mod numerics;
+
+use numerics::array::{Array, ShapeError};
+use std::thread;
+
+fn main(){
+    // 4 x 6 matrix with a[i][j] = 10 i + j, so every element says where it lives
+    let a = Array::from_shape_vec(&[4, 6], (0..24).map(|k| 10 * (k / 6) + k % 6).collect()).unwrap();
+
+    // Every other column from column 1, no copy: just a bigger stride
+    let odd = a.slice(&s![.., 1..;2]).unwrap();
+    println!("a[:, 1::2] has shape {:?} and strides {:?}", odd.shape(), odd.strides());
+    assert_eq!(odd.shape(), &[4, 3]);
+    assert_eq!(odd.strides(), &[6, 2]);
+    assert_eq!(odd[(2, 1)], 23);
+    assert_eq!(odd.to_owned().as_slice(), &[1, 3, 5, 11, 13, 15, 21, 23, 25, 31, 33, 35]);
+    assert!(odd.as_slice().is_none());              // Not contiguous any more
+
+    // A single index drops the axis, ranges past the end are clipped like numpy
+    let row = a.slice(&s![2, ..]).unwrap();
+    assert_eq!(row.shape(), &[6]);
+    assert_eq!(row.as_slice(), Some(&[20, 21, 22, 23, 24, 25][..]));
+    assert_eq!(a.slice(&s![1..=2, 4..100]).unwrap().to_owned().as_slice(), &[14, 15, 24, 25]);
+    assert_eq!(a.slice(&s![..;3, 5]).unwrap().to_owned().as_slice(), &[5, 35]);
+
+    // Like .get() on a slice in 21_arrays.rs: out of bounds is None / an error, not garbage
+    assert_eq!(odd.get(&[0, 3]), None);
+    assert_eq!(a.slice(&s![4, ..]).unwrap_err(), ShapeError::OutOfBounds { axis: 0, index: 4, len: 4 });
+    assert!(a.slice(&s![.., .., 0]).is_err());
+
+    // Transposes swap the strides, and views of views keep borrowing the same data
+    let at = a.t();
+    assert_eq!(at.shape(), &[6, 4]);
+    assert_eq!(at[(5, 3)], 35);
+    assert_eq!(at.slice(&s![1..;2, 0]).unwrap().to_owned().as_slice(), &[1, 3, 5]);
+
+    // Row and column iterators
+    let row_sums: Vec<i32> = a.rows().map(|r| r.iter().sum()).collect();
+    let col_sums: Vec<i32> = a.columns().map(|c| c.iter().sum()).collect();
+    println!("Row sums {:?}, column sums {:?}", row_sums, col_sums);
+    assert_eq!(row_sums, vec![15, 75, 135, 195]);
+    assert_eq!(col_sums, vec![60, 64, 68, 72, 76, 80]);
+    assert_eq!(a.t().rows().len(), 6);
+
+    // Mutable views: zero the border of a 5 x 5 map through slices
+    let mut map = Array::<f64>::ones(&[5, 5]);
+    for args in [s![0, ..], s![4, ..], s![.., 0], s![.., 4]] {
+        map.slice_mut(&args).unwrap().fill(0.0);
+    }
+    assert_eq!(map.sum(), 9.0);
+    map.slice_mut(&s![1..4, 1..4;2]).unwrap().map_inplace(|x| *x *= 10.0);
+    assert_eq!(map[(1, 3)], 10.0);
+    assert_eq!(map[(1, 2)], 1.0);
+
+    // Split a big map into 4 bands of rows and fill them from 4 threads at once
+    let (n_rows, n_cols) = (103, 64);
+    let mut image = Array::<f64>::zeros(&[n_rows, n_cols]);
+    let bands = image.view_mut().split_chunks(0, 4).unwrap();
+    println!("Band heights: {:?}", bands.iter().map(|b| b.shape()[0]).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for (k, mut band) in bands.into_iter().enumerate() {
+            scope.spawn(move || band.fill(k as f64 + 1.0));
+        }
+    });
+    let band_of_row: Vec<f64> = image.rows().map(|r| r[&[0][..]]).collect();
+    assert_eq!(band_of_row[0], 1.0);
+    assert_eq!(band_of_row[26], 2.0);       // 103 = 26 + 26 + 26 + 25
+    assert_eq!(band_of_row[102], 4.0);
+    assert_eq!(image.sum(), 64.0 * (26.0 + 52.0 + 78.0 + 100.0));
+
+    // The transpose splits along its columns; rows that interleave cannot be split
+    let (left, right) = image.view_mut().t().split_at(1, 50).unwrap();
+    assert_eq!((left.shape(), right.shape()), (&[64, 50][..], &[64, 53][..]));
+    let every_other = image.slice_mut(&s![..;2, ..]).unwrap();
+    assert_eq!(every_other.split_at(1, 10).unwrap_err(), ShapeError::Overlapping { axis: 1 });
+
+    // A single column of the transpose: its rows still interleave in memory, but its
+    // columns split, and a length 1 axis gives everything to the non-empty half
+    let mut small = Array::from_shape_vec(&[3, 4], (0..12).map(|k| k as f64).collect()).unwrap();
+    let column = small.view_mut().t().into_slice(&s![0..1, ..]).unwrap();
+    assert_eq!((column.shape(), column.strides()), (&[1, 3][..], &[1, 4][..]));
+    assert_eq!(column.split_at(0, 1).unwrap_err(), ShapeError::Overlapping { axis: 0 });
+    let column = small.view_mut().t().into_slice(&s![0..1, ..]).unwrap();
+    let mut parts = column.split_chunks(1, 3).unwrap();
+    for (k, part) in parts.iter_mut().enumerate() {
+        part.fill(-(k as f64));
+    }
+    assert_eq!(small.as_slice()[..9], [0.0, 1.0, 2.0, 3.0, -1.0, 5.0, 6.0, 7.0, -2.0]);
+    let row = small.slice_mut(&s![1..2, ..]).unwrap();
+    let (mut whole, rest) = row.split_at(0, 1).unwrap();
+    assert_eq!((whole.shape(), rest.shape()), (&[1, 4][..], &[0, 4][..]));
+    whole.fill(9.0);
+    let (none, all) = small.view_mut().into_slice(&s![2..3, ..]).unwrap().split_at(0, 0).unwrap();
+    assert_eq!((none.len(), all.len()), (0, 4));
+    assert_eq!(small.as_slice(), [0.0, 1.0, 2.0, 3.0, 9.0, 9.0, 9.0, 9.0, -2.0, 9.0, 10.0, 11.0]);
+}
@@ -0,0 +1,110 @@
+// Practice script for the dense linear algebra module: products, LU, QR, Cholesky,
+// symmetric eigenvalues and the SVD, each checked on a matrix with a known answer
+// Compile from this folder with: rustc p14_linalg.rs
+
+mod numerics;
+
+use numerics::array::Array;
+use numerics::linalg::{self, LinalgError, Svd};
+
+fn matrix(rows: Vec<Vec<f64>>) -> Array<f64> {
+    Array::from_rows(rows).unwrap()
+}
+
+fn max_diff(a: &Array<f64>, b: &Array<f64>) -> f64 {
+    a.iter().zip(b.iter()).fold(0.0, |acc, (x, y)| acc.max((x - y).abs()))
+}
+
+fn main(){
+    // Products
+    let a = matrix(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+    let b = matrix(vec![vec![7.0, 8.0], vec![9.0, 10.0], vec![11.0, 12.0]]);
+    let ab = linalg::mat_mul(&a, &b);
+    assert_eq!(ab.as_slice(), &[58.0, 64.0, 139.0, 154.0]);
+    assert_eq!(linalg::mat_vec(&a, &[1.0, 0.0, -1.0]), vec![-2.0, -2.0]);
+    assert!(linalg::try_mat_mul(&a, &a).is_err());
+
+    // LU: a system with the solution (2, 3, -1), and a determinant worked out by hand
+    let m = matrix(vec![vec![2.0, 1.0, -1.0], vec![-3.0, -1.0, 2.0], vec![-2.0, 1.0, 2.0]]);
+    let x = linalg::solve(&m, &[8.0, -11.0, -3.0]).unwrap();
+    println!("LU solve: {:?}", x);
+    assert!((x[0] - 2.0).abs() < 1e-12 && (x[1] - 3.0).abs() < 1e-12 && (x[2] + 1.0).abs() < 1e-12);
+    assert!((linalg::determinant(&m).unwrap() + 1.0).abs() < 1e-12);
+    let inv = linalg::inverse(&m).unwrap();
+    assert!(max_diff(&linalg::mat_mul(&m, &inv), &linalg::identity(3)) < 1e-12);
+    let singular = matrix(vec![vec![1.0, 2.0], vec![2.0, 4.0]]);
+    assert_eq!(linalg::solve(&singular, &[1.0, 2.0]).unwrap_err(), LinalgError::Singular);
+    assert_eq!(linalg::determinant(&singular).unwrap(), 0.0);
+    assert!(matches!(linalg::lu(&a), Err(LinalgError::NotSquare { .. })));
+
+    // QR: the classic 3 x 3 example has R = [[14, 21, -14], [0, 175, -70], [0, 0, -35]] up to signs
+    let h = matrix(vec![vec![12.0, -51.0, 4.0], vec![6.0, 167.0, -68.0], vec![-4.0, 24.0, -41.0]]);
+    let f = linalg::qr(&h).unwrap();
+    let diag: Vec<f64> = (0..3).map(|i| f.r[(i, i)].abs()).collect();
+    println!("|diag(R)| = {:?}", diag);
+    assert!((diag[0] - 14.0).abs() < 1e-10 && (diag[1] - 175.0).abs() < 1e-10 && (diag[2] - 35.0).abs() < 1e-10);
+    assert!(max_diff(&linalg::mat_mul(&f.q, &f.r), &h) < 1e-10);
+    assert!(max_diff(&linalg::mat_mul(&linalg::transpose(&f.q), &f.q), &linalg::identity(3)) < 1e-12);
+
+    // Least squares: the straight line through (0, 1), (1, 3), (2, 5), (3, 7) is 1 + 2 t
+    let design = matrix((0..4).map(|t| vec![1.0, t as f64]).collect());
+    let line = linalg::least_squares(&design, &[1.0, 3.0, 5.0, 7.0]).unwrap();
+    assert!((line[0] - 1.0).abs() < 1e-12 && (line[1] - 2.0).abs() < 1e-12);
+    let pinv = linalg::least_squares_matrix(&design).unwrap();
+    assert_eq!(pinv.shape(), &[2, 4]);
+    assert!(max_diff(&linalg::mat_mul(&pinv, &design), &linalg::identity(2)) < 1e-12);
+    assert!(matches!(linalg::qr(&a), Err(LinalgError::Underdetermined { .. })));
+
+    // Cholesky: [[4, 12, -16], [12, 37, -43], [-16, -43, 98]] = L L^T with L = [[2], [6, 1], [-8, 5, 3]]
+    let spd = matrix(vec![vec![4.0, 12.0, -16.0], vec![12.0, 37.0, -43.0], vec![-16.0, -43.0, 98.0]]);
+    let l = linalg::cholesky(&spd).unwrap();
+    assert!(max_diff(&l, &matrix(vec![vec![2.0, 0.0, 0.0], vec![6.0, 1.0, 0.0], vec![-8.0, 5.0, 3.0]])) < 1e-12);
+    let y = linalg::cholesky_solve(&l, &linalg::mat_vec(&spd, &[1.0, 1.0, 1.0]));
+    assert!(y.iter().all(|v| (v - 1.0).abs() < 1e-10));
+    assert_eq!(linalg::cholesky(&singular).unwrap_err(), LinalgError::NotPositiveDefinite);
+
+    // Symmetric eigenvalues: the second difference matrix has 2 - 2 cos(k pi / (n + 1))
+    let n = 6;
+    let mut lap = Array::zeros(&[n, n]);
+    for i in 0..n {
+        lap[(i, i)] = 2.0;
+        if i + 1 < n {
+            lap[(i, i + 1)] = -1.0;
+            lap[(i + 1, i)] = -1.0;
+        }
+    }
+    let (w, v) = linalg::eigh(&lap).unwrap();
+    println!("Eigenvalues: {:?}", w);
+    for (k, wk) in w.iter().enumerate() {
+        let exact = 2.0 - 2.0 * ((k + 1) as f64 * std::f64::consts::PI / (n + 1) as f64).cos();
+        assert!((wk - exact).abs() < 1e-12);
+    }
+    // A V = V diag(w)
+    let av = linalg::mat_mul(&lap, &v);
+    let mut vw = v.clone();
+    for i in 0..n {
+        for k in 0..n {
+            vw[(i, k)] *= w[k];
+        }
+    }
+    assert!(max_diff(&av, &vw) < 1e-12);
+    assert_eq!(linalg::eigh(&h).unwrap_err(), LinalgError::NotSymmetric);
+
+    // SVD: [[3, 2, 2], [2, 3, -2]] has singular values 5 and 3, and U diag(s) V^T gives it back
+    let c = matrix(vec![vec![3.0, 2.0, 2.0], vec![2.0, 3.0, -2.0]]);
+    let Svd { u, s, v } = linalg::svd(&c).unwrap();
+    println!("Singular values: {:?}", s);
+    assert!((s[0] - 5.0).abs() < 1e-12 && (s[1] - 3.0).abs() < 1e-12);
+    assert_eq!((u.shape(), v.shape()), (&[2, 2][..], &[3, 2][..]));
+    let mut us = u.clone();
+    for row in 0..2 {
+        for k in 0..2 {
+            us[(row, k)] *= s[k];
+        }
+    }
+    assert!(max_diff(&linalg::mat_mul(&us, &linalg::transpose(&v)), &c) < 1e-12);
+
+    // The pseudo-inverse of a full column rank matrix is the least squares matrix
+    let p = linalg::pseudo_inverse(&design, 1e-12).unwrap();
+    assert!(max_diff(&p, &pinv) < 1e-12);
+}
@@ -0,0 +1,101 @@
+// Practice script for the FFT module: checks against a direct DFT for every kind of size,
+// inverse and real transforms, shifts and frequencies, and a PSF from a padded pupil
+// Compile from this folder with: rustc -O p15_fft.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use numerics::complex::Complex;
+use numerics::fft;
+use numerics::rng::Rng;
+use optics::pupil::{circular_mask, polar_grid};
+use std::f64::consts::PI;
+
+fn naive_dft(x: &[Complex<f64>]) -> Vec<Complex<f64>> {
+    // The O(n^2) definition, to check the fast versions against
+    let n = x.len();
+    (0..n)
+        .map(|k| x.iter().enumerate().fold(Complex::new(0.0, 0.0), |acc, (j, &xj)| {
+            acc + xj * Complex::cis(-2.0 * PI * ((j * k) % n) as f64 / n as f64)
+        }))
+        .collect()
+}
+
+fn max_error(a: &[Complex<f64>], b: &[Complex<f64>]) -> f64 {
+    a.iter().zip(b.iter()).fold(0.0, |acc, (x, y)| acc.max((*x - *y).abs()))
+}
+
+fn main(){
+    let mut rng = Rng::new(38);
+
+    // Powers of two, mixed radix (12 = 2^2 3, 210 = 2 3 5 7) and Bluestein (17, 1009 are prime, 34 = 2 17)
+    for &n in &[1, 2, 8, 64, 12, 45, 210, 17, 34, 1009] {
+        let x: Vec<Complex<f64>> = (0..n).map(|_| Complex::new(rng.normal(), rng.normal())).collect();
+        let fast = fft::transform(&x, false);
+        let err = max_error(&fast, &naive_dft(&x));
+        let back = max_error(&fft::transform(&fast, true), &x);
+        println!("n = {:4}: |fft - dft| = {:.1e}, |ifft(fft(x)) - x| = {:.1e}", n, err, back);
+        assert!(err < 1e-9 * n as f64 && back < 1e-12 * n as f64);
+
+        // Parseval: sum |x|^2 = sum |X|^2 / n
+        let energy: f64 = x.iter().map(|z| z.norm_sqr()).sum();
+        let spectrum: f64 = fast.iter().map(|z| z.norm_sqr()).sum::<f64>() / n as f64;
+        assert!((energy - spectrum).abs() < 1e-9 * energy);
+    }
+
+    // A pure tone lands in exactly one bin
+    let n = 48;
+    let tone = Array::from_vec((0..n).map(|j| Complex::cis(2.0 * PI * 5.0 * j as f64 / n as f64)).collect());
+    let spectrum = fft::fft(&tone).unwrap();
+    assert!((spectrum[5].re - n as f64).abs() < 1e-10);
+    assert!(spectrum.iter().enumerate().all(|(k, z)| k == 5 || z.abs() < 1e-10));
+
+    // Real transforms agree with the complex ones, for even and odd lengths, and go back
+    for &n in &[16, 15, 34, 17] {
+        let x: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+        let half = fft::rfft(&Array::from_vec(x.clone())).unwrap();
+        assert_eq!(half.len(), n / 2 + 1);
+        let full = fft::transform(&x.iter().map(|&v| Complex::from(v)).collect::<Vec<_>>(), false);
+        assert!(max_error(half.as_slice(), &full[..n / 2 + 1]) < 1e-10);
+        let back = fft::irfft(&half, n).unwrap();
+        assert!(back.iter().zip(x.iter()).all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+
+    // Frequencies and shifts, as in numpy
+    assert_eq!(fft::fftfreq(5, 0.1).as_slice(), &[0.0, 2.0, 4.0, -4.0, -2.0]);
+    assert_eq!(fft::rfftfreq(4, 1.0).as_slice(), &[0.0, 0.25, 0.5]);
+    let freq = fft::fftfreq(5, 1.0);
+    let shifted = fft::fftshift(&freq);
+    println!("fftshift(fftfreq(5)) = {:?}", shifted.as_slice());
+    assert!(shifted.as_slice().windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(fft::ifftshift(&shifted), freq);
+    let grid = Array::from_shape_vec(&[2, 3], vec![0, 1, 2, 3, 4, 5]).unwrap();
+    assert_eq!(fft::fftshift(&grid).as_slice(), &[5, 3, 4, 2, 0, 1]);
+
+    // Padding
+    let small = Array::from_shape_vec(&[2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    assert_eq!(fft::zero_pad(&small, &[3, 3]).unwrap().as_slice(), &[1.0, 2.0, 0.0, 3.0, 4.0, 0.0, 0.0, 0.0, 0.0]);
+    assert_eq!(fft::zero_pad_centered(&small, &[4, 4]).unwrap()[(2, 2)], 4.0);
+    assert!(fft::zero_pad(&small, &[1, 4]).is_err());
+
+    // PSF of a circular pupil: pad 4x, transform, |.|^2, centre. The Airy pattern has its
+    // first dark ring at 1.22 lambda / D, which is 1.22 * 4 = 4.9 pixels here
+    let (n_pix, pad) = (64, 4);
+    let (rho, _) = polar_grid(n_pix);
+    let pupil = circular_mask(&rho, 1.0).map(|&inside| Complex::from(if inside { 1.0 } else { 0.0 }));
+    let field = fft::fft2(&fft::zero_pad_centered(&pupil, &[pad * n_pix, pad * n_pix]).unwrap()).unwrap();
+    let psf = fft::fftshift(&field.map(|z| z.norm_sqr()));
+    let c = pad * n_pix / 2;
+    let peak = psf[(c, c)];
+    assert!(psf.iter().all(|&v| v <= peak));
+    let profile: Vec<f64> = (0..8).map(|k| psf[(c, c + k)] / peak).collect();
+    let first_min = (1..8).min_by(|&i, &j| profile[i].total_cmp(&profile[j])).unwrap();
+    println!("PSF profile: {:?}", profile.iter().map(|v| format!("{:.4}", v)).collect::<Vec<_>>());
+    assert_eq!(first_min, 5);
+    assert!(profile[first_min] < 0.01);
+
+    // The 2D inverse gets the padded pupil back
+    let back = fft::ifft2(&field).unwrap();
+    assert!((back[(c, c)].re - 1.0).abs() < 1e-10 && back[(0, 0)].abs() < 1e-10);
+}
@@ -0,0 +1,93 @@
+// Practice script for complex numbers and complex arrays: arithmetic, exp / ln / powers,
+// and a complex pupil field built from a Zernike wavefront, with its Strehl ratio
+// Compile from this folder with: rustc -O p16_complex.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use numerics::complex::Complex;
+use numerics::fft;
+use optics::pupil::{circular_mask, polar_grid, pupil_field, Aperture};
+use optics::zernike::Zernike;
+use std::f64::consts::PI;
+
+fn close(a: Complex<f64>, b: Complex<f64>) -> bool {
+    (a - b).abs() < 1e-12
+}
+
+fn main(){
+    // Arithmetic
+    let z = Complex::new(1.0, 2.0);
+    let w = Complex::new(3.0, -1.0);
+    println!("z = {}, w = {}, z w = {}, z / w = {:.2}", z, w, z * w, z / w);
+    assert_eq!(z * w, Complex::new(5.0, 5.0));
+    assert!(close(z / w * w, z));
+    assert_eq!(Complex::<f64>::i() * Complex::i(), Complex::from(-1.0));
+    assert_eq!(z * 2.0 + 1.0, Complex::new(3.0, 4.0));
+    assert_eq!(z.conj(), Complex::new(1.0, -2.0));
+    assert_eq!(format!("{:.1}", w), "3.0-1.0i");
+    let mut acc = z;
+    acc *= w;
+    acc -= 5.0;
+    assert_eq!(acc, Complex::new(0.0, 5.0));
+
+    // exp, ln, roots and powers
+    assert!(close(Complex::new(0.0, PI).exp(), Complex::from(-1.0)));           // e^(i pi) = -1
+    assert!(close(Complex::from(-1.0).ln(), Complex::new(0.0, PI)));
+    assert!(close(Complex::from(-4.0).sqrt(), Complex::new(0.0, 2.0)));
+    assert!(close(z.powi(3), z * z * z) && close(z.powi(-2), (z * z).inv()));
+    assert!(close(z.powf(0.5), z.sqrt()));
+    // Zero base: 0^0 = 1 like f64::powf(0.0, 0.0), 0^p = 0 for Re p > 0 and inf for Re p < 0
+    let zero = Complex::<f64>::from(0.0);
+    assert_eq!((zero.powf(0.0), zero.powc(Complex::from(0.0))), (Complex::from(1.0), Complex::from(1.0)));
+    assert_eq!((zero.powf(2.5), zero.powc(Complex::new(1.0, 3.0))), (zero, zero));
+    assert_eq!(zero.powf(-1.0), Complex::from(f64::INFINITY));
+    assert_eq!(zero.powc(Complex::new(-0.5, 1.0)), Complex::from(f64::INFINITY));
+    assert!(zero.powc(Complex::i()).is_nan());
+    let i_to_the_i = Complex::<f64>::i().powc(Complex::i());
+    println!("i^i = {}", i_to_the_i);
+    assert!(close(i_to_the_i, Complex::from((-PI / 2.0).exp())));
+    assert!((z.abs() - 5.0_f64.sqrt()).abs() < 1e-15 && (Complex::new(-1.0, 0.0).arg() - PI).abs() < 1e-15);
+    assert!(close(Complex::from_polar(2.0, PI / 2.0), Complex::new(0.0, 2.0)));
+
+    // Complex arrays: built from real ones, element-wise ops, and the parts back out
+    let phase = Array::from_vec(vec![0.0, PI / 2.0, PI, -PI / 2.0]);
+    let unit = phase.cis();
+    assert!(close(unit[1], Complex::i()) && close(unit[2], Complex::from(-1.0)));
+    let field = &unit * &Array::full(&[4], Complex::from(2.0));
+    assert!(field.abs().iter().all(|&r| (r - 2.0).abs() < 1e-15));
+    assert!(field.arg().iter().zip(phase.iter()).all(|(a, b)| (a - b).abs() < 1e-15));
+    let intensity = (&field * &field.conj()).re();
+    assert_eq!(intensity, field.norm_sqr());
+    let rebuilt = Array::from_re_im(&field.re(), &field.im()).unwrap();
+    assert_eq!(rebuilt, field);
+    let column = Array::from_shape_vec(&[2, 1], vec![1.0, 3.0]).unwrap();
+    assert_eq!(unit.scale_by(&column).unwrap().shape(), &[2, 4]);     // Broadcasts
+
+    // A pupil field from a wavefront in nm: 40 nm RMS of defocus at 633 nm
+    let (n_pix, wavelength, defocus_nm) = (64, 633.0, 40.0);
+    let (rho, theta) = polar_grid(n_pix);
+    let mask = circular_mask(&rho, 1.0);
+    let aperture = Aperture::new(&mask, &rho, &theta).unwrap();
+    let zern = Zernike::new();
+    let w: Vec<f64> = zern.z_j(4, &aperture.rho, &aperture.theta, "Standard").iter().map(|z| defocus_nm * z).collect();
+    let wavefront = aperture.scatter(&w, f64::NAN).unwrap();
+    let amplitude = mask.map(|&inside| if inside { 1.0 } else { 0.0 });
+    let aberrated = pupil_field(&amplitude, &wavefront, wavelength).unwrap();
+    let perfect = pupil_field(&amplitude, &Array::zeros(&[n_pix, n_pix]), wavelength).unwrap();
+
+    // Strehl ratio = peak of the aberrated PSF / peak of the perfect one
+    let pad = [4 * n_pix, 4 * n_pix];
+    let peak = |field: &Array<Complex<f64>>| {
+        let psf = fft::fft2(&fft::zero_pad_centered(field, &pad).unwrap()).unwrap().norm_sqr();
+        psf.iter().cloned().fold(0.0, f64::max)
+    };
+    let strehl = peak(&aberrated) / peak(&perfect);
+    let sigma = 2.0 * PI * defocus_nm / wavelength;
+    println!("Strehl {:.4}, Marechal exp(-sigma^2) {:.4}", strehl, (-sigma * sigma).exp());
+    assert!((strehl - (-sigma * sigma).exp()).abs() < 0.01);
+    // For a pure phase error the peak is the on-axis term |mean of exp(i phi)|^2
+    let on_axis = aberrated.as_slice().iter().fold(Complex::new(0.0, 0.0), |acc, &z| acc + z) / aperture.len() as f64;
+    assert!((on_axis.norm_sqr() - strehl).abs() < 1e-10);
+}
@@ -0,0 +1,149 @@
+// Practice script for polynomials: Horner evaluation, arithmetic, composition,
+// derivatives / integrals, roots from the companion matrix, and the Legendre / Chebyshev /
+// Jacobi families (the Jacobi ones being what the Zernike radial polynomials are made of)
+// Compile from this folder with: rustc -O p17_polynomial.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use numerics::complex::Complex;
+use numerics::linalg;
+use numerics::polynomial::{self, Polynomial};
+use numerics::ranges::linspace;
+use optics::zernike::{self, Zernike};
+
+fn close(a: f64, b: f64, tol: f64) -> bool {
+    (a - b).abs() < tol
+}
+
+fn main(){
+    // Building and evaluating
+    let p = Polynomial::new(vec![1.0, -2.0, 0.0, 0.5, 0.0]);        // trailing zero trimmed
+    println!("p(x) = {}, degree {:?}", p, p.degree());
+    assert_eq!(p.degree(), Some(3));
+    assert_eq!(p.eval(2.0), 1.0 - 4.0 + 4.0);
+    assert_eq!(format!("{:.1}", p), "1.0 - 2.0x + 0.5x^3");
+    assert_eq!(p.eval_slice(&[0.0, 1.0]), vec![1.0, -0.5]);
+    let x = linspace(-1.0, 1.0, 5);
+    assert_eq!(p.eval_array(&x).shape(), &[5]);
+    assert_eq!(Polynomial::<f64>::zero().degree(), None);
+
+    // Integer coefficients work too
+    let q = Polynomial::new(vec![1, 1]);                            // 1 + x
+    let q3 = &(&q * &q) * &q;
+    assert_eq!(q3.coefficients(), &[1, 3, 3, 1]);
+    assert_eq!(q3.derivative().coefficients(), &[3, 6, 3]);
+    assert_eq!(q3.eval(2), 27);
+
+    // Arithmetic and composition
+    let a = Polynomial::new(vec![1.0, 2.0]);                        // 1 + 2x
+    let b = Polynomial::new(vec![0.0, 0.0, 3.0]);                   // 3x^2
+    assert_eq!((&a + &b).coefficients(), &[1.0, 2.0, 3.0]);
+    assert_eq!(&(&a + &b) - &b, a);
+    assert_eq!((a.clone() * b.clone()).coefficients(), &[0.0, 0.0, 3.0, 6.0]);
+    let ab = a.compose(&b);                                         // 1 + 6x^2
+    let ba = b.compose(&a);                                         // 3 (1 + 2x)^2
+    println!("a(b(x)) = {}, b(a(x)) = {}", ab, ba);
+    assert_eq!(ab.coefficients(), &[1.0, 0.0, 6.0]);
+    assert_eq!(ba.coefficients(), &[3.0, 12.0, 12.0]);
+
+    // Long division: (x^3 - 1) / (x - 1) = x^2 + x + 1
+    let (quot, rem) = Polynomial::new(vec![-1.0, 0.0, 0.0, 1.0]).div_rem(&Polynomial::new(vec![-1.0, 1.0]));
+    assert_eq!(quot.coefficients(), &[1.0, 1.0, 1.0]);
+    assert!(rem.is_zero());
+
+    // Derivative and integral undo each other
+    assert_eq!(p.derivative().coefficients(), &[-2.0, 0.0, 1.5]);
+    assert_eq!(p.derivative().integral(1.0), p);
+    assert!(close(b.integrate(0.0, 2.0), 8.0, 1e-14));
+
+    // Roots: eigenvalues of the companion matrix
+    let cubic = Polynomial::from_roots(&[1.0, 2.0, 3.0]);
+    println!("{} has roots {:?}", cubic, cubic.real_roots(1e-9).unwrap());
+    let roots = cubic.real_roots(1e-9).unwrap();
+    for (r, expected) in roots.iter().zip([1.0, 2.0, 3.0]) {
+        assert!(close(*r, expected, 1e-10));
+    }
+    let roots = Polynomial::new(vec![1.0, 0.0, 1.0]).roots().unwrap();   // x^2 + 1
+    assert!((roots[0] - Complex::new(0.0, -1.0)).abs() < 1e-12);
+    assert!((roots[1] - Complex::new(0.0, 1.0)).abs() < 1e-12);
+    // x^5 - 1: the fifth roots of unity
+    let unity = Polynomial::new(vec![-1.0, 0.0, 0.0, 0.0, 0.0, 1.0]).roots().unwrap();
+    assert_eq!(unity.len(), 5);
+    for z in &unity {
+        assert!((z.powi(5) - 1.0).abs() < 1e-12);
+    }
+    // Coefficients spanning many orders of magnitude (Wilkinson-like)
+    let wide = Polynomial::from_roots(&[1e-3, 0.5, 20.0, 300.0]);
+    let roots = wide.real_roots(1e-6).unwrap();
+    for (r, expected) in roots.iter().zip([1e-3, 0.5, 20.0, 300.0]) {
+        assert!(close(*r / expected, 1.0, 1e-9));
+    }
+
+    // The eigenvalue solver on its own: a rotation-like block and a real eigenvalue
+    let m = Array::from_rows(vec![
+        vec![0.0, -2.0, 1.0],
+        vec![2.0, 0.0, 0.5],
+        vec![0.0, 0.0, 3.0],
+    ]).unwrap();
+    let eig = linalg::eigvals(&m).unwrap();
+    println!("eigenvalues: {:?}", eig);
+    assert!((eig[0] - Complex::new(0.0, -2.0)).abs() < 1e-12);
+    assert!((eig[1] - Complex::new(0.0, 2.0)).abs() < 1e-12);
+    assert!((eig[2] - Complex::from(3.0)).abs() < 1e-12);
+    // A cyclic permutation, where the plain shifts stall and the exceptional ones take
+    // over: the eigenvalues are the 4th roots of unity
+    let cycle = Array::from_shape_vec(&[4, 4], (0..16).map(|k| f64::from(k % 4 == (k / 4 + 3) % 4)).collect()).unwrap();
+    let eig = linalg::eigvals(&cycle).unwrap();
+    let expected = [Complex::from(-1.0), Complex::new(0.0, -1.0), Complex::new(0.0, 1.0), Complex::from(1.0)];
+    for (&z, &expected) in eig.iter().zip(&expected) {
+        assert!((z - expected).abs() < 1e-12);
+    }
+
+    // Legendre: known low orders, P_n(1) = 1, orthogonality with norm 2 / (2n + 1)
+    assert_eq!(polynomial::legendre(2).coefficients(), &[-0.5, 0.0, 1.5]);
+    for n in 0..8 {
+        let pn = polynomial::legendre(n);
+        assert!(close(pn.eval(1.0), 1.0, 1e-12));
+        assert!(close(pn.eval(0.3), polynomial::legendre_eval(n, 0.3), 1e-12));
+        for k in 0..8 {
+            let integral = (&pn * &polynomial::legendre(k)).integrate(-1.0, 1.0);
+            let expected = if n == k { 2.0 / (2 * n + 1) as f64 } else { 0.0 };
+            assert!(close(integral, expected, 1e-12));
+        }
+    }
+    // Legendre roots are the Gauss-Legendre nodes, all real inside (-1, 1)
+    let nodes = polynomial::legendre(5).real_roots(1e-9).unwrap();
+    println!("P_5 roots: {:?}", nodes);
+    assert_eq!(nodes.len(), 5);
+    assert!(close(nodes[2], 0.0, 1e-12) && close(nodes[4], 0.906179845938664, 1e-12));
+
+    // Chebyshev: T_n(cos t) = cos(n t)
+    assert_eq!(polynomial::chebyshev(3).coefficients(), &[0.0, -3.0, 0.0, 4.0]);
+    for n in 0..10 {
+        for &t in &[0.1, 0.7, 2.5] {
+            assert!(close(polynomial::chebyshev(n).eval(f64::cos(t)), (n as f64 * t).cos(), 1e-12));
+            assert!(close(polynomial::chebyshev_eval(n, f64::cos(t)), (n as f64 * t).cos(), 1e-12));
+        }
+    }
+
+    // Jacobi with a = b = 0 is Legendre, and the Zernike radial polynomials are Jacobi ones
+    assert!(close(polynomial::jacobi(4, 0.0, 0.0).eval(0.4), polynomial::legendre_eval(4, 0.4), 1e-14));
+    assert!(close(polynomial::jacobi(3, 1.5, 0.5).eval(-0.2), polynomial::jacobi_eval(3, 1.5, 0.5, -0.2), 1e-12));
+    let z = Zernike::new();
+    let rho = linspace(0.0, 1.0, 11).into_vec();
+    for (n, m) in [(2, 0), (4, 0), (3, 1), (5, -3), (6, 2), (8, 4)] {
+        let explicit = z.r_nm(n, m, &rho);
+        let jacobi = z.r_nm_jacobi(n, m, &rho);
+        let radial = zernike::radial_polynomial(n, m);
+        for i in 0..rho.len() {
+            assert!(close(explicit[i], jacobi[i], 1e-12));
+        }
+        // Every R_nm is 1 at the edge of the pupil
+        assert!(close(radial.eval(1.0), 1.0, 1e-12));
+        println!("R_{}^{}(rho) = {}", n, m, radial);
+    }
+    // Defocus R_2^0 = 2 rho^2 - 1, with derivative 4 rho
+    assert_eq!(zernike::radial_polynomial(2, 0).derivative().coefficients(), &[0.0, 4.0]);
+}
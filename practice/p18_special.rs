@@ -0,0 +1,116 @@
+// Practice script for the special functions: exact factorials and binomials (u128 and
+// BigInt), gamma / ln-gamma / beta, Bessel J_n, and the analytic Airy pattern against the
+// FFT of a circular pupil
+// Compile from this folder with: rustc -O p18_special.rs
+
+mod numerics;
+mod optics;
+
+use numerics::complex::Complex;
+use numerics::fft;
+use numerics::special::{
+    airy, bessel_j, beta, binomial, binomial_big, factorial, factorial_big, factorial_f64, gamma, jinc, ln_beta,
+    ln_factorial, ln_gamma,
+};
+use optics::pupil::{circular_mask, polar_grid};
+use std::f64::consts::PI;
+
+fn close(a: f64, b: f64, tol: f64) -> bool {
+    (a - b).abs() <= tol * b.abs().max(1.0)
+}
+
+fn main(){
+    // Factorials: u128 up to 34!, BigInt after that
+    assert_eq!(factorial(0), Some(1));
+    assert_eq!(factorial(10), Some(3_628_800));
+    assert_eq!(factorial(34).unwrap().to_string(), "295232799039604140847618609643520000000");
+    assert_eq!(factorial(35), None);
+    for n in 0..=34 {
+        assert_eq!(factorial_big(n).to_string(), factorial(n).unwrap().to_string());
+    }
+    println!("50! = {}", factorial_big(50));
+    assert_eq!(factorial_big(50).to_string(), "30414093201713378043612608166064768844377641568960512000000000000");
+    assert_eq!(factorial_f64(20), 2_432_902_008_176_640_000.0);
+    assert!(factorial_f64(171).is_infinite());
+
+    // Binomials: exact, symmetric, Pascal's rule, and no early overflow
+    assert_eq!(binomial(5, 2), Some(10));
+    assert_eq!(binomial(5, 7), Some(0));
+    assert_eq!(binomial(0, 0), Some(1));
+    for n in 1..40 {
+        for k in 1..n {
+            let pascal = binomial(n - 1, k - 1).unwrap() + binomial(n - 1, k).unwrap();
+            assert_eq!(binomial(n, k), Some(pascal));
+            assert_eq!(binomial(n, k), binomial(n, n - k));
+        }
+    }
+    // C(130, 65) ~ 9.5e37 still fits in a u128 although 130! is way past it
+    let c = binomial(130, 65).unwrap();
+    println!("C(130, 65) = {}", c);
+    assert_eq!(binomial_big(130, 65).to_string(), c.to_string());
+    assert_eq!(binomial(140, 70), None);
+    println!("C(200, 100) = {}", binomial_big(200, 100));
+    assert_eq!(binomial_big(200, 100).to_string(), "90548514656103281165404177077484163874504589675413336841320");
+
+    // Gamma: exact at the integers, sqrt(pi) at 1/2, the reflection formula below 1/2
+    for n in 1..=20 {
+        assert_eq!(gamma(n as f64 + 1.0), factorial_f64(n));
+    }
+    assert!(close(gamma(0.5), PI.sqrt(), 1e-14));
+    assert!(close(gamma(-0.5), -2.0 * PI.sqrt(), 1e-14));
+    assert!(close(gamma(4.5), 11.631728396567448, 1e-14));
+    // ln-gamma where gamma itself overflows
+    assert!(gamma(200.0).is_infinite());
+    assert!(close(ln_gamma(200.0), ln_factorial(199), 1e-14));
+    assert!(close(ln_factorial(1000), 5912.128178488163, 1e-13));
+
+    // Beta: B(a, b) = Gamma(a) Gamma(b) / Gamma(a + b), B(1/2, 1/2) = pi
+    assert!(close(beta(2.0, 3.0), 1.0 / 12.0, 1e-14));
+    assert!(close(beta(0.5, 0.5), PI, 1e-14));
+    assert!(close(beta(2.5, 1.5), beta(1.5, 2.5), 1e-15));
+    assert!(close(ln_beta(300.0, 400.0), ln_gamma(300.0) + ln_gamma(400.0) - ln_gamma(700.0), 1e-14));
+    assert!(beta(300.0, 400.0) > 0.0);
+
+    // Bessel: reference values, J_(-n) = (-1)^n J_n, and the recurrence
+    // J_(n-1)(x) + J_(n+1)(x) = 2n / x J_n(x)
+    assert!(close(bessel_j(0, 1.0), 0.7651976865579666, 1e-14));
+    assert!(close(bessel_j(1, 1.0), 0.4400505857449335, 1e-14));
+    assert!(close(bessel_j(5, 10.0), -0.23406152818679365, 1e-13));
+    assert!(close(bessel_j(-3, 2.5), -bessel_j(3, 2.5), 1e-15));
+    for n in 1..10 {
+        let x = 7.3;
+        let lhs = bessel_j(n - 1, x) + bessel_j(n + 1, x);
+        assert!(close(lhs, 2.0 * n as f64 / x * bessel_j(n, x), 1e-13));
+    }
+    // Large x goes through Hankel's expansion: same values and recurrence, any size of x
+    assert!(close(bessel_j(0, 100.0), 0.019985850304223122, 1e-15));
+    assert!(close(bessel_j(1, 100.0), -0.07714535201411216, 1e-15));
+    assert!(close(bessel_j(3, -250.0), -bessel_j(3, 250.0), 1e-15));
+    for n in 1..10 {
+        let x = 1234.5;
+        let lhs = bessel_j(n - 1, x) + bessel_j(n + 1, x);
+        assert!(close(lhs, 2.0 * n as f64 / x * bessel_j(n, x), 1e-15));
+    }
+    let far = bessel_j(0, 1e18);
+    assert!(far.abs() <= (2.0 / (std::f64::consts::PI * 1e18)).sqrt());
+    assert!(bessel_j(0, f64::INFINITY).is_nan() && bessel_j(2, f64::NAN).is_nan());
+    // The first zero of J_1 is the first dark ring of the Airy pattern
+    assert!(bessel_j(1, 3.8317059702075125).abs() < 1e-14);
+    assert_eq!(jinc(0.0), 1.0);
+    assert!(airy(3.8317059702075125) < 1e-28);
+
+    // Analytic Airy pattern vs the FFT of a circular pupil padded 4x, along a row of the PSF.
+    // Pixel k of the padded transform is at sin(angle) = k lambda / (4 D), so x = pi k / 4
+    let (n_pix, pad) = (64, 4);
+    let (rho, _) = polar_grid(n_pix);
+    let pupil = circular_mask(&rho, 1.0).map(|&inside| Complex::from(if inside { 1.0 } else { 0.0 }));
+    let field = fft::fft2(&fft::zero_pad_centered(&pupil, &[pad * n_pix, pad * n_pix]).unwrap()).unwrap();
+    let psf = fft::fftshift(&field.map(|z| z.norm_sqr()));
+    let c = pad * n_pix / 2;
+    for k in 0..16 {
+        let numeric = psf[(c, c + k)] / psf[(c, c)];
+        let analytic = airy(PI * k as f64 / pad as f64);
+        println!("pixel {:2}: FFT {:.5}  Airy {:.5}", k, numeric, analytic);
+        assert!((numeric - analytic).abs() < 0.01);
+    }
+}
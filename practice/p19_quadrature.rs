@@ -0,0 +1,89 @@
+// Practice script for Gaussian quadrature: Gauss-Legendre and Gauss-Jacobi rules, and the
+// polar rules over the unit disk and an annulus, used to check that the Zernike
+// polynomials are orthonormal (<Z_i, Z_j> = pi delta_ij over the unit disk)
+// Compile from this folder with: rustc -O p19_quadrature.rs
+
+mod numerics;
+mod optics;
+
+use numerics::quadrature::{annulus, disk, gauss_jacobi, gauss_legendre};
+use numerics::special::beta;
+use optics::zernike::Zernike;
+use std::f64::consts::PI;
+
+fn close(a: f64, b: f64, tol: f64) -> bool {
+    (a - b).abs() <= tol * b.abs().max(1.0)
+}
+
+fn main(){
+    // Gauss-Legendre: known nodes, weights summing to 2, exact up to degree 2n - 1
+    let gl = gauss_legendre(3);
+    println!("Gauss-Legendre 3: nodes {:?}, weights {:?}", gl.nodes, gl.weights);
+    assert!(close(gl.nodes[2], (0.6f64).sqrt(), 1e-15) && close(gl.weights[1], 8.0 / 9.0, 1e-15));
+    for n in 1..20 {
+        let rule = gauss_legendre(n);
+        assert!(close(rule.weights.iter().sum::<f64>(), 2.0, 1e-14));
+        for k in 0..2 * n {
+            let exact = if k % 2 == 1 { 0.0 } else { 2.0 / (k + 1) as f64 };
+            assert!(close(rule.integrate(|x| x.powi(k as i32)), exact, 1e-13));
+        }
+    }
+    // Smooth non-polynomial integrands converge very fast
+    assert!(close(gauss_legendre(12).integrate_interval(0.0, PI, f64::sin), 2.0, 1e-14));
+    assert!(close(gauss_legendre(64).integrate_interval(0.0, 1.0, |x| 1.0 / (1.0 + x * x)), PI / 4.0, 1e-14));
+
+    // Gauss-Jacobi: exact for int (1 - x)^a (1 + x)^b (1 + x)^k dx = 2^(a + b + k + 1) B(a + 1, b + k + 1)
+    // for k up to 2n - 1
+    let (a, b) = (1.5, -0.5);
+    let gj = gauss_jacobi(6, a, b).unwrap();
+    for k in 0..12 {
+        let exact = 2f64.powf(a + b + k as f64 + 1.0) * beta(a + 1.0, b + k as f64 + 1.0);
+        assert!(close(gj.integrate(|x| (1.0 + x).powi(k)), exact, 1e-13));
+    }
+    // Legendre is the a = b = 0 case
+    let gj0 = gauss_jacobi(7, 0.0, 0.0).unwrap();
+    for (x, y) in gj0.nodes.iter().zip(gauss_legendre(7).nodes.iter()) {
+        assert!(close(*x, *y, 1e-14));
+    }
+    // Chebyshev weight a = b = -1/2: all the weights are pi / n
+    let cheb = gauss_jacobi(5, -0.5, -0.5).unwrap();
+    assert!(cheb.weights.iter().all(|&w| close(w, PI / 5.0, 1e-13)));
+
+    // The unit disk: area, moments of x^2 and r^4, exact up to the chosen degree
+    let rule = disk(8);
+    println!("disk rule of degree 8: {} points", rule.len());
+    assert!(close(rule.area(), PI, 1e-14));
+    assert!(close(rule.integrate(|r, t| (r * t.cos()).powi(2)), PI / 4.0, 1e-14));
+    assert!(close(rule.integrate(|r, _| r.powi(4)), PI / 3.0, 1e-14));
+    assert!(close(rule.integrate(|r, t| (r * t.cos()).powi(4) * (r * t.sin()).powi(2)), PI / 64.0, 1e-14));
+    assert!(rule.integrate(|r, t| (r * t.cos()).powi(3) * r * t.sin()).abs() < 1e-15);
+
+    // Zernike orthonormality: <Z_i, Z_j> / pi = delta_ij for the first 28 modes (n <= 6).
+    // Products have degree <= 12
+    let z = Zernike::new();
+    let rule = disk(12);
+    let n_modes = 28;
+    let modes: Vec<Vec<f64>> = (1..=n_modes).map(|j| z.z_j(j, &rule.rho, &rule.theta, "Standard")).collect();
+    let mut worst: f64 = 0.0;
+    for i in 0..n_modes {
+        for j in 0..n_modes {
+            let product: Vec<f64> = modes[i].iter().zip(modes[j].iter()).map(|(a, b)| a * b).collect();
+            let inner = rule.integrate_samples(&product) / PI;
+            let expected = if i == j { 1.0 } else { 0.0 };
+            worst = worst.max((inner - expected).abs());
+        }
+    }
+    println!("Zernike orthonormality: worst error {:.2e} over {} x {} inner products", worst, n_modes, n_modes);
+    assert!(worst < 1e-13);
+
+    // Annulus: area pi (1 - eps^2), and int r^2 = pi (1 - eps^4) / 2
+    let eps = 0.3;
+    let ring = annulus(eps, 1.0, 6);
+    assert!(close(ring.area(), PI * (1.0 - eps * eps), 1e-14));
+    assert!(close(ring.integrate(|r, _| r * r), PI * (1.0 - eps.powi(4)) / 2.0, 1e-14));
+    assert!(ring.rho.iter().all(|&r| r > eps && r < 1.0));
+    // Defocus is not orthogonal to piston any more on the annulus: int (2 r^2 - 1)
+    let overlap = ring.integrate(|r, _| 2.0 * r * r - 1.0);
+    println!("<Z_1, R_2^0> on the annulus: {:.6}", overlap);
+    assert!(close(overlap, PI * (1.0 - eps.powi(4)) - PI * (1.0 - eps * eps), 1e-14));
+}
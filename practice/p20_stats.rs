@@ -0,0 +1,106 @@
+// Practice script for the statistics module: means of integers without truncation,
+// variances without cancellation, medians / quantiles, weighted statistics, histograms,
+// and Welford accumulators filled by several threads and merged
+// Compile from this folder with: rustc -O p20_stats.rs
+
+mod numerics;
+
+use numerics::array::Array;
+use numerics::rng::Rng;
+use numerics::stats::{self, Welford, WeightedWelford};
+use std::thread;
+
+fn close(a: f64, b: f64, tol: f64) -> bool {
+    (a - b).abs() <= tol * b.abs().max(1.0)
+}
+
+fn main(){
+    // Integers: 1.5, not the 1 of (1 + 2) / 2
+    assert_eq!(stats::mean(&[1, 2]), 1.5);
+    let num: Vec<usize> = (0..=1001).collect();
+    println!("mean of 0..=1001 = {}", stats::mean(&num));
+    assert_eq!(stats::mean(&num), 500.5);
+    assert_eq!(stats::median(&[3u8, 1, 2]), 2.0);
+    assert_eq!(stats::median(&[4i64, 1, 3, 2]), 2.5);
+    assert!(stats::mean::<f64>(&[]).is_nan());
+
+    // Cancellation: a big offset, a small spread. The textbook sum(x^2)/n - mean^2 loses
+    // everything, the two-pass and Welford ones don't
+    let data: Vec<f64> = [4.0, 7.0, 13.0, 16.0].iter().map(|x| 1e9 + x).collect();
+    let n = data.len() as f64;
+    let naive = (data.iter().map(|x| x * x).sum::<f64>() - data.iter().sum::<f64>().powi(2) / n) / (n - 1.0);
+    println!("sample variance: naive {}, two-pass {}", naive, stats::variance(&data, 1));
+    assert_eq!(stats::variance(&data, 1), 30.0);
+    assert_eq!(stats::variance(&data, 0), 22.5);
+    let mut acc = Welford::new();
+    acc.extend(&data);
+    assert!(close(acc.variance(1), 30.0, 1e-9) && acc.mean() == 1e9 + 10.0);
+    // Compensated sums: 1 + 1e-16 + ... adds up where a plain fold stays at 1
+    let tiny: Vec<f64> = std::iter::once(1.0).chain(std::iter::repeat_n(1e-16, 10_000)).collect();
+    assert!(close(stats::sum(&tiny), 1.0 + 1e-12, 1e-15));
+    assert_eq!(tiny.iter().sum::<f64>(), 1.0);
+
+    // Quantiles: linear interpolation like numpy
+    let x = [1.0, 2.0, 3.0, 4.0];
+    assert_eq!(stats::quantiles(&x, &[0.0, 0.25, 0.5, 1.0]), vec![1.0, 1.75, 2.5, 4.0]);
+    assert_eq!(stats::quantile(&[10, 20, 30], 0.9), 28.0);
+
+    // Weighted: integer weights give the same as repeating the values
+    let values = [1.0, 2.0, 5.0];
+    let weights = [3, 1, 2];
+    let repeated = [1.0, 1.0, 1.0, 2.0, 5.0, 5.0];
+    assert!(close(stats::weighted_mean(&values, &weights), stats::mean(&repeated), 1e-15));
+    assert!(close(stats::weighted_variance(&values, &weights), stats::variance(&repeated, 0), 1e-15));
+    let mut wacc = WeightedWelford::new();
+    for (&v, &w) in values.iter().zip(weights.iter()) {
+        wacc.push(v, w as f64);
+    }
+    assert!(close(wacc.variance(), stats::weighted_variance(&values, &weights), 1e-14));
+
+    // Histograms: numpy's edges and the closed last bin
+    let h = stats::histogram(&[1, 2, 2, 3, 3, 3, 4], 3, None);
+    println!("histogram: edges {:?}, counts {:?}", h.edges, h.counts);
+    assert_eq!(h.counts, vec![1, 2, 4]);
+    assert_eq!(h.total(), 7);
+    let h = stats::histogram(&[0.5, 1.5, 9.0, -1.0], 2, Some((0.0, 2.0)));
+    assert_eq!(h.counts, vec![1, 1]);
+    assert_eq!(h.centers(), vec![0.5, 1.5]);
+    assert!(close(h.density().iter().sum::<f64>(), 1.0, 1e-15));
+
+    // Gaussian samples: the histogram density follows exp(-x^2/2)/sqrt(2 pi)
+    let mut rng = Rng::new(7);
+    let samples: Vec<f64> = (0..200_000).map(|_| rng.normal()).collect();
+    let h = stats::histogram(&samples, 40, Some((-4.0, 4.0)));
+    for (c, d) in h.centers().iter().zip(h.density()) {
+        let expected = (-c * c / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt();
+        assert!((d - expected).abs() < 0.01);
+    }
+    let q = stats::quantiles(&samples, &[0.1587, 0.5, 0.8413]);
+    println!("normal samples: mean {:.4}, std {:.4}, 16/50/84% quantiles {:.3?}", stats::mean(&samples), stats::std(&samples, 1), q);
+    assert!(stats::mean(&samples).abs() < 0.01 && close(stats::std(&samples, 1), 1.0, 0.01));
+    assert!(close(q[0], -1.0, 0.02) && close(q[2], 1.0, 0.02));
+
+    // Threads: each one fills its own Welford, merged at the end
+    let chunks: Vec<&[f64]> = samples.chunks(50_000).collect();
+    let merged = thread::scope(|s| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|&chunk| {
+                s.spawn(move || {
+                    let mut acc = Welford::new();
+                    acc.extend(chunk);
+                    acc
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).fold(Welford::new(), |a, b| a.merge(&b))
+    });
+    assert_eq!(merged.count(), samples.len());
+    assert!(close(merged.mean(), stats::mean(&samples), 1e-12));
+    assert!(close(merged.variance(1), stats::variance(&samples, 1), 1e-12));
+    assert_eq!((merged.min(), merged.max()), stats::min_max(&samples).unwrap());
+
+    // Arrays through their slice
+    let a = Array::from_shape_vec(&[2, 3], vec![1, 2, 3, 4, 5, 6]).unwrap();
+    assert_eq!(stats::mean(a.as_slice()), 3.5);
+}
@@ -0,0 +1,143 @@
+// Practice script for interpolation: 1D methods on uneven samples, 2D sampling that
+// reproduces low order polynomials, and resampling / rotating a masked Zernike wavefront
+// without the NaN outside the pupil leaking in
+// Compile from this folder with: rustc -O p21_interp.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use numerics::interp::{fill_nan, interp1d, interp2d, resample, rotate, CubicSpline, Interpolator2d, Method};
+use numerics::ranges::linspace;
+use optics::pupil::{circular_mask, polar_grid};
+use optics::zernike::Zernike;
+use std::f64::consts::PI;
+
+fn masked_mode(z: &Zernike, n_pix: usize, n: i32, m: i32) -> Array<f64> {
+    // Z_nm on the n_pix grid, NaN outside the unit disk
+    let (rho, theta) = polar_grid(n_pix);
+    let values = z.z_nm(n, m, rho.as_slice(), theta.as_slice(), "Standard");
+    let mask = circular_mask(&rho, 1.0);
+    let data = values.iter().zip(mask.iter()).map(|(&v, &inside)| if inside { v } else { f64::NAN }).collect();
+    Array::from_shape_vec(&[n_pix, n_pix], data).unwrap()
+}
+
+fn innermost_lost(result: &Array<f64>, exact: &Array<f64>) -> f64 {
+    // Smallest rho of a pixel inside the pupil that came out NaN (2.0 if none did)
+    let (rho, _) = polar_grid(exact.shape()[0]);
+    result
+        .iter()
+        .zip(exact.iter())
+        .zip(rho.iter())
+        .filter(|((a, b), _)| a.is_nan() && !b.is_nan())
+        .map(|(_, &r)| r)
+        .fold(2.0, f64::min)
+}
+
+fn max_error(a: &Array<f64>, b: &Array<f64>) -> f64 {
+    // Over the pixels where both are defined
+    a.iter().zip(b.iter()).filter(|(x, y)| !x.is_nan() && !y.is_nan()).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max)
+}
+
+fn main(){
+    // 1D: uneven x, sin(x) sampled at 12 points
+    let x: Vec<f64> = (0..12).map(|k| (k as f64 / 11.0).powf(1.3) * PI).collect();
+    let y: Vec<f64> = x.iter().map(|v| v.sin()).collect();
+    let xi = linspace(0.0, PI, 101).into_vec();
+    for method in [Method::Nearest, Method::Linear, Method::Cubic, Method::Spline] {
+        let yi = interp1d(&x, &y, &xi, method).unwrap();
+        let err = yi.iter().zip(xi.iter()).map(|(a, t)| (a - t.sin()).abs()).fold(0.0, f64::max);
+        println!("1D {:?}: max error {:.2e}", method, err);
+        let tol = match method {
+            Method::Nearest => 0.2,
+            Method::Linear => 0.02,
+            _ => 3e-3,
+        };
+        assert!(err < tol);
+    }
+    // Every method passes through the samples, and outside the range is NaN
+    assert_eq!(interp1d(&x, &y, &x, Method::Cubic).unwrap(), y);
+    assert!(interp1d(&x, &y, &[-0.1, 4.0], Method::Linear).unwrap().iter().all(|v| v.is_nan()));
+    // NaN samples are skipped
+    assert_eq!(interp1d(&[0.0, 1.0, 2.0], &[0.0, f64::NAN, 4.0], &[1.0], Method::Linear).unwrap(), vec![2.0]);
+    assert!(interp1d(&[0.0, 1.0], &[0.0], &[0.5], Method::Linear).is_err());
+
+    // The natural spline is exact for straight lines and has a continuous derivative
+    let spline = CubicSpline::new(&[0.0, 1.0, 3.0, 4.0], &[1.0, 3.0, 7.0, 9.0]).unwrap();
+    assert!((spline.eval(2.2) - 5.4).abs() < 1e-14 && (spline.derivative(0.5) - 2.0).abs() < 1e-14);
+    let s = CubicSpline::new(&x, &y).unwrap();
+    assert!((s.derivative(x[5] - 1e-12) - s.derivative(x[5] + 1e-12)).abs() < 1e-9);
+
+    // 2D: bilinear is exact for planes, bicubic for quadratics. The B-spline would be exact
+    // for cubics on an infinite grid; the mirror borders leave a small error that decays
+    // by 0.27 per pixel inwards
+    let f = |y: f64, x: f64, order: i32| match order {
+        1 => 1.0 + 0.5 * x - 0.25 * y,
+        2 => 1.0 + 0.5 * x - 0.25 * y + 0.1 * x * y - 0.03 * x * x,
+        _ => 0.002 * x * x * x - 0.001 * y * y * x + 0.05 * y * y,
+    };
+    for (method, order) in [(Method::Linear, 1), (Method::Cubic, 2), (Method::Spline, 3)] {
+        let img = Array::from_shape_vec(&[20, 24], (0..480).map(|k| f((k / 24) as f64, (k % 24) as f64, order)).collect())
+            .unwrap();
+        let interp = Interpolator2d::new(&img, method).unwrap();
+        let mut worst: f64 = 0.0;
+        for &(py, px) in &[(7.3, 8.9), (10.5, 12.25), (6.0, 15.75), (12.0, 11.0)] {
+            worst = worst.max((interp.sample(py, px) - f(py, px, order)).abs());
+        }
+        println!("2D {:?} on a degree {} polynomial: max error {:.2e}", method, order, worst);
+        assert!(worst < if method == Method::Spline { 1e-4 } else { 1e-12 });
+        // All of them go through the samples
+        assert!((interp.sample(3.0, 4.0) - img[(3, 4)]).abs() < 1e-13);
+    }
+    let img = Array::from_shape_vec(&[2, 2], vec![0.0, 1.0, 2.0, 3.0]).unwrap();
+    assert_eq!(interp2d(&img, &[0.5, 0.0, -0.6], &[0.5, 1.0, 0.0], Method::Linear).unwrap()[..2], [1.5, 1.0]);
+    assert!(interp2d(&img, &[-0.6], &[0.0], Method::Linear).unwrap()[0].is_nan());
+
+    // fill_nan grows the valid values outwards
+    let holes = Array::from_shape_vec(&[3, 3], vec![f64::NAN, 1.0, f64::NAN, 1.0, 1.0, 1.0, f64::NAN, f64::NAN, f64::NAN])
+        .unwrap();
+    assert!(fill_nan(&holes).unwrap().iter().all(|&v| v == 1.0));
+
+    // Resampling a masked defocus map from 64 to 100 pixels, against the exact map at 100.
+    // Without the fill, every kernel touching the NaN outside would give NaN, and the
+    // pupil would shrink by the kernel radius
+    let z = Zernike::new();
+    let coarse = masked_mode(&z, 64, 2, 0);
+    let exact = masked_mode(&z, 100, 2, 0);
+    for method in [Method::Nearest, Method::Linear, Method::Cubic, Method::Spline] {
+        let fine = resample(&coarse, &[100, 100], method).unwrap();
+        let lost = innermost_lost(&fine, &exact);
+        let err = max_error(&fine, &exact);
+        println!("resample 64 -> 100, {:?}: max error {:.2e}, innermost NaN at rho {:.3}", method, err, lost);
+        // Only the jagged rim of the coarse disk is lost, less than one coarse pixel (2 / 64)
+        assert!(lost > 1.0 - 2.0 / 64.0);
+        if method != Method::Nearest {
+            // The interior is smooth; only the rim sees the extrapolated fill
+            let (rho, _) = polar_grid(100);
+            let inner_err = fine
+                .iter()
+                .zip(exact.iter())
+                .zip(rho.iter())
+                .filter(|(_, &r)| r < 0.9)
+                .map(|((a, b), _)| (a - b).abs())
+                .fold(0.0, f64::max);
+            assert!(inner_err < 0.02, "{:?}: {}", method, inner_err);
+        }
+    }
+    // Down and back up keeps the shape of the map
+    let down = resample(&exact, &[50, 50], Method::Spline).unwrap();
+    assert_eq!(down.shape(), &[50, 50]);
+    assert!(max_error(&down, &masked_mode(&z, 50, 2, 0)) < 0.1);
+
+    // Rotation: a quarter turn is exact even for the linear method; 45 degrees turns
+    // vertical astigmatism Z_(2,2) ~ cos(2 theta) into oblique Z_(2,-2) ~ sin(2 theta)
+    let astig = masked_mode(&z, 64, 2, 2);
+    let quarter = rotate(&astig, PI / 2.0, Method::Linear).unwrap();
+    assert!(max_error(&quarter, &astig.map(|v| -v)) < 1e-12);
+    let turned = rotate(&astig, PI / 4.0, Method::Spline).unwrap();
+    let oblique = masked_mode(&z, 64, 2, -2);
+    let err = max_error(&turned, &oblique);
+    let lost = innermost_lost(&turned, &oblique);
+    println!("rotate Z(2, 2) by 45 degrees vs Z(2, -2): max error {:.2e}, innermost NaN at rho {:.3}", err, lost);
+    assert!(err < 0.1 && lost > 1.0 - 2.0 / 64.0);
+}
@@ -0,0 +1,150 @@
+// Practice script for 2D convolution and filters: direct vs FFT convolution, the Gaussian,
+// box and median filters, Sobel gradients, denoising a masked phase map and blurring a PSF
+// with the detector pixel
+// Compile from this folder with: rustc -O p22_filters.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use numerics::filter::{self, Border, Mode};
+use numerics::rng::Rng;
+use numerics::stats;
+use optics::pupil::{circular_mask, polar_grid};
+use optics::zernike::Zernike;
+
+fn random_image(rng: &mut Rng, rows: usize, cols: usize) -> Array<f64> {
+    Array::from_shape_vec(&[rows, cols], rng.normal_vec(rows * cols)).unwrap()
+}
+
+fn max_diff(a: &Array<f64>, b: &Array<f64>) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max)
+}
+
+fn rms_error(a: &Array<f64>, b: &Array<f64>) -> f64 {
+    // Over the pixels where both are defined
+    let d: Vec<f64> = a.iter().zip(b.iter()).filter(|(x, y)| !x.is_nan() && !y.is_nan()).map(|(x, y)| x - y).collect();
+    (d.iter().map(|v| v * v).sum::<f64>() / d.len() as f64).sqrt()
+}
+
+fn main(){
+    // The scipy.signal.convolve2d example: [[1, 2], [3, 4]] with a 2 x 2 box
+    let a = Array::from_shape_vec(&[2, 2], vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    let ones = Array::from_shape_vec(&[2, 2], vec![1.0; 4]).unwrap();
+    let full = filter::convolve2d(&a, &ones, Mode::Full).unwrap();
+    println!("full:\n{:?}", full);
+    assert_eq!(full.as_slice(), &[1.0, 3.0, 2.0, 4.0, 10.0, 6.0, 3.0, 7.0, 4.0]);
+    assert_eq!(filter::convolve2d(&a, &ones, Mode::Same).unwrap().as_slice(), &[1.0, 3.0, 4.0, 10.0]);
+    assert_eq!(filter::convolve2d(&a, &ones, Mode::Valid).unwrap().as_slice(), &[10.0]);
+    assert!(filter::convolve2d(&Array::zeros(&[4]), &ones, Mode::Full).is_err());
+
+    // Direct and FFT convolution agree in every mode
+    let mut rng = Rng::new(22);
+    let img = random_image(&mut rng, 30, 20);
+    let kernel = random_image(&mut rng, 7, 5);
+    for mode in [Mode::Full, Mode::Same, Mode::Valid] {
+        let direct = filter::convolve2d(&img, &kernel, mode).unwrap();
+        let fast = filter::fft_convolve2d(&img, &kernel, mode).unwrap();
+        println!("{:?}: shape {:?}, direct vs FFT {:.1e}", mode, direct.shape(), max_diff(&direct, &fast));
+        assert_eq!(direct.shape(), fast.shape());
+        assert!(max_diff(&direct, &fast) < 1e-12);
+    }
+    // The method picks one of the two
+    let big = random_image(&mut rng, 12, 12);
+    let picked = img.convolve2d(&big, Mode::Same).unwrap();
+    assert!(max_diff(&picked, &filter::convolve2d(&img, &big, Mode::Same).unwrap()) < 1e-12);
+
+    // A Gaussian kernel sums to 1, and blurring a delta leaves a Gaussian of variance sigma^2
+    // (a little less, from cutting the tails at 4 sigma)
+    let sigma = 2.5;
+    let g = filter::gaussian_kernel(sigma, 10);
+    assert!((g.iter().sum::<f64>() - 1.0).abs() < 1e-15);
+    let mut delta = Array::zeros(&[41, 41]);
+    delta[(20, 20)] = 1.0;
+    let blurred = filter::gaussian_filter(&delta, sigma, Border::Zero).unwrap();
+    let row: Vec<f64> = (0..41).map(|j| (0..41).map(|i| blurred[(i, j)]).sum()).collect();
+    let positions: Vec<f64> = (0..41).map(|j| j as f64 - 20.0).collect();
+    let var = stats::weighted_variance(&positions, &row);
+    println!("Gaussian sigma {}: variance of the blurred delta {:.4}", sigma, var);
+    assert!((var - sigma * sigma).abs() < 0.01);
+    assert!((blurred.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+    // Separable filtering is the same as the 2D kernel made of the outer product
+    let (col, row) = ([1.0, -2.0, 0.5], [0.25, 0.5, 0.25, 1.0]);
+    let products = col.iter().flat_map(|c| row.iter().map(move |r| c * r)).collect();
+    let outer = Array::from_shape_vec(&[3, 4], products).unwrap();
+    for border in [Border::Zero, Border::Reflect, Border::Nearest, Border::Wrap] {
+        let two_passes = filter::separable_filter(&img, &col, &row, border).unwrap();
+        assert!(max_diff(&two_passes, &filter::filter2d(&img, &outer, border).unwrap()) < 1e-13);
+    }
+    // Inside the image filter2d is the Same convolution (odd kernel)
+    let k3 = random_image(&mut rng, 3, 3);
+    let same = filter::convolve2d(&img, &k3, Mode::Same).unwrap();
+    assert!(max_diff(&same, &filter::filter2d(&img, &k3, Border::Zero).unwrap()) < 1e-13);
+
+    // Box filter: a constant stays constant with the Reflect / Nearest / Wrap borders,
+    // the Zero border darkens the edges
+    let flat = Array::from_shape_vec(&[6, 7], vec![3.0; 42]).unwrap();
+    for border in [Border::Reflect, Border::Nearest, Border::Wrap] {
+        assert!(filter::box_filter(&flat, 3, border).unwrap().iter().all(|v| (v - 3.0).abs() < 1e-14));
+    }
+    let dark = filter::box_filter(&flat, 3, Border::Zero).unwrap();
+    assert!((dark[(0, 0)] - 3.0 * 4.0 / 9.0).abs() < 1e-14 && (dark[(3, 3)] - 3.0).abs() < 1e-14);
+
+    // Median filter: isolated hot pixels on a smooth ramp disappear exactly
+    let ramp = Array::from_shape_vec(&[16, 16], (0..256).map(|k| (k % 16) as f64).collect()).unwrap();
+    let mut spiky = ramp.clone();
+    for &(i, j) in &[(3, 4), (8, 12), (12, 2)] {
+        spiky[(i, j)] = 1e3;
+    }
+    let cleaned = filter::median_filter(&spiky, 3, Border::Nearest).unwrap();
+    println!("median filter: max error after removing spikes {}", max_diff(&cleaned, &ramp));
+    assert_eq!(cleaned, ramp);
+
+    // Sobel on a plane gives its slopes per pixel away from the edges (where the Nearest
+    // border flattens it)
+    let values = (0..120).map(|k| 0.7 * (k / 12) as f64 - 1.5 * (k % 12) as f64).collect();
+    let plane = Array::from_shape_vec(&[10, 12], values).unwrap();
+    let (gy, gx) = filter::sobel(&plane, Border::Nearest).unwrap();
+    for i in 1..9 {
+        for j in 1..11 {
+            assert!((gy[(i, j)] - 0.7).abs() < 1e-13 && (gx[(i, j)] + 1.5).abs() < 1e-13);
+        }
+    }
+    let magnitude = filter::gradient_magnitude(&plane, Border::Nearest).unwrap();
+    assert!((magnitude[(5, 5)] - 0.7f64.hypot(1.5)).abs() < 1e-13);
+
+    // Denoising a measured phase map: defocus inside the pupil, NaN outside, plus noise.
+    // The masked Gaussian keeps the pupil shape and doesn't pull the NaN (or zeros) in
+    let n_pix = 64;
+    let (rho, theta) = polar_grid(n_pix);
+    let mask = circular_mask(&rho, 1.0);
+    let z = Zernike::new();
+    let defocus = z.z_nm(2, 0, rho.as_slice(), theta.as_slice(), "Standard");
+    let clean = defocus.iter().zip(mask.iter()).map(|(&v, &inside)| if inside { v } else { f64::NAN }).collect();
+    let clean = Array::from_shape_vec(&[n_pix, n_pix], clean).unwrap();
+    let noise = rng.normal_vec(n_pix * n_pix);
+    let noisy = clean.zip_with(&Array::from_shape_vec(&[n_pix, n_pix], noise).unwrap(), |a, b| a + 0.2 * b).unwrap();
+    let smooth = filter::gaussian_filter(&noisy, 1.5, Border::Reflect).unwrap();
+    let (before, after) = (rms_error(&noisy, &clean), rms_error(&smooth, &clean));
+    println!("phase map: RMS error {:.3} noisy, {:.3} after the masked Gaussian", before, after);
+    assert!(smooth.iter().zip(clean.iter()).all(|(a, b)| a.is_nan() == b.is_nan()));
+    assert!(after < 0.4 * before);
+    // The median filter keeps the mask too
+    let median = filter::median_filter(&noisy, 3, Border::Reflect).unwrap();
+    assert!(median.iter().zip(clean.iter()).all(|(a, b)| a.is_nan() == b.is_nan()));
+    assert!(rms_error(&median, &clean) < before);
+
+    // A PSF convolved with the detector pixel (a 4 x 4 box on the oversampled grid): the
+    // Full convolution keeps every bit of energy, and the peak goes down
+    let g = filter::gaussian_kernel(1.2, 5);
+    let psf = filter::separable_filter(&delta, &g, &g, Border::Zero).unwrap();
+    let pixel = Array::from_shape_vec(&[4, 4], vec![1.0 / 16.0; 16]).unwrap();
+    let detected = psf.convolve2d(&pixel, Mode::Full).unwrap();
+    let peak = |a: &Array<f64>| a.iter().cloned().fold(0.0, f64::max);
+    let (before, after) = (psf.iter().sum::<f64>(), detected.iter().sum::<f64>());
+    println!("PSF x pixel: energy {:.15} -> {:.15}, peak {:.4} -> {:.4}", before, after, peak(&psf), peak(&detected));
+    assert_eq!(detected.shape(), &[44, 44]);
+    assert!((after - before).abs() < 1e-14);
+    assert!(peak(&detected) < peak(&psf));
+}
@@ -0,0 +1,132 @@
+// Practice script for the random numbers: reproducible seeds, the moments of the uniform,
+// normal, exponential and Poisson samplers, unbiased integers and shuffles, and one
+// stream per thread so a multi-threaded Monte Carlo gives the same answer every run
+// Compile from this folder with: rustc -O p23_rng.rs
+
+mod numerics;
+
+use numerics::rng::Rng;
+use numerics::special::ln_factorial;
+use numerics::stats;
+use std::thread;
+
+fn close(a: f64, b: f64, tol: f64) -> bool {
+    (a - b).abs() <= tol * b.abs().max(1.0)
+}
+
+fn monte_carlo_pi(seed: u64, n_threads: usize, per_thread: usize) -> f64 {
+    // Fraction of random points of the unit square inside the quarter disk, times 4
+    let hits: usize = thread::scope(|s| {
+        let handles: Vec<_> = Rng::streams(seed, n_threads)
+            .into_iter()
+            .map(|mut rng| {
+                s.spawn(move || {
+                    (0..per_thread)
+                        .filter(|_| {
+                            let (x, y) = (rng.uniform(), rng.uniform());
+                            x * x + y * y < 1.0
+                        })
+                        .count()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    });
+    4.0 * hits as f64 / (n_threads * per_thread) as f64
+}
+
+fn main(){
+    // Same seed, same numbers
+    let mut a = Rng::new(46);
+    let mut b = Rng::new(46);
+    assert_eq!(a.uniform_vec(5), b.uniform_vec(5));
+    assert_ne!(Rng::new(0).next_u64(), Rng::new(1).next_u64());
+
+    // Streams: stream k is the seed's generator jumped k times, and they don't overlap
+    let streams = Rng::streams(7, 4);
+    let mut third = Rng::new(7);
+    third.jump();
+    third.jump();
+    assert_eq!(streams[2].clone().next_u64(), third.next_u64());
+    assert_eq!(Rng::stream(7, 3).next_u64(), streams[3].clone().next_u64());
+    let firsts: Vec<u64> = streams.into_iter().map(|mut r| r.next_u64()).collect();
+    assert!((1..firsts.len()).all(|i| !firsts[..i].contains(&firsts[i])));
+
+    // Moments of the continuous samplers
+    let n = 400_000;
+    let mut rng = Rng::new(2024);
+    let u = rng.uniform_vec(n);
+    println!("uniform: mean {:.4}, variance {:.5} (1/12 = {:.5})", stats::mean(&u), stats::variance(&u, 1), 1.0 / 12.0);
+    assert!(close(stats::mean(&u), 0.5, 0.005) && close(stats::variance(&u, 1), 1.0 / 12.0, 0.005));
+    let g: Vec<f64> = (0..n).map(|_| rng.normal_with(3.0, 0.5)).collect();
+    println!("normal(3, 0.5): mean {:.4}, std {:.4}", stats::mean(&g), stats::std(&g, 1));
+    assert!(close(stats::mean(&g), 3.0, 0.002) && close(stats::std(&g, 1), 0.5, 0.005));
+    let e: Vec<f64> = (0..n).map(|_| rng.exponential(4.0)).collect();
+    println!("exponential(rate 4): mean {:.4}, std {:.4}", stats::mean(&e), stats::std(&e, 1));
+    assert!(close(stats::mean(&e), 0.25, 0.005) && close(stats::std(&e, 1), 0.25, 0.005));
+    assert!(e.iter().all(|&v| v >= 0.0 && v.is_finite()));
+    // Memoryless: P(X > 0.5 | X > 0.25) = P(X > 0.25)
+    let beyond = |t: f64| e.iter().filter(|&&v| v > t).count() as f64;
+    assert!(close(beyond(0.5) / beyond(0.25), beyond(0.25) / n as f64, 0.01));
+
+    // Poisson: mean = variance = lambda on both sides of the switch of algorithm, and the
+    // probabilities of each count match lambda^k e^-lambda / k!
+    for &lambda in &[0.3, 3.0, 9.9, 10.0, 50.0, 1000.0] {
+        let counts: Vec<u64> = (0..n).map(|_| rng.poisson(lambda)).collect();
+        let (mean, var) = (stats::mean(&counts), stats::variance(&counts, 1));
+        println!("Poisson({}): mean {:.4}, variance {:.4}", lambda, mean, var);
+        assert!(close(mean, lambda, 0.01) && close(var, lambda, 0.02));
+        let k = lambda.round() as u64;
+        let observed = counts.iter().filter(|&&c| c == k).count() as f64 / n as f64;
+        let expected = (k as f64 * lambda.ln() - lambda - ln_factorial(k as u32)).exp();
+        assert!((observed - expected).abs() < 5.0 * (expected / n as f64).sqrt() + 1e-4);
+    }
+    assert_eq!(rng.poisson(0.0), 0);
+
+    // Photon noise on an array: SNR = sqrt(N)
+    let frame = rng.array_with(&[128, 128], |r| r.poisson(400.0) as f64);
+    let snr = stats::mean(frame.as_slice()) / stats::std(frame.as_slice(), 1);
+    println!("shot noise at 400 photons: SNR {:.2} (sqrt(400) = 20)", snr);
+    assert!(close(snr, 20.0, 0.02));
+    let noise = rng.normal_array(&[3, 4]);
+    assert_eq!(noise.shape(), &[3, 4]);
+    let mut buffer = [0.0; 8];
+    rng.fill_uniform(&mut buffer);
+    assert!(buffer.iter().all(|v| (0.0..1.0).contains(v)));
+
+    // Integers: every value of 0..6 as often as the others
+    let mut counts = [0usize; 6];
+    for _ in 0..600_000 {
+        counts[rng.below(6) as usize] += 1;
+    }
+    println!("die rolls: {:?}", counts);
+    assert!(counts.iter().all(|&c| (c as f64 - 100_000.0).abs() < 1500.0));
+    assert!((0..1000).all(|_| rng.below(u64::MAX) < u64::MAX));
+
+    // Shuffling: the 6 orders of 3 elements equally likely, and nothing lost
+    let orders = ["abc", "acb", "bac", "bca", "cab", "cba"];
+    let mut seen = [0usize; 6];
+    for _ in 0..60_000 {
+        let mut x = ['a', 'b', 'c'];
+        rng.shuffle(&mut x);
+        let s: String = x.iter().collect();
+        seen[orders.iter().position(|&o| o == s).unwrap()] += 1;
+    }
+    println!("shuffles of abc: {:?}", seen);
+    assert!(seen.iter().all(|&c| (c as f64 - 10_000.0).abs() < 400.0));
+    let mut deck: Vec<usize> = (0..52).collect();
+    rng.shuffle(&mut deck);
+    let mut sorted = deck.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..52).collect::<Vec<_>>());
+    let hand = rng.sample_indices(52, 5);
+    assert!(hand.len() == 5 && (1..5).all(|i| !hand[..i].contains(&hand[i])));
+    assert!(rng.choose::<u8>(&[]).is_none() && [1, 2, 3].contains(rng.choose(&[1, 2, 3]).unwrap()));
+
+    // Threads: one stream each, so the estimate doesn't depend on which thread runs first
+    let first = monte_carlo_pi(3, 8, 200_000);
+    let second = monte_carlo_pi(3, 8, 200_000);
+    println!("Monte Carlo pi with 8 threads: {} (twice: {})", first, second);
+    assert_eq!(first, second);
+    assert!((first - std::f64::consts::PI).abs() < 0.01);
+}
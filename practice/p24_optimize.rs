@@ -0,0 +1,132 @@
+// Practice script for Levenberg-Marquardt: the Rosenbrock valley with and without an
+// analytic Jacobian, a bound that stops it half way, Powell's singular function, and two
+// fits from the lab: the centre / radius of a pupil edge and the parameters of a noisy PSF
+// Compile from this folder with: rustc -O p24_optimize.rs
+
+mod numerics;
+
+use numerics::array::Array;
+use numerics::optimize::{levenberg_marquardt, levenberg_marquardt_with_jacobian, LmOptions, LmReport, LmStatus};
+use numerics::rng::Rng;
+use std::f64::consts::PI;
+
+fn rosenbrock(x: &[f64]) -> Vec<f64> {
+    // 1/2 |r|^2 = 1/2 (100 (y - x^2)^2 + (1 - x)^2), minimum 0 at (1, 1)
+    vec![10.0 * (x[1] - x[0] * x[0]), 1.0 - x[0]]
+}
+
+fn rosenbrock_jacobian(x: &[f64]) -> Array<f64> {
+    Array::from_shape_vec(&[2, 2], vec![-20.0 * x[0], 10.0, -1.0, 0.0]).unwrap()
+}
+
+fn show(name: &str, report: &LmReport) {
+    println!(
+        "{}: x = {:.6?}, cost {:.2e}, {} iterations, {} evaluations, {:?}",
+        name, report.x, report.cost, report.iterations, report.evaluations, report.status
+    );
+}
+
+fn main(){
+    let options = LmOptions::default();
+
+    // Rosenbrock from the classic start (-1.2, 1)
+    let exact = levenberg_marquardt_with_jacobian(rosenbrock, rosenbrock_jacobian, &[-1.2, 1.0], &options);
+    show("Rosenbrock, analytic J", &exact);
+    assert!(exact.converged());
+    assert!((exact.x[0] - 1.0).abs() < 1e-10 && (exact.x[1] - 1.0).abs() < 1e-10 && exact.cost < 1e-20);
+    let fd = levenberg_marquardt(rosenbrock, &[-1.2, 1.0], &options);
+    show("Rosenbrock, finite differences", &fd);
+    assert!(fd.converged() && (fd.x[0] - 1.0).abs() < 1e-7 && (fd.x[1] - 1.0).abs() < 1e-7);
+    // Each finite-difference Jacobian costs n = 2 more evaluations
+    assert!(fd.evaluations > exact.evaluations);
+
+    // With x <= 0.5 the best point is on the bound, at the bottom of the valley: (0.5, 0.25)
+    let bounds = vec![(f64::NEG_INFINITY, 0.5), (f64::NEG_INFINITY, f64::INFINITY)];
+    let bounded = LmOptions { bounds: Some(bounds), ..LmOptions::default() };
+    let report = levenberg_marquardt(rosenbrock, &[-1.2, 1.0], &bounded);
+    show("Rosenbrock, x <= 0.5", &report);
+    assert!(report.converged());
+    assert!((report.x[0] - 0.5).abs() < 1e-12 && (report.x[1] - 0.25).abs() < 1e-7);
+    assert!((report.cost - 0.125).abs() < 1e-12);
+
+    // A parameter fixed by lower == upper, and one in a box narrower than the difference
+    // step: the finite differences never evaluate outside the box
+    let boxed = |x: &[f64]| {
+        assert!(x[1] == 2.0 && (0.5..=0.5 + 1e-10).contains(&x[2]), "evaluated outside the box at {:?}", x);
+        vec![x[0] - 1.0, x[0] + x[1] - 4.0, x[0] * x[2] - 1.0]
+    };
+    let fixed = LmOptions { bounds: Some(vec![(-10.0, 10.0), (2.0, 2.0), (0.5, 0.5 + 1e-10)]), ..LmOptions::default() };
+    let report = levenberg_marquardt(boxed, &[0.0, 5.0, 0.0], &fixed);
+    show("fixed parameter", &report);
+    assert!(report.converged() && report.x[1] == 2.0 && (report.x[0] - 14.0 / 9.0).abs() < 1e-6);
+
+    // Powell's singular function: J is singular at the minimum (0, 0, 0, 0), so the
+    // convergence is only linear there and the gradient (~ x^3) vanishes before x does
+    let powell = |x: &[f64]| {
+        vec![
+            x[0] + 10.0 * x[1],
+            5f64.sqrt() * (x[2] - x[3]),
+            (x[1] - 2.0 * x[2]).powi(2),
+            10f64.sqrt() * (x[0] - x[3]).powi(2),
+        ]
+    };
+    let patient = LmOptions { max_iter: 500, ..LmOptions::default() };
+    let report = levenberg_marquardt(powell, &[3.0, -1.0, 0.0, 1.0], &patient);
+    show("Powell singular", &report);
+    assert!(report.converged() && report.cost < 1e-12 && report.x.iter().all(|v| v.abs() < 1e-3));
+
+    // Out of iterations is a status, not an error
+    let short = levenberg_marquardt(rosenbrock, &[-1.2, 1.0], &LmOptions { max_iter: 3, ..LmOptions::default() });
+    assert!(!short.converged() && short.status == LmStatus::MaxIterations && short.iterations == 3);
+    // So is a start where the residuals are NaN, reported at once
+    let nan_start = levenberg_marquardt(|x: &[f64]| vec![x[0].sqrt() - 2.0], &[-1.0], &LmOptions::default());
+    assert!(!nan_start.converged() && nan_start.status == LmStatus::NonFiniteStart);
+    assert!(nan_start.iterations == 0 && nan_start.evaluations == 1 && nan_start.cost.is_nan());
+
+    // Pupil centre and radius from noisy edge points, residual = distance to the centre - R
+    let mut rng = Rng::new(47);
+    let (cx, cy, radius) = (12.3, -4.1, 30.5);
+    let edge: Vec<(f64, f64)> = (0..200)
+        .map(|_| {
+            let t = rng.uniform_range(0.0, 2.0 * PI);
+            let r = radius + 0.2 * rng.normal();
+            (cx + r * t.cos(), cy + r * t.sin())
+        })
+        .collect();
+    let circle = |p: &[f64]| edge.iter().map(|&(x, y)| (x - p[0]).hypot(y - p[1]) - p[2]).collect::<Vec<f64>>();
+    let report = levenberg_marquardt(circle, &[0.0, 0.0, 10.0], &options);
+    show("pupil edge", &report);
+    println!("  rms misfit {:.3} (noise 0.2)", report.rms());
+    assert!(report.converged());
+    assert!((report.x[0] - cx).abs() < 0.05 && (report.x[1] - cy).abs() < 0.05);
+    assert!((report.x[2] - radius).abs() < 0.05);
+    assert!((report.rms() - 0.2).abs() < 0.03);
+
+    // A Gaussian PSF with photon noise: amplitude, centre, width and background. The width
+    // is kept positive and the centre on the detector while it searches
+    let n = 32;
+    let truth = [900.0, 14.6, 17.2, 2.3, 20.0];
+    let model = |p: &[f64], i: usize, j: usize| {
+        let d2 = (i as f64 - p[1]).powi(2) + (j as f64 - p[2]).powi(2);
+        p[0] * (-d2 / (2.0 * p[3] * p[3])).exp() + p[4]
+    };
+    let counts = (0..n * n).map(|k| rng.poisson(model(&truth, k / n, k % n)) as f64).collect();
+    let image = Array::from_shape_vec(&[n, n], counts).unwrap();
+    let psf_residuals = |p: &[f64]| {
+        // Weighted by the Poisson noise, estimated from the observed counts as sqrt(counts)
+        // (at least 1), so that cost ~ chi^2 / 2
+        let counts = image.as_slice();
+        (0..n * n).map(|k| (model(p, k / n, k % n) - counts[k]) / counts[k].max(1.0).sqrt()).collect::<Vec<f64>>()
+    };
+    let limits = Some(vec![(0.0, 1e6), (0.0, 31.0), (0.0, 31.0), (0.3, 10.0), (0.0, 1e3)]);
+    let psf_options = LmOptions { bounds: limits, ..LmOptions::default() };
+    let report = levenberg_marquardt(psf_residuals, &[500.0, 10.0, 10.0, 5.0, 0.0], &psf_options);
+    show("Gaussian PSF", &report);
+    assert!(report.converged());
+    assert!((report.x[1] - truth[1]).abs() < 0.05 && (report.x[2] - truth[2]).abs() < 0.05);
+    assert!((report.x[3] - truth[3]).abs() < 0.05 && (report.x[0] / truth[0] - 1.0).abs() < 0.05);
+    // chi^2 per degree of freedom ~ 1 for a good fit
+    let chi2 = 2.0 * report.cost / (n * n - 5) as f64;
+    println!("  chi^2 per degree of freedom {:.3}", chi2);
+    assert!((chi2 - 1.0).abs() < 0.15);
+}
@@ -0,0 +1,150 @@
+// Practice script for sparse matrices: building CSR / CSC from triplets, products and
+// transposes against the dense versions, conjugate gradient on a 2D Laplacian, LSQR for
+// least squares (plain and damped), and fitting a deformable mirror through its sparse
+// influence matrix
+// Compile from this folder with: rustc -O p25_sparse.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::{Array, ShapeError};
+use numerics::linalg::{self, LinalgError};
+use numerics::rng::Rng;
+use numerics::sparse::{conjugate_gradient, lsqr, CscMatrix, CsrMatrix, LinearOperator};
+use optics::deformable_mirror::{ActuatorLayout, DeformableMirror};
+use optics::pupil::PupilGrid;
+
+fn max_diff(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max)
+}
+
+fn random_sparse(rng: &mut Rng, rows: usize, cols: usize, per_row: usize) -> CsrMatrix {
+    let triplets: Vec<(usize, usize, f64)> = (0..rows)
+        .flat_map(|i| (0..per_row).map(move |_| i))
+        .map(|i| (i, rng.below(cols as u64) as usize, rng.normal()))
+        .collect();
+    CsrMatrix::from_triplets(rows, cols, &triplets).unwrap()
+}
+
+fn laplacian(n: usize) -> CsrMatrix {
+    // -d2/dx2 - d2/dy2 on an n x n grid with zero boundaries: 4 on the diagonal, -1 for
+    // each of the 4 neighbours
+    let mut triplets = Vec::new();
+    for i in 0..n {
+        for j in 0..n {
+            let k = i * n + j;
+            triplets.push((k, k, 4.0));
+            if i > 0 {
+                triplets.push((k, k - n, -1.0));
+            }
+            if i + 1 < n {
+                triplets.push((k, k + n, -1.0));
+            }
+            if j > 0 {
+                triplets.push((k, k - 1, -1.0));
+            }
+            if j + 1 < n {
+                triplets.push((k, k + 1, -1.0));
+            }
+        }
+    }
+    CsrMatrix::from_triplets(n * n, n * n, &triplets).unwrap()
+}
+
+fn main(){
+    // Triplets in any order, the repeated (1, 2) adds up
+    let triplets = [(1, 2, 3.0), (0, 0, 1.0), (2, 1, -2.0), (1, 2, 0.5), (0, 3, 4.0)];
+    let a = CsrMatrix::from_triplets(3, 4, &triplets).unwrap();
+    println!("CSR {:?}, {} nonzeros:\n{:?}", a.shape(), a.nnz(), a.to_dense().to_rows().unwrap());
+    assert_eq!(a.nnz(), 4);
+    assert_eq!((a.get(1, 2), a.get(2, 2)), (3.5, 0.0));
+    assert_eq!(a.row(0), (&[0, 3][..], &[1.0, 4.0][..]));
+    let dense = a.to_dense();
+    assert_eq!(a.transpose().to_dense(), linalg::transpose(&dense));
+    let csc = CscMatrix::from_triplets(3, 4, &triplets).unwrap();
+    assert_eq!(csc, a.to_csc());
+    assert_eq!(csc.to_csr(), a);
+    assert_eq!(csc.column(2), (&[1][..], &[3.5][..]));
+    assert_eq!(CsrMatrix::from_dense(&dense, 0.0).unwrap(), a);
+    assert_eq!(
+        CsrMatrix::from_triplets(3, 4, &[(3, 0, 1.0)]),
+        Err(ShapeError::OutOfBounds { axis: 0, index: 3, len: 3 })
+    );
+
+    // Products against the dense matrix, both orientations and both formats
+    let mut rng = Rng::new(48);
+    let s = random_sparse(&mut rng, 200, 150, 4);
+    let d = s.to_dense();
+    let x = rng.normal_vec(150);
+    let y = rng.normal_vec(200);
+    println!("random 200 x 150: {} nonzeros, density {:.3}", s.nnz(), s.density());
+    assert!(max_diff(&s.mat_vec(&x), &linalg::mat_vec(&d, &x)) < 1e-12);
+    assert!(max_diff(&s.transpose_mat_vec(&y), &linalg::mat_vec(&linalg::transpose(&d), &y)) < 1e-12);
+    assert!(max_diff(&s.to_csc().mat_vec(&x), &s.mat_vec(&x)) < 1e-12);
+    assert!(max_diff(&s.to_csc().transpose_mat_vec(&y), &s.transpose_mat_vec(&y)) < 1e-12);
+    assert!(max_diff(&d.apply(&x), &s.apply(&x)) < 1e-12);
+
+    // Conjugate gradient on the Poisson equation, against LU on the dense matrix
+    let n = 20;
+    let lap = laplacian(n);
+    let b = rng.normal_vec(n * n);
+    let cg = conjugate_gradient(&lap, &b, 1e-12, 1000).unwrap();
+    let exact = linalg::solve(&lap.to_dense(), &b).unwrap();
+    println!(
+        "CG on the {0} x {0} Laplacian: {1} iterations, |r| = {2:.1e}, vs LU {3:.1e} ({4} of {5} entries stored)",
+        n * n, cg.iterations, cg.residual_norm, max_diff(&cg.x, &exact), lap.nnz(), n * n * n * n
+    );
+    assert!(max_diff(&cg.x, &exact) < 1e-10 && cg.iterations < n * n);
+    assert!(conjugate_gradient(&lap, &b, 1e-12, 5).is_err());
+    assert!(conjugate_gradient(&s, &y, 1e-12, 5).is_err());
+
+    // LSQR: overdetermined least squares, against QR, plain and damped
+    let tall = random_sparse(&mut rng, 300, 40, 3);
+    let rhs = rng.normal_vec(300);
+    let plain = lsqr(&tall, &rhs, 0.0, 1e-12, 500).unwrap();
+    let qr = linalg::least_squares(&tall.to_dense(), &rhs).unwrap();
+    let err = max_diff(&plain.x, &qr);
+    println!("LSQR 300 x 40: {} iterations, |r| = {:.4}, vs QR {:.1e}", plain.iterations, plain.residual_norm, err);
+    assert!(err < 1e-8);
+    let r: Vec<f64> = tall.mat_vec(&plain.x).iter().zip(rhs.iter()).map(|(a, b)| b - a).collect();
+    assert!((r.iter().map(|v| v * v).sum::<f64>().sqrt() - plain.residual_norm).abs() < 1e-8);
+    // Damped: the ridge solution (A^T A + damp^2 I)^-1 A^T b
+    let damp = 2.0;
+    let ridge = lsqr(&tall, &rhs, damp, 1e-12, 500).unwrap();
+    let t = tall.to_dense();
+    let mut normal = linalg::mat_mul(&linalg::transpose(&t), &t);
+    for k in 0..40 {
+        normal[(k, k)] += damp * damp;
+    }
+    let expected = linalg::solve(&normal, &tall.transpose_mat_vec(&rhs)).unwrap();
+    assert!(max_diff(&ridge.x, &expected) < 1e-8);
+    let norm = |v: &[f64]| v.iter().map(|a| a * a).sum::<f64>().sqrt();
+    assert!(norm(&ridge.x) < norm(&plain.x));
+    // Dense arrays go through the same solver
+    assert!(max_diff(&lsqr(&t, &rhs, 0.0, 1e-12, 500).unwrap().x, &qr) < 1e-8);
+
+    // A deformable mirror: every pixel only feels its neighbouring actuators, so the
+    // influence matrix above 1e-4 is mostly empty. Fitting the surface of known commands
+    // with it leaves about the dropped tails as error
+    let grid = PupilGrid::new(64);
+    let dm = DeformableMirror::new(ActuatorLayout::square(11), 0.15, &grid);
+    let influence = dm.sparse_influence(1e-4);
+    let (pixels, actuators) = influence.shape();
+    println!("DM influence {} x {}: density {:.3}", pixels, actuators, influence.density());
+    assert!(influence.density() < 0.15);
+    let commands = rng.normal_vec(actuators);
+    let surface = linalg::mat_vec(&dm.influence, &commands);
+    let fitted = dm.fit_commands(&surface, 1e-4, 0.0).unwrap();
+    let fitted_surface = linalg::mat_vec(&dm.influence, &fitted);
+    let err = max_diff(&fitted_surface, &surface);
+    println!("DM fit through LSQR: worst surface error {:.1e}", err);
+    assert!(err < 1e-3);
+    assert_eq!(
+        dm.fit_commands(&surface[1..], 1e-4, 0.0),
+        Err(LinalgError::Shape(ShapeError::SizeMismatch { shape: vec![pixels], len: pixels - 1 }))
+    );
+    assert_eq!(
+        CsrMatrix::from_dense(&Array::zeros(&[2, 2, 2]), 0.0),
+        Err(ShapeError::WrongNdim { expected: 2, shape: vec![2, 2, 2] })
+    );
+}
@@ -0,0 +1,129 @@
+// Practice script for the plotting module: tick placement, colour maps, the PNG encoder,
+// a line plot of Zernike radial polynomials and heatmaps of a wavefront and its PSF, all
+// written to SVG and PNG in the temp directory
+// Compile from this folder with: rustc -O p26_plots.rs
+
+mod numerics;
+mod optics;
+mod plot;
+
+use numerics::array::Array;
+use numerics::fft;
+use numerics::ranges::linspace;
+use optics::pupil::{circular_mask, polar_grid, pupil_field};
+use optics::zernike::{radial_polynomial, Zernike};
+use plot::colormap::{Colormap, NAN_COLOUR};
+use plot::figure::{format_tick, nice_ticks, Heatmap, LinePlot};
+use plot::png;
+
+fn out_path(name: &str) -> String {
+    std::env::temp_dir().join(name).to_string_lossy().into_owned()
+}
+
+fn check_png(bytes: &[u8]) -> (u32, u32) {
+    // Walks the chunks, checking every CRC; returns (width, height) from IHDR
+    assert_eq!(&bytes[..8], &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+    let be = |b: &[u8]| u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+    let (mut pos, mut kinds) = (8, Vec::new());
+    while pos < bytes.len() {
+        let len = be(&bytes[pos..]) as usize;
+        let body = &bytes[pos + 4..pos + 8 + len];
+        assert_eq!(png::crc32(body), be(&bytes[pos + 8 + len..]), "bad CRC");
+        kinds.push(String::from_utf8_lossy(&body[..4]).into_owned());
+        pos += 12 + len;
+    }
+    assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+    (be(&bytes[16..]), be(&bytes[20..]))
+}
+
+fn main(){
+    // Ticks on 1 - 2 - 5 steps, printed with the decimals the step needs
+    let ticks = nice_ticks(0.0, 1.0, 5);
+    println!("ticks on [0, 1]: {:?}", ticks);
+    assert_eq!(ticks.len(), 6);
+    assert!((ticks[1] - 0.2).abs() < 1e-12);
+    assert_eq!(nice_ticks(-3.7, 12.1, 6), [0.0, 5.0, 10.0]);
+    assert_eq!(nice_ticks(1.0, 1.0, 5), [1.0]);
+    assert_eq!(format_tick(0.25, 0.05), "0.25");
+    assert_eq!(format_tick(3.0, 1.0), "3");
+    assert_eq!(format_tick(2e-6, 1e-6), "2.0e-6");
+
+    // Colour maps: the end stops, NaN blank, Diverging centred on 0
+    assert_eq!(Colormap::Gray.rgb(0.0), [0, 0, 0]);
+    assert_eq!(Colormap::Gray.rgb(2.0), [255, 255, 255]);
+    assert_eq!(Colormap::Viridis.rgb(1.0), [253, 231, 37]);
+    assert_eq!(Colormap::Viridis.rgb(f64::NAN), NAN_COLOUR);
+    assert_eq!(Colormap::Diverging.auto_range(&[-0.2, 0.5, f64::NAN]), (-0.5, 0.5));
+    assert_eq!(Colormap::Viridis.auto_range(&[3.0, 3.0]), (2.5, 3.5));
+
+    // PNG: a 2 x 2 image, every chunk CRC checked
+    let tiny = png::encode_rgb(2, 2, &[255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]);
+    assert_eq!(check_png(&tiny), (2, 2));
+    assert_eq!(png::crc32(b"123456789"), 0xCBF4_3926);
+
+    // Radial polynomials R_nm(rho) for m = 0 and m = 1, as in the textbook figures
+    let rho = linspace(0.0, 1.0, 101).into_vec();
+    let mut radial = LinePlot::new("Zernike radial polynomials", "rho", "R_nm(rho)");
+    for &(n, m) in &[(0, 0), (2, 0), (4, 0), (6, 0), (1, 1), (3, 1), (5, 1)] {
+        radial.add(&format!("R_{}^{}", n, m), &rho, &radial_polynomial(n, m).eval_slice(&rho));
+    }
+    let svg = radial.to_svg();
+    assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<polyline").count(), 7);
+    assert!(svg.contains("R_4^0") && svg.contains("Zernike radial polynomials"));
+    let canvas = radial.to_canvas();
+    assert_eq!(check_png(&canvas.to_png()), (radial.width as u32, radial.height as u32));
+    radial.save_svg(&out_path("radial.svg")).unwrap();
+    radial.save_png(&out_path("radial.png")).unwrap();
+
+    // A wavefront with NaN outside the pupil, in the diverging map: white at 0 and outside
+    let n_pix = 64;
+    let (rho, theta) = polar_grid(n_pix);
+    let mask = circular_mask(&rho, 1.0);
+    let z = Zernike::new();
+    let coef = [0.0, 0.0, 0.0, 0.0, 0.05, 0.1, 0.0, 0.08, 0.0, 0.0, 0.06];
+    let values = z.evaluate(&coef, rho.as_slice(), theta.as_slice(), "Standard");
+    let values = values.iter().zip(mask.iter()).map(|(&v, &inside)| if inside { v } else { f64::NAN }).collect();
+    let wavefront = Array::from_shape_vec(&[n_pix, n_pix], values).unwrap();
+    let mut map = Heatmap::new(&wavefront, "Wavefront (waves)").unwrap();
+    map.colormap = Colormap::Diverging;
+    let (lo, hi) = map.value_range();
+    println!("wavefront colour scale [{:.3}, {:.3}], {} pixels per cell", lo, hi, map.cell);
+    assert!((lo + hi).abs() < 1e-15);
+    let cells = map.cells();
+    assert_eq!(cells.get(0, 0), NAN_COLOUR);
+    assert_ne!(cells.get(n_pix / 2, n_pix / 2), NAN_COLOUR);
+    let canvas = map.to_canvas();
+    assert_eq!(canvas.get(20, 40), NAN_COLOUR); // the top left cell, outside the pupil
+    assert_eq!(check_png(&canvas.to_png()), (canvas.width as u32, canvas.height as u32));
+    let svg = map.to_svg();
+    assert!(svg.contains("data:image/png;base64,iVBORw0KGgo") && svg.contains("<linearGradient"));
+    map.save_svg(&out_path("wavefront.svg")).unwrap();
+    map.save_png(&out_path("wavefront.png")).unwrap();
+
+    // Its PSF on a log scale, the central 64 x 64 of the 4x padded transform
+    let amplitude = mask.map(|&inside| if inside { 1.0 } else { 0.0 });
+    let field = pupil_field(&amplitude, &wavefront, 1.0).unwrap();
+    let padded = fft::zero_pad_centered(&field, &[4 * n_pix, 4 * n_pix]).unwrap();
+    let psf = fft::fftshift(&fft::fft2(&padded).unwrap().map(|z| z.norm_sqr()));
+    let peak = psf.iter().cloned().fold(0.0, f64::max);
+    let c = 2 * n_pix;
+    let core: Vec<f64> = (0..n_pix * n_pix).map(|k| psf[(c - 32 + k / n_pix, c - 32 + k % n_pix)]).collect();
+    let log_psf = Array::from_shape_vec(&[n_pix, n_pix], core.iter().map(|v| (v / peak).max(1e-6).log10()).collect())
+        .unwrap();
+    let mut map = Heatmap::new(&log_psf, "log10 PSF").unwrap();
+    map.range = Some((-6.0, 0.0));
+    let canvas = map.to_canvas();
+    let bytes = canvas.to_png();
+    let raw = 3 * canvas.width * canvas.height;
+    println!("PSF heatmap {} x {}: PNG {} bytes for {} raw", canvas.width, canvas.height, bytes.len(), raw);
+    assert!(bytes.len() < canvas.width * canvas.height);
+    map.save_png(&out_path("psf.png")).unwrap();
+    map.save_svg(&out_path("psf.svg")).unwrap();
+
+    for name in &["radial", "wavefront", "psf"] {
+        println!("wrote {} and .svg", out_path(&format!("{}.png", name)));
+    }
+    let written = std::fs::read(out_path("psf.png")).unwrap();
+    assert_eq!(written, bytes);
+}
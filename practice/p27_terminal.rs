@@ -0,0 +1,144 @@
+// Practice script for the terminal heatmap: ANSI colour matching, the ASCII fallback,
+// shrinking big arrays, a masked wavefront and its PSF drawn with half blocks, and the
+// `{:?}` preview. Also a small viewer for grids saved as text (np.savetxt, CSV):
+//     ./p27_terminal wavefront.txt [columns] [--ascii]    (in any order)
+// NO_COLOR in the environment picks the ASCII style too
+// Compile from this folder with: rustc -O p27_terminal.rs
+
+mod numerics;
+mod optics;
+mod plot;
+
+use numerics::array::Array;
+use numerics::fft;
+use optics::pupil::{circular_mask, polar_grid, pupil_field};
+use optics::zernike::Zernike;
+use plot::colormap::Colormap;
+use plot::figure::Heatmap;
+use plot::terminal::{ansi256, ansi256_rgb, load_text, render, Style};
+use std::env;
+use std::fs;
+use std::process;
+
+fn view(args: &[String]) {
+    // The CLI, arguments in any order: the file, optionally a width and --ascii
+    let ascii = args.iter().any(|a| a == "--ascii") || env::var_os("NO_COLOR").is_some();
+    let columns = args.iter().find_map(|a| a.parse().ok()).unwrap_or(64);
+    let Some(path) = args.iter().find(|a| !a.starts_with("--") && a.parse::<usize>().is_err()) else {
+        eprintln!("usage: p27_terminal grid.txt [columns] [--ascii]");
+        process::exit(2);
+    };
+    let data = load_text(path).unwrap_or_else(|e| {
+        eprintln!("can't read {}: {}", path, e);
+        process::exit(1);
+    });
+    let mut map = Heatmap::new(&data, path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(1);
+    });
+    // Signed data (a wavefront) gets the diverging map
+    if data.iter().any(|&v| v < 0.0) {
+        map.colormap = Colormap::Diverging;
+    }
+    print!("{}", render(&map, columns, if ascii { Style::Ascii } else { Style::Ansi256 }));
+}
+
+fn main(){
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        view(&args);
+        return;
+    }
+
+    // Colours of the cube and the grey ramp come back as themselves
+    for code in 16..=255u8 {
+        assert_eq!(ansi256(ansi256_rgb(code)), code);
+    }
+    assert_eq!(ansi256([255, 0, 0]), 196);
+    assert_eq!(ansi256([128, 128, 128]), 244);
+    assert_eq!(ansi256([250, 0, 10]), 196);
+
+    // ASCII: one character for two rows, darkest to brightest, NaN blank
+    let ramp = Array::from_shape_vec(&[2, 3], vec![0.0, 0.5, 1.0, 0.0, 0.5, f64::NAN]).unwrap();
+    let mut map = Heatmap::new(&ramp, "").unwrap();
+    map.range = Some((0.0, 1.0));
+    let text = render(&map, 80, Style::Ascii);
+    println!("{}", text);
+    assert!(text.starts_with(".+@\n"));
+    let empty = Array::from_shape_vec(&[2, 1], vec![f64::NAN; 2]).unwrap();
+    assert!(render(&Heatmap::new(&empty, "").unwrap(), 80, Style::Ascii).starts_with(" \n"));
+
+    // A masked wavefront, shrunk from 128 to 32 columns: the NaN corners stay blank
+    let n_pix = 128;
+    let (rho, theta) = polar_grid(n_pix);
+    let mask = circular_mask(&rho, 1.0);
+    let coef = [0.0, 0.0, 0.0, 0.0, 0.05, 0.1, 0.0, 0.08, 0.0, 0.0, 0.06];
+    let values = Zernike::new().evaluate(&coef, rho.as_slice(), theta.as_slice(), "Standard");
+    let values = values.iter().zip(mask.iter()).map(|(&v, &inside)| if inside { v } else { f64::NAN }).collect();
+    let wavefront = Array::from_shape_vec(&[n_pix, n_pix], values).unwrap();
+    let mut map = Heatmap::new(&wavefront, "wavefront (waves)").unwrap();
+    map.colormap = Colormap::Diverging;
+    let text = map.to_terminal(32);
+    print!("{}", text);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 1 + 16 + 2); // title, 32 pixel rows in 16 lines, legend
+    assert!(lines[1].starts_with("\x1b[0m \x1b[0m ")); // outside the pupil
+    assert_eq!(lines[1].matches('▀').count() + lines[1].matches('▄').count() + lines[1].matches(' ').count(), 32);
+    let ascii = render(&map, 32, Style::Ascii);
+    print!("{}", ascii);
+    assert!(ascii.lines().skip(1).take(16).all(|l| l.chars().count() <= 32));
+    assert!(ascii.lines().last().unwrap().contains('0')); // the symmetric range has 0 in the middle
+
+    // Its PSF, log scale, through the {:?} helper
+    let amplitude = mask.map(|&inside| if inside { 1.0 } else { 0.0 });
+    let field = pupil_field(&amplitude, &wavefront, 1.0).unwrap();
+    let padded = fft::zero_pad_centered(&field, &[2 * n_pix, 2 * n_pix]).unwrap();
+    let psf = fft::fftshift(&fft::fft2(&padded).unwrap().map(|z| z.norm_sqr()));
+    let peak = psf.iter().cloned().fold(0.0, f64::max);
+    let c = n_pix;
+    let core: Vec<f64> = (0..48 * 48).map(|k| psf[(c - 24 + k / 48, c - 24 + k % 48)] / peak).collect();
+    let core = core.iter().map(|v| v.max(1e-6).log10()).collect();
+    let log_psf = Array::from_shape_vec(&[48, 48], core).unwrap();
+    let preview = format!("{:?}", log_psf.preview());
+    print!("{}", preview);
+    assert!(preview.starts_with("[48, 48] array\n"));
+    assert_eq!(preview.lines().count(), 1 + 24 + 2);
+    // Not 2D: the usual Debug output
+    let line = Array::from_shape_vec(&[3], vec![1.0, 2.0, 3.0]).unwrap();
+    assert_eq!(format!("{:?}", line.preview()), format!("{:?}", line));
+
+    // Wide ranges keep both end labels, with the ones in between where they fit
+    let wide = Array::from_shape_vec(&[2, 32], (0..64).map(|k| (k as f64 - 31.5) * 3200.0).collect()).unwrap();
+    let mut map = Heatmap::new(&wide, "").unwrap();
+    map.colormap = Colormap::Diverging;
+    let text = render(&map, 32, Style::Ascii);
+    print!("{}", text);
+    let ticks = text.lines().last().unwrap();
+    assert!(ticks.starts_with("-1.0e5") && ticks.ends_with("1.0e5") && ticks.contains(" 0 "));
+    // Too narrow for both ends: the last one stays, in its place at the right
+    let narrow = Array::from_shape_vec(&[2, 12], (0..24).map(|k| (k as f64 - 11.5) / 11.5 * 1e-4).collect()).unwrap();
+    let mut map = Heatmap::new(&narrow, "").unwrap();
+    map.colormap = Colormap::Diverging;
+    let ticks = render(&map, 12, Style::Ascii).lines().last().unwrap().to_string();
+    println!("{}", ticks);
+    assert!(ticks.ends_with("0.0001") && ticks.chars().count() == 12);
+
+    // A text grid back from disk, NaN and comments included, as the CLI reads it
+    let path = env::temp_dir().join("p27_grid.txt").to_string_lossy().into_owned();
+    fs::write(&path, "# 2 x 3 grid\n0.5, -1, nan\n2\t3 4\n").unwrap();
+    let grid = load_text(&path).unwrap();
+    assert_eq!(grid.shape(), &[2, 3]);
+    assert!(grid[(0, 2)].is_nan() && grid[(1, 2)] == 4.0);
+    fs::write(&path, "1 2\n3\n").unwrap();
+    assert!(load_text(&path).is_err());
+    let mut saved = String::new();
+    for i in 0..n_pix {
+        let row: Vec<String> = (0..n_pix).map(|j| format!("{}", wavefront[(i, j)])).collect();
+        saved.push_str(&row.join(" "));
+        saved.push('\n');
+    }
+    fs::write(&path, saved).unwrap();
+    let back = load_text(&path).unwrap();
+    assert!(back.iter().zip(wavefront.iter()).all(|(a, b)| a == b || (a.is_nan() && b.is_nan())));
+    println!("try: ./p27_terminal {} 40", path);
+}
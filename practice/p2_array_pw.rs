@@ -1,11 +1,12 @@
 // Practice script to sum the powers of an array
 // rho = a1 * r^1 + a2 * r^2...
 
-fn linspace(start: f64, end: f64, n: usize) -> Vec<f64> {
-    (0..n)
-        .map(|i| start + (end - start) * (i as f64) / ((n - 1) as f64))
-        .collect()
-}
+mod numerics;
+
+use numerics::array::Array;
+use numerics::polynomial::Polynomial;
+use numerics::ranges::linspace;
+use numerics::special::factorial;
 
 fn practice_map(n: usize) -> Vec<usize>{
     // Practice function where we create an 'iterator' of a certain size
@@ -21,38 +22,44 @@ fn get_scale_coef(n: i32, m: i32) {
 
 }
 
-fn sum_along_direction(arr: &Vec<Vec<f64>>) -> Vec<f64>{
-    let shape = arr[0].len();
-    let mut result = vec![0.0; shape];
-
-    // Sum over the arrays
-    for each_vec in arr{
-        println!("{:?}", each_vec);
-        for (i, &val) in each_vec.iter().enumerate(){
-            result[i] += val;
-        }
-    }
-    result
+fn sum_along_direction(arr: &Array<f64>) -> Array<f64> {
+    // Sum over the rows (axis 0), one value per column. The Array can't be jagged,
+    // and an empty array just gives an empty result
+    arr.sum_axis(0).expect("2D arrays always have an axis 0")
 }
 
 // Function to get
 // for j in range(int((n - m) / 2) + 1):
 // coef = ((-1) ** j * fact(n - j)) / (fact(j) * fact((n + m) / 2 - j) * fact((n - m) / 2 - j))
+fn radial_coef(n: u32, m: u32, j: u32) -> i128 {
+    // The factorials are exact u128s, so this is fine as long as n! fits (n <= 34)
+    let fact = |k: u32| factorial(k).expect("factorial overflows u128");
+    let sign = if j.is_multiple_of(2) { 1 } else { -1 };
+    sign * (fact(n - j) / (fact(j) * fact((n + m) / 2 - j) * fact((n - m) / 2 - j))) as i128
+}
 
 fn main(){
-    let rho : Vec<f64> = linspace(0.0, 1.0, 10);
+    let rho : Vec<f64> = linspace(0.0, 1.0, 10).into_vec();
     println!("{:?}", rho);
 
+    // rho = a1 * r^1 + a2 * r^2 + a3 * r^3, evaluated with Horner's rule (no powers)
+    let poly = Polynomial::new(vec![0.0, 1.0, 0.5, 0.25]);
+    println!("{} = {:?}", poly, poly.eval_slice(&rho));
+
     println!("{:?}", practice_map(10));
 
     get_scale_coef(1, 1);
+    // R_4^0 = 6 r^4 - 6 r^2 + 1
+    let coefs: Vec<i128> = (0..=2).map(|j| radial_coef(4, 0, j)).collect();
+    println!("R_4^0 coefficients {:?}", coefs);
 
     let vec2d: Vec<Vec<f64>> = vec![
         vec![1.0, 2.0, 3.0],
         vec![4.0, 5.0, 6.0],
         vec![7.0, 8.0, 9.0],
     ];
-    let res = sum_along_direction(&vec2d);
-    println!("{:?}", res);
+    let arr = Array::from_rows(vec2d).unwrap();
+    let res = sum_along_direction(&arr);
+    println!("{:?}", res.as_slice());
 
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+// Practice script for random wavefronts with Kolmogorov / von Karman statistics
+// Compile from this folder with: rustc p3_turbulence.rs (it picks up numerics/ and optics/)
+
+mod numerics;
+mod optics;
+
+use optics::turbulence::{kolmogorov_covariance, PhaseScreenGenerator, Turbulence};
+use optics::zernike::Zernike;
+
+fn main(){
+    // Noll's numbers: with (D/r0) = 1 each tilt has a variance of ~0.449 rad^2
+    // and the residual after removing tilts is 0.134 (from a total of 1.0299).
+    // Noll's residuals were computed with a slightly rounded spectrum, hence the tolerance
+    let tilt = kolmogorov_covariance(2, 2);
+    println!("Tilt variance: {:.4}", tilt);
+    assert!((tilt - 0.449).abs() < 1e-3);
+    assert!((kolmogorov_covariance(2, 3)).abs() < 1e-15);      // x and y tilts are independent
+
+    let n_zern = 36;
+    let kolmogorov = Turbulence::kolmogorov(1.0);
+    let cov = kolmogorov.covariance_matrix(n_zern);
+    let residual_after_tilt = 1.0299 - cov[(1, 1)] - cov[(2, 2)];
+    println!("Residual after tilt: {:.4}", residual_after_tilt);
+    assert!((residual_after_tilt - 0.134).abs() < 3e-3);
+
+    // Tilt and coma (Z2 and Z8) are correlated, with a negative sign
+    println!("<a2 a8> = {:.5}", cov[(1, 7)]);
+    assert!(cov[(1, 7)] < 0.0);
+
+    // Von Karman with a huge outer scale goes back to Kolmogorov
+    // Tilt gets there very slowly (the difference goes like (D/L0)^(1/3)), the rest straight away
+    let almost_kolmogorov = Turbulence::von_karman(1.0, 1.0e6).covariance_matrix(n_zern);
+    for k in 1..n_zern {
+        let tol = if k < 3 { 2e-2 } else { 1e-3 };
+        let rel = (almost_kolmogorov[(k, k)] - cov[(k, k)]).abs() / cov[(k, k)];
+        assert!(rel < tol, "mode {} differs by {}", k + 1, rel);
+    }
+
+    // A finite outer scale mostly removes tilt, the high orders barely change
+    let von_karman = Turbulence::von_karman(1.0, 10.0).covariance_matrix(n_zern);
+    println!("Tilt variance with L0 = 10 D: {:.4}", von_karman[(1, 1)]);
+    assert!(von_karman[(1, 1)] < 0.8 * cov[(1, 1)]);
+    assert!(von_karman[(20, 20)] > 0.9 * cov[(20, 20)]);
+
+    // Draw coefficient vectors for D/r0 = 10 and compare the sample covariance
+    let d_over_r0: f64 = 10.0;
+    let strong = Turbulence::kolmogorov(d_over_r0);
+    let mut generator = PhaseScreenGenerator::new(&strong, n_zern, 1234);
+    let n_samples = 5000;
+    let mut var = vec![0.0; n_zern];
+    for _ in 0..n_samples {
+        let coef = generator.sample();
+        assert_eq!(coef.len(), n_zern);
+        assert_eq!(coef[0], 0.0);
+        for (v, c) in var.iter_mut().zip(coef.iter()) {
+            *v += c * c / n_samples as f64;
+        }
+    }
+    let expected_tilt = tilt * d_over_r0.powf(5.0 / 3.0);
+    println!("Sample tilt variance: {:.3} (expected {:.3})", var[1], expected_tilt);
+    assert!((var[1] - expected_tilt).abs() / expected_tilt < 0.1);
+
+    // Same seed -> same wavefront
+    let a = PhaseScreenGenerator::new(&strong, n_zern, 7).sample();
+    let b = PhaseScreenGenerator::new(&strong, n_zern, 7).sample();
+    assert_eq!(a, b);
+
+    // And the coefficients can be turned into a phase map with the Zernike evaluation
+    let zern = Zernike::new();
+    let rho = vec![0.0, 0.5, 1.0];
+    let theta = vec![0.0, 1.0, 2.0];
+    let phase = zern.evaluate(&a, &rho, &theta, "Standard");
+    println!("Phase [rad]: {:?}", phase);
+}
@@ -0,0 +1,78 @@
+// Practice script for the Shack-Hartmann sensor: simulate slopes, reconstruct Zernikes
+// Compile from this folder with: rustc p4_shack_hartmann.rs
+
+mod numerics;
+mod optics;
+
+use optics::shack_hartmann::{LensletArray, ModalReconstructor, ShackHartmann};
+use optics::turbulence::{PhaseScreenGenerator, Turbulence};
+use optics::zernike::Zernike;
+
+fn rms_error(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    let sum: f64 = (0..n).map(|i| (a[i] - b[i]).powi(2)).sum();
+    (sum / n as f64).sqrt()
+}
+
+fn main(){
+    // The analytic Zernike gradients against finite differences of the wavefront
+    let zern = Zernike::new();
+    let coef = vec![0.0, 0.3, -0.2, 0.5, 0.1, -0.4, 0.2, 0.25, -0.15, 0.05, 0.3];
+    let (x, y, h): (f64, f64, f64) = (0.3, -0.45, 1e-6);
+    let at = |x: f64, y: f64| {
+        let rho = x.hypot(y);
+        zern.evaluate(&coef, &[rho], &[y.atan2(x)], "Standard")[0]
+    };
+    let (dx, dy) = zern.gradient(&coef, &[x.hypot(y)], &[y.atan2(x)]);
+    let fd_x = (at(x + h, y) - at(x - h, y)) / (2.0 * h);
+    let fd_y = (at(x, y + h) - at(x, y - h)) / (2.0 * h);
+    println!("dW/dx = {:.6} (finite differences {:.6})", dx[0], fd_x);
+    assert!((dx[0] - fd_x).abs() < 1e-6 && (dy[0] - fd_y).abs() < 1e-6);
+
+    // 10 x 10 lenslets, each sampled 8 x 8, keep the ones at least half illuminated
+    let lenslets = LensletArray::square(10, 8, 0.5);
+    println!("Valid lenslets: {}", lenslets.n_lenslets());
+
+    let n_zern = 21;
+    let reconstructor = ModalReconstructor::new(&lenslets, n_zern);
+
+    // A turbulent wavefront with only the modes the reconstructor knows: exact recovery
+    let mut screens = PhaseScreenGenerator::new(&Turbulence::kolmogorov(5.0), n_zern, 42);
+    let truth = screens.sample();
+    let mut sensor = ShackHartmann::new(lenslets, 0.0, 1);
+    let (sx, sy) = sensor.measure(&truth);
+    let estimate = reconstructor.reconstruct(&sx, &sy);
+    let err = rms_error(&truth, &estimate);
+    println!("Noise-free reconstruction error: {:e}", err);
+    assert!(err < 1e-8);
+
+    // With centroid noise the error grows with the noise but stays small
+    sensor.noise_rms = 0.05;
+    let (sx, sy) = sensor.measure(&truth);
+    let noisy = reconstructor.reconstruct(&sx, &sy);
+    let err_noisy = rms_error(&truth, &noisy);
+    println!("Reconstruction error with noise: {:.4} (coefficients RMS {:.3})", err_noisy, rms_error(&truth, &vec![0.0; n_zern]));
+    assert!(err_noisy > err && err_noisy < 0.05);
+
+    // Averaging frames beats the noise down
+    let n_frames = 50;
+    let mut averaged = vec![0.0; n_zern];
+    for _ in 0..n_frames {
+        let (sx, sy) = sensor.measure(&truth);
+        for (a, c) in averaged.iter_mut().zip(reconstructor.reconstruct(&sx, &sy)) {
+            *a += c / n_frames as f64;
+        }
+    }
+    let err_averaged = rms_error(&truth, &averaged);
+    println!("After averaging {} frames: {:.4}", n_frames, err_averaged);
+    assert!(err_averaged < err_noisy);
+
+    // Modes beyond n_zern alias into the reconstructed ones (but the low orders survive)
+    let mut high_order = PhaseScreenGenerator::new(&Turbulence::kolmogorov(5.0), 66, 42);
+    let truth = high_order.sample();
+    sensor.noise_rms = 0.0;
+    let (sx, sy) = sensor.measure(&truth);
+    let aliased = reconstructor.reconstruct(&sx, &sy);
+    println!("Tilt: true {:.3} reconstructed {:.3}", truth[1], aliased[1]);
+    assert!((truth[1] - aliased[1]).abs() < 0.1 * truth[1].abs().max(1.0));
+}
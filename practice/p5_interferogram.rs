@@ -0,0 +1,80 @@
+// Practice script for interferograms: fringes -> wrapped phase -> unwrapped phase -> Zernikes
+// Compile from this folder with: rustc p5_interferogram.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use optics::interferogram::{
+    four_step, four_step_shifts, hariharan, hariharan_shifts, second_difference_quality, unwrap_quality_guided,
+    InterferogramSimulator,
+};
+use optics::turbulence::{PhaseScreenGenerator, Turbulence};
+use optics::zernike::Zernike;
+
+fn max_abs_diff(a: &[f64], b: &[f64]) -> f64 {
+    // Ignoring piston (coef[0]), which the interferometer cannot see
+    a.iter().zip(b.iter()).skip(1).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max)
+}
+
+fn main(){
+    let n_zern = 21;
+    let truth = PhaseScreenGenerator::new(&Turbulence::kolmogorov(8.0), n_zern, 3).sample();
+    println!("True coefficients [rad]: {:?}", &truth[..6]);
+
+    let mut sim = InterferogramSimulator::new(128, 11);
+    sim.carrier = (6.0, 2.0);
+
+    // Noise-free 4-step: the unwrapped phase is the true phase up to a constant 2 pi k
+    let frames = sim.phase_shifted(&truth, &four_step_shifts());
+    let (wrapped, _) = four_step(&frames, &sim.grid.mask);
+    let unwrapped = unwrap_quality_guided(&wrapped, &second_difference_quality(&wrapped));
+
+    let mut with_carrier = truth.clone();
+    for (c, k) in with_carrier.iter_mut().zip(sim.carrier_coefficients()) {
+        *c += k;
+    }
+    let true_phase = sim.grid.wavefront(&with_carrier);
+    let (_, _, measured) = sim.grid.in_pupil(&unwrapped);
+    let (_, _, expected) = sim.grid.in_pupil(&true_phase);
+    let offset = measured[0] - expected[0];
+    let worst = measured.iter().zip(expected.iter()).map(|(m, e)| (m - e - offset).abs()).fold(0.0, f64::max);
+    println!("Offset {:.3} rad ({:.1} x 2pi), worst pixel error {:e}", offset, offset / (2.0 * std::f64::consts::PI), worst);
+    assert!(worst < 1e-9);
+
+    // The fitter consumes the unwrapped map directly; remove the carrier tilts afterwards
+    let zern = Zernike::new();
+    let fit_and_remove_carrier = |sim: &InterferogramSimulator, map: &Array<f64>| {
+        let (rho, theta, values) = sim.grid.in_pupil(map);
+        let mut coef = zern.fit(&rho, &theta, &values, n_zern, "Standard");
+        for (c, k) in coef.iter_mut().zip(sim.carrier_coefficients()) {
+            *c -= k;
+        }
+        coef
+    };
+    let fitted = fit_and_remove_carrier(&sim, &unwrapped);
+    println!("Noise-free fit error: {:e}", max_abs_diff(&truth, &fitted));
+    assert!(max_abs_diff(&truth, &fitted) < 1e-8);
+
+    // Noisy frames through Hariharan
+    sim.noise_rms = 0.05;
+    let frames = sim.phase_shifted(&truth, &hariharan_shifts());
+    let (wrapped, modulation) = hariharan(&frames, &sim.grid.mask);
+    println!("Fringe modulation at the centre: {:.3}", modulation[(64, 64)]);
+    let unwrapped = unwrap_quality_guided(&wrapped, &second_difference_quality(&wrapped));
+    let fitted = fit_and_remove_carrier(&sim, &unwrapped);
+    println!("Noisy Hariharan fit error: {:.2e}", max_abs_diff(&truth, &fitted));
+    assert!(max_abs_diff(&truth, &fitted) < 0.02);
+
+    // A phase shifter running 5% long: Hariharan copes much better than the 4-step
+    sim.noise_rms = 0.0;
+    let stretched = |shifts: Vec<f64>| -> Vec<f64> { shifts.iter().map(|s| 1.05 * s).collect() };
+    let frames4 = sim.phase_shifted(&truth, &stretched(four_step_shifts()));
+    let frames5 = sim.phase_shifted(&truth, &stretched(hariharan_shifts()));
+    let (w4, _) = four_step(&frames4, &sim.grid.mask);
+    let (w5, _) = hariharan(&frames5, &sim.grid.mask);
+    let err4 = max_abs_diff(&truth, &fit_and_remove_carrier(&sim, &unwrap_quality_guided(&w4, &second_difference_quality(&w4))));
+    let err5 = max_abs_diff(&truth, &fit_and_remove_carrier(&sim, &unwrap_quality_guided(&w5, &second_difference_quality(&w5))));
+    println!("Miscalibrated shifter: 4-step error {:.2e}, Hariharan error {:.2e}", err4, err5);
+    assert!(err5 < err4);
+}
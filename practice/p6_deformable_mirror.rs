@@ -0,0 +1,83 @@
+// Practice script for the deformable mirror: influence functions, control matrix, closed loop
+// Compile from this folder with: rustc p6_deformable_mirror.rs
+
+mod numerics;
+mod optics;
+
+use numerics::array::Array;
+use numerics::linalg;
+use optics::deformable_mirror::{ActuatorLayout, DeformableMirror};
+use optics::pupil::PupilGrid;
+use optics::turbulence::{PhaseScreenGenerator, Turbulence};
+use optics::zernike::Zernike;
+
+fn rms(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+}
+
+fn main(){
+    // The SVD against a matrix we know: [[3, 2, 2], [2, 3, -2]] has singular values 5 and 3
+    let a = Array::from_rows(vec![vec![3.0, 2.0, 2.0], vec![2.0, 3.0, -2.0]]).unwrap();
+    let s = linalg::svd(&a).unwrap().s;
+    println!("Singular values: {:?}", s);
+    assert!((s[0] - 5.0).abs() < 1e-12 && (s[1] - 3.0).abs() < 1e-12);
+
+    let grid = PupilGrid::new(64);
+    let square = ActuatorLayout::square(11);
+    let hexagonal = ActuatorLayout::hexagonal(5);
+    println!("Actuators: square {} | hexagonal {}", square.n_actuators(), hexagonal.n_actuators());
+    assert_eq!(hexagonal.n_actuators(), 1 + 3 * 5 * 6);
+
+    let mut dm = DeformableMirror::new(square, 0.15, &grid);
+
+    // Poke the central actuator: the surface peaks at 1 and the neighbours see the coupling
+    let centre = dm.layout.positions.iter().position(|&(x, y)| x.abs() < 1e-12 && y.abs() < 1e-12).unwrap();
+    dm.commands[centre] = 1.0;
+    let poke = dm.surface();
+    let peak = poke.iter().cloned().fold(f64::MIN, f64::max);
+    println!("Poke peak: {:.3}", peak);
+    assert!(peak > 0.95 && peak <= 1.0);
+
+    // The control matrix turns a Zernike request into commands that make it
+    let n_zern = 15;
+    let control = dm.control_matrix(&grid, n_zern, 1e-3);
+    let mut defocus = vec![0.0; n_zern];
+    defocus[3] = 1.0;
+    dm.commands = linalg::mat_vec(&control, &defocus);
+    let (rho, theta) = (&grid.aperture.rho, &grid.aperture.theta);
+    let zern = Zernike::new();
+    let achieved = zern.fit(rho, theta, &dm.surface(), n_zern, "Standard");
+    println!("Requested defocus 1.0, got {:.4} (largest leak {:.2e})", achieved[3],
+        achieved.iter().enumerate().filter(|&(k, _)| k != 3).map(|(_, c)| c.abs()).fold(0.0, f64::max));
+    assert!((achieved[3] - 1.0).abs() < 1e-6);
+
+    // Closed loop: an integrator on the Zernike content of the residual
+    // The loop drives the controlled modes to zero, what is left is the fitting error
+    let turbulence = PhaseScreenGenerator::new(&Turbulence::kolmogorov(4.0), 45, 9).sample();
+    let (_, _, phase) = grid.in_pupil(&grid.wavefront(&turbulence));
+    dm.commands = vec![0.0; dm.layout.n_actuators()];
+    let gain = 0.5;
+    let initial = rms(&phase);
+    let mut controlled = Vec::new();
+    for iteration in 0..30 {
+        let residual: Vec<f64> = phase.iter().zip(dm.surface()).map(|(p, s)| p - s).collect();
+        controlled = zern.fit(rho, theta, &residual, n_zern, "Standard");
+        let correction = linalg::mat_vec(&control, &controlled);
+        for (c, d) in dm.commands.iter_mut().zip(correction) {
+            *c += gain * d;
+        }
+        if iteration % 10 == 0 {
+            println!("Iteration {:2}: residual RMS {:.4} rad", iteration, rms(&residual));
+        }
+    }
+    let residual: Vec<f64> = phase.iter().zip(dm.surface()).map(|(p, s)| p - s).collect();
+    let largest_controlled = controlled.iter().skip(1).map(|c| c.abs()).fold(0.0, f64::max);
+    println!("Final residual RMS {:.4} rad (from {:.4}), controlled modes below {:.1e}", rms(&residual), initial, largest_controlled);
+    assert!(rms(&residual) < 0.3 * initial);
+    assert!(largest_controlled < 1e-6);
+
+    // The residual map can go back on the grid for display
+    let map = grid.scatter(&residual);
+    assert!(map[(0, 0)].is_nan() && !map[(32, 32)].is_nan());
+}
@@ -0,0 +1,62 @@
+// Practice script for the exact Zernike reference: how accurate are the fast modes?
+// Compile from this folder with: rustc p7_reference.rs
+
+mod numerics;
+mod optics;
+
+use numerics::bigfloat::BigFloat;
+use numerics::bigint::BigInt;
+use optics::reference::{mode_errors, r_nm_exact, radial_coefficients_exact, read_golden, write_golden};
+use optics::zernike::Zernike;
+
+fn main(){
+    // BigInt sanity: 30! has 33 digits and does not fit in a u64
+    let mut fact = BigInt::one();
+    for k in 1..=30 {
+        fact = fact.mul_small(k);
+    }
+    println!("30! = {}", fact);
+    assert_eq!(fact.to_string(), "265252859812191058636308480000000");
+
+    // BigFloat is exact: (1e16 + 1) - 1e16 loses the 1 in f64 but not here
+    let x: f64 = 1e16;
+    let big = BigFloat::from_f64(x);
+    let exact = &(&big + &BigFloat::from_int(1)) - &big;
+    println!("(1e16 + 1) - 1e16 = {} exactly, f64 gives {}", exact.to_f64(), x + 1.0 - x);
+    assert_eq!(exact.to_f64(), 1.0);
+    assert_eq!(BigFloat::from_f64(-1.5e-300).to_f64(), -1.5e-300);
+
+    // R_4^0 = 6 rho^4 - 6 rho^2 + 1
+    let coefs: Vec<String> = radial_coefficients_exact(4, 0).iter().map(|c| c.to_string()).collect();
+    println!("R_4^0 coefficients: {:?}", coefs);
+    assert_eq!(coefs, vec!["6", "-6", "1"]);
+    assert_eq!(r_nm_exact(4, 0, 0.5).to_f64(), 6.0 / 16.0 - 6.0 / 4.0 + 1.0);
+
+    // Error of every fast mode against the reference, largest per radial order
+    let rho: Vec<f64> = (0..=50).map(|i| i as f64 / 50.0).collect();
+    let n_max = 40;
+    let errors = mode_errors(n_max, &rho, &["Standard", "Jacobi"]);
+    let worst = |n: i32, mode: &str| {
+        errors.iter().filter(|e| e.n == n && e.mode == mode).map(|e| e.max_abs).fold(0.0, f64::max)
+    };
+    println!("{:>4} {:>12} {:>12}", "n", "Standard", "Jacobi");
+    for n in (0..=n_max).step_by(5) {
+        println!("{:>4} {:>12.2e} {:>12.2e}", n, worst(n, "Standard"), worst(n, "Jacobi"));
+    }
+    // Both fine at low orders, the factorial sum falls apart at high orders
+    assert!(worst(10, "Standard") < 1e-12 && worst(10, "Jacobi") < 1e-12);
+    assert!(worst(40, "Standard") > 1e-3);
+    assert!(worst(40, "Jacobi") < 1e-10);
+
+    // Golden data: write, read back, and check a fast mode against it
+    let path = std::env::temp_dir().join("zernike_golden.csv");
+    let path = path.to_str().unwrap();
+    write_golden(path, 12, &rho).expect("Could not write the golden file");
+    let golden = read_golden(path).expect("Could not read the golden file");
+    println!("Golden file {} with {} rows", path, golden.len());
+    let zern = Zernike::new();
+    for &(n, m, x, r) in &golden {
+        let fast = zern.r_nm_jacobi(n, m, &[x])[0];
+        assert!((fast - r).abs() < 1e-13, "R_{}^{}({}) = {} vs golden {}", n, m, x, fast, r);
+    }
+}
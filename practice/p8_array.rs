@@ -0,0 +1,61 @@
+// Practice script for the N-dimensional Array type (numerics/array.rs)
+// Compile from this folder with: rustc p8_array.rs
+
+mod numerics;
+
+use numerics::array::{Array, ShapeError};
+
+fn main(){
+    // The vec2d of p2_array_pw.rs, now as a proper 2D array
+    let vec2d: Vec<Vec<f64>> = vec![
+        vec![1.0, 2.0, 3.0],
+        vec![4.0, 5.0, 6.0],
+        vec![7.0, 8.0, 9.0],
+    ];
+    let arr = Array::from_rows(vec2d).unwrap();
+    println!("Shape {:?} | strides {:?}", arr.shape(), arr.strides());
+    assert_eq!(arr.shape(), &[3, 3]);
+    assert_eq!(arr[(1, 2)], 6.0);
+
+    // Jagged rows are an error instead of a silent wrong answer
+    let jagged = Array::from_rows(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0]]);
+    match &jagged {
+        Err(e) => println!("Jagged rows: {}", e),
+        Ok(_) => panic!("Jagged rows should not make an array"),
+    }
+    assert_eq!(jagged, Err(ShapeError::Jagged { row: 1, len: 2, expected: 3 }));
+
+    // And empty input is just an empty array (p2's sum_along_direction would panic on arr[0])
+    let empty: Array<f64> = Array::from_rows(Vec::new()).unwrap();
+    println!("Empty: shape {:?}, len {}", empty.shape(), empty.len());
+    assert!(empty.is_empty());
+
+    // Constructors
+    let zeros: Array<f64> = Array::zeros(&[2, 3, 4]);
+    let ones: Array<i32> = Array::ones_like(&zeros);
+    let sevens = Array::full(&[2, 2], 7u64);
+    assert_eq!(zeros.strides(), &[12, 4, 1]);
+    assert_eq!(ones.iter().sum::<i32>(), 24);
+    assert_eq!(sevens.as_slice(), &[7, 7, 7, 7]);
+    assert_eq!(Array::<f64>::zeros_like(&sevens).shape(), &[2, 2]);
+
+    let err = Array::from_shape_vec(&[2, 3], vec![1, 2, 3, 4]).unwrap_err();
+    println!("from_shape_vec: {}", err);
+
+    // Indexing by tuples, or by a slice for any number of dimensions
+    let mut cube = Array::from_shape_vec(&[2, 3, 4], (0..24).collect::<Vec<i64>>()).unwrap();
+    assert_eq!(cube[(1, 2, 3)], 23);
+    cube[(0, 1, 0)] = -1;
+    assert_eq!(cube[&[0, 1, 0][..]], -1);
+    assert_eq!(cube.get(&[2, 0, 0]), None);         // Checked access, like Vec::get
+    assert_eq!(cube.unravel(23), vec![1, 2, 3]);
+
+    // Reshape keeps the data, and checks the size
+    let flat = cube.clone().reshape(&[24]).unwrap();
+    assert_eq!(flat[5], 5);
+    assert!(cube.reshape(&[5, 5]).is_err());
+
+    // map() changes the element type
+    let halves = arr.map(|&x| x / 2.0);
+    println!("Halves as rows: {:?}", halves.to_rows().unwrap());
+}
@@ -0,0 +1,72 @@
+// Practice script for reductions along an axis (numerics/reduce.rs)
+// Compile from this folder with: rustc p9_reductions.rs
+
+mod numerics;
+
+use numerics::array::{Array, ShapeError};
+
+fn main(){
+    let arr = Array::from_rows(vec![
+        vec![1.0, 2.0, 3.0],
+        vec![4.0, 5.0, 6.0],
+        vec![7.0, 8.0, 9.0],
+        vec![10.0, 11.0, 12.0],
+    ]).unwrap();
+
+    // Axis 0 goes down the columns, axis 1 along the rows
+    let sum0 = arr.sum_axis(0).unwrap();
+    let sum1 = arr.sum_axis(1).unwrap();
+    println!("sum(axis=0) = {:?} | sum(axis=1) = {:?}", sum0.as_slice(), sum1.as_slice());
+    assert_eq!(sum0.as_slice(), &[22.0, 26.0, 30.0]);
+    assert_eq!(sum1.as_slice(), &[6.0, 15.0, 24.0, 33.0]);
+
+    assert_eq!(arr.mean_axis(0).unwrap().as_slice(), &[5.5, 6.5, 7.5]);
+    assert_eq!(arr.var_axis(1, 0).unwrap().as_slice(), &[2.0 / 3.0; 4]);
+    assert_eq!(arr.std_axis(1, 1).unwrap().as_slice(), &[1.0; 4]);
+    assert_eq!(arr.min_axis(0).unwrap().as_slice(), &[1.0, 2.0, 3.0]);
+    assert_eq!(arr.max_axis(1).unwrap().as_slice(), &[3.0, 6.0, 9.0, 12.0]);
+    assert_eq!(arr.argmax_axis(0).unwrap().as_slice(), &[3, 3, 3]);
+    assert_eq!(arr.argmin_axis(1).unwrap().as_slice(), &[0, 0, 0, 0]);
+    assert_eq!(arr.product_axis(1).unwrap().as_slice(), &[6.0, 120.0, 504.0, 1320.0]);
+    assert_eq!(arr.cumsum_axis(0).unwrap().to_rows().unwrap()[3], vec![22.0, 26.0, 30.0]);
+    assert_eq!(arr.cumsum_axis(1).unwrap().to_rows().unwrap()[1], vec![4.0, 9.0, 15.0]);
+
+    // Any axis of any number of dimensions, and integers work too
+    let cube = Array::from_shape_vec(&[2, 3, 4], (0..24).collect::<Vec<i64>>()).unwrap();
+    let middle = cube.sum_axis(1).unwrap();
+    println!("3D summed along axis 1: shape {:?} {:?}", middle.shape(), middle.as_slice());
+    assert_eq!(middle.shape(), &[2, 4]);
+    assert_eq!(middle[(1, 0)], 12 + 16 + 20);
+    assert_eq!(cube.max_axis(2).unwrap()[(1, 2)], 23);
+
+    // Bad axis, empty lanes
+    assert_eq!(arr.sum_axis(2), Err(ShapeError::AxisOutOfBounds { axis: 2, ndim: 2 }));
+    let empty: Array<f64> = Array::zeros(&[0, 3]);
+    assert_eq!(empty.sum_axis(0).unwrap().as_slice(), &[0.0, 0.0, 0.0]);
+    assert!(empty.mean_axis(0).unwrap().iter().all(|x| x.is_nan()));
+    let err = empty.max_axis(0).unwrap_err();
+    println!("max of an empty axis: {}", err);
+
+    // Masked wavefront: NaN outside the pupil. Plain reductions propagate the NaN,
+    // the nan* ones skip it
+    let nan = f64::NAN;
+    let masked = Array::from_rows(vec![
+        vec![nan, 1.0, nan],
+        vec![2.0, 3.0, 4.0],
+        vec![nan, 5.0, nan],
+    ]).unwrap();
+    assert!(masked.mean_axis(0).unwrap()[0].is_nan());
+    assert!(masked.max_axis(1).unwrap()[0].is_nan());
+    assert_eq!(masked.nanmean_axis(0).unwrap().as_slice(), &[2.0, 3.0, 4.0]);
+    assert_eq!(masked.nansum_axis(1).unwrap().as_slice(), &[1.0, 9.0, 5.0]);
+    assert_eq!(masked.nanmax_axis(0).unwrap().as_slice(), &[2.0, 5.0, 4.0]);
+    assert_eq!(masked.nanargmin_axis(1).unwrap().as_slice(), &[1, 0, 1]);
+    assert_eq!(masked.nanstd_axis(0, 0).unwrap()[1], (8.0f64 / 3.0).sqrt());
+    assert_eq!(masked.nanmean(), 3.0);
+    println!("nanmean of the masked map: {}", masked.nanmean());
+
+    // All-NaN lane: nanmean gives NaN, nanargmax has nothing to point at
+    let all_nan = Array::from_rows(vec![vec![nan, nan], vec![1.0, 2.0]]).unwrap();
+    assert!(all_nan.nanmean_axis(1).unwrap()[0].is_nan());
+    assert_eq!(all_nan.nanargmax_axis(1), Err(ShapeError::EmptyLane { axis: 1 }));
+}
@@ -0,0 +1,119 @@
+// RGB raster for the PNG output: rectangles, lines and numbers
+//
+// Pixel (0, 0) is the top left corner. There is no font renderer, just a 3 x 5 pixel
+// glyph for the characters of a number ("-1.5e-3"), scaled up, enough for tick labels;
+// titles and axis labels only make it to the SVG output.
+
+use crate::plot::colormap::Rgb;
+use crate::plot::png;
+use std::io;
+
+// Rows of the 3 x 5 glyphs, top to bottom, bit 2 is the left column
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0'..='9' => DIGITS[c as usize - '0' as usize],
+        '.' => [0, 0, 0, 0, 0b010],
+        '-' => [0, 0, 0b111, 0, 0],
+        '+' => [0, 0b010, 0b111, 0b010, 0],
+        'e' => [0, 0b011, 0b111, 0b100, 0b011],
+        _ => [0; 5], // anything else is a blank
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Canvas { width, height, pixels: vec![background; width * height] }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, colour: Rgb) {
+        // Points outside the canvas are dropped, so shapes can hang over the edge
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.pixels[y as usize * self.width + x as usize] = colour;
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i64, y: i64, width: usize, height: usize, colour: Rgb) {
+        for dy in 0..height as i64 {
+            for dx in 0..width as i64 {
+                self.set(x + dx, y + dy, colour);
+            }
+        }
+    }
+
+    pub fn rect(&mut self, x: i64, y: i64, width: usize, height: usize, colour: Rgb) {
+        // Outline, 1 pixel wide
+        let (w, h) = (width as i64 - 1, height as i64 - 1);
+        self.line((x as f64, y as f64), ((x + w) as f64, y as f64), colour, 1);
+        self.line((x as f64, (y + h) as f64), ((x + w) as f64, (y + h) as f64), colour, 1);
+        self.line((x as f64, y as f64), (x as f64, (y + h) as f64), colour, 1);
+        self.line(((x + w) as f64, y as f64), ((x + w) as f64, (y + h) as f64), colour, 1);
+    }
+
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), colour: Rgb, thickness: usize) {
+        // Steps of at most one pixel along the longer axis, each stamping a thickness x
+        // thickness square
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as usize;
+        let half = (thickness as i64 - 1) / 2;
+        for k in 0..=steps {
+            let t = k as f64 / steps as f64;
+            let (x, y) = ((from.0 + t * dx).round() as i64, (from.1 + t * dy).round() as i64);
+            self.fill_rect(x - half, y - half, thickness, thickness, colour);
+        }
+    }
+
+    pub fn text_width(text: &str, scale: usize) -> usize {
+        // 3 columns + 1 of spacing per character
+        (4 * text.chars().count()).saturating_sub(1) * scale
+    }
+
+    pub fn text(&mut self, x: i64, y: i64, text: &str, scale: usize, colour: Rgb) {
+        // Top left corner at (x, y), 5 * scale pixels high
+        for (n, c) in text.chars().enumerate() {
+            let left = x + (4 * n * scale) as i64;
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.fill_rect(left + (col * scale) as i64, y + (row * scale) as i64, scale, scale, colour);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn to_rgb(&self) -> Vec<u8> {
+        self.pixels.iter().flatten().copied().collect()
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode_rgb(self.width, self.height, &self.to_rgb())
+    }
+
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        png::write_rgb(path, self.width, self.height, &self.to_rgb())
+    }
+}
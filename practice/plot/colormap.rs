@@ -0,0 +1,79 @@
+// Colour maps for false-colour images: value -> RGB
+//
+// Each map is a few control colours spaced evenly on [0, 1] with linear interpolation in
+// between. Viridis is perceptually uniform and readable in grey, the default for PSFs
+// and intensities. Diverging is blue - white - red for signed data like wavefronts, where
+// white should mean 0, so its automatic range is symmetric around 0.
+
+pub type Rgb = [u8; 3];
+
+pub const BACKGROUND: Rgb = [255, 255, 255];
+pub const NAN_COLOUR: Rgb = [255, 255, 255]; // outside the pupil: blank, like matplotlib
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Colormap {
+    Viridis,
+    Gray,
+    Diverging,
+}
+
+const VIRIDIS: [Rgb; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+const GRAY: [Rgb; 2] = [[0, 0, 0], [255, 255, 255]];
+const DIVERGING: [Rgb; 5] = [[59, 76, 192], [141, 176, 254], [240, 240, 240], [244, 154, 123], [180, 4, 38]];
+
+impl Colormap {
+    fn stops(&self) -> &'static [Rgb] {
+        match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Gray => &GRAY,
+            Colormap::Diverging => &DIVERGING,
+        }
+    }
+
+    pub fn rgb(&self, t: f64) -> Rgb {
+        // t in [0, 1], clamped; NaN gives NAN_COLOUR
+        if t.is_nan() {
+            return NAN_COLOUR;
+        }
+        let stops = self.stops();
+        let pos = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let k = (pos.floor() as usize).min(stops.len() - 2);
+        let f = pos - k as f64;
+        let mut out = [0; 3];
+        for c in 0..3 {
+            let (a, b) = (stops[k][c] as f64, stops[k + 1][c] as f64);
+            out[c] = (a + f * (b - a)).round() as u8;
+        }
+        out
+    }
+
+    pub fn map(&self, value: f64, range: (f64, f64)) -> Rgb {
+        // The colour of value on a scale from range.0 (bottom colour) to range.1 (top)
+        self.rgb((value - range.0) / (range.1 - range.0))
+    }
+
+    pub fn auto_range(&self, data: &[f64]) -> (f64, f64) {
+        // min to max of the finite values (symmetric around 0 for Diverging). All equal or
+        // nothing finite: widened to a unit range, like stats::histogram
+        let finite = data.iter().filter(|v| v.is_finite());
+        let (lo, hi) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let (lo, hi) = if lo > hi { (0.0, 0.0) } else { (lo, hi) };
+        let (lo, hi) = if *self == Colormap::Diverging {
+            let m = lo.abs().max(hi.abs());
+            (-m, m)
+        } else {
+            (lo, hi)
+        };
+        if lo == hi { (lo - 0.5, hi + 0.5) } else { (lo, hi) }
+    }
+}
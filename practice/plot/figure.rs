@@ -0,0 +1,439 @@
+// Line plots and heatmaps, rendered to SVG (vector, with all the text) or to a PNG through
+// Canvas (tick labels only)
+//
+// Axes get "nice" ticks: steps of 1, 2 or 5 times a power of ten. NaN points break a line
+// and NaN cells of a heatmap are left blank, so masked pupils come out as disks.
+// Heatmaps are drawn like matplotlib's imshow: row 0 at the top, one square per element.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::plot::canvas::Canvas;
+use crate::plot::colormap::{Colormap, Rgb, BACKGROUND};
+use std::fs::File;
+use std::io::{self, Write};
+
+const BLACK: Rgb = [0, 0, 0];
+const GRID: Rgb = [225, 225, 225];
+// matplotlib's "tab10" colour cycle
+const PALETTE: [Rgb; 10] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+    [227, 119, 194],
+    [127, 127, 127],
+    [188, 189, 34],
+    [23, 190, 207],
+];
+
+pub fn nice_ticks(lo: f64, hi: f64, target: usize) -> Vec<f64> {
+    // About `target` round values covering [lo, hi]; just lo for an empty or unbounded range
+    if hi <= lo || !(hi - lo).is_finite() {
+        return vec![lo];
+    }
+    let raw = (hi - lo) / target.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].iter().map(|m| m * magnitude).find(|&s| s >= raw * (1.0 - 1e-9)).unwrap();
+    let (first, last) = ((lo / step - 1e-9).ceil() as i64, (hi / step + 1e-9).floor() as i64);
+    (first..=last).map(|k| k as f64 * step).collect()
+}
+
+pub fn format_tick(value: f64, step: f64) -> String {
+    // As many decimals as the step needs; exponent notation for very big or small values
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    if value.abs() >= 1e5 || step < 1e-4 {
+        return format!("{:.1e}", value);
+    }
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+fn tick_step(ticks: &[f64]) -> f64 {
+    if ticks.len() > 1 { ticks[1] - ticks[0] } else { 1.0 }
+}
+
+fn hex(c: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn base64(data: &[u8]) -> String {
+    // For the PNG embedded in the SVG heatmaps
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for group in data.chunks(3) {
+        let b = [group[0], *group.get(1).unwrap_or(&0), *group.get(2).unwrap_or(&0)];
+        let v = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for k in 0..4 {
+            if k <= group.len() {
+                out.push(TABLE[(v >> (18 - 6 * k) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn save_text(path: &str, text: &str) -> io::Result<()> {
+    File::create(path)?.write_all(text.as_bytes())
+}
+
+struct Svg(String);
+
+impl Svg {
+    // Just the few elements the plots need, appended one per line
+    fn new(width: usize, height: usize) -> Self {
+        let mut svg = Svg(String::new());
+        let size = format!(r#"width="{0}" height="{1}" viewBox="0 0 {0} {1}""#, width, height);
+        let font = r#"font-family="sans-serif" font-size="12""#;
+        svg.raw(&format!(r#"<svg xmlns="http://www.w3.org/2000/svg" {} {}>"#, size, font));
+        svg.raw(&format!(r#"<rect width="{}" height="{}" fill="white"/>"#, width, height));
+        svg
+    }
+
+    fn raw(&mut self, element: &str) {
+        self.0.push_str(element);
+        self.0.push('\n');
+    }
+
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), colour: Rgb, width: f64) {
+        let (x1, y1, x2, y2) = (from.0, from.1, to.0, to.1);
+        let stroke = format!(r#"stroke="{}" stroke-width="{}""#, hex(colour), width);
+        self.raw(&format!(r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" {}/>"#, x1, y1, x2, y2, stroke));
+    }
+
+    fn rect(&mut self, corner: (f64, f64), size: (f64, f64), attributes: &str) {
+        let (x, y, w, h) = (corner.0, corner.1, size.0, size.1);
+        self.raw(&format!(r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" {}/>"#, x, y, w, h, attributes));
+    }
+
+    fn text(&mut self, at: (f64, f64), text: &str, attributes: &str) {
+        self.raw(&format!(r#"<text x="{:.1}" y="{:.1}" {}>{}</text>"#, at.0, at.1, attributes, escape(text)));
+    }
+
+    fn finish(mut self) -> String {
+        self.raw("</svg>");
+        self.0
+    }
+}
+
+// Line plots
+
+#[derive(Clone, Debug)]
+pub struct Series {
+    pub label: String,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub colour: Rgb,
+}
+
+impl Series {
+    fn segments(&self) -> Vec<Vec<(f64, f64)>> {
+        // Runs of finite points, a NaN ends the current one
+        let mut out = vec![Vec::new()];
+        for (&x, &y) in self.x.iter().zip(self.y.iter()) {
+            if x.is_finite() && y.is_finite() {
+                out.last_mut().unwrap().push((x, y));
+            } else if !out.last().unwrap().is_empty() {
+                out.push(Vec::new());
+            }
+        }
+        out.retain(|s| !s.is_empty());
+        out
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LinePlot {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub width: usize,
+    pub height: usize,
+    pub series: Vec<Series>,
+}
+
+struct Frame {
+    // Where the data rectangle sits in the image and what it shows
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+}
+
+impl Frame {
+    fn px(&self, x: f64, y: f64) -> (f64, f64) {
+        let (x0, x1) = self.x_range;
+        let (y0, y1) = self.y_range;
+        (self.left + (x - x0) / (x1 - x0) * self.width, self.top + (y1 - y) / (y1 - y0) * self.height)
+    }
+}
+
+impl LinePlot {
+    pub fn new(title: &str, x_label: &str, y_label: &str) -> Self {
+        LinePlot {
+            title: title.to_string(),
+            x_label: x_label.to_string(),
+            y_label: y_label.to_string(),
+            width: 640,
+            height: 420,
+            series: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, label: &str, x: &[f64], y: &[f64]) {
+        // Colours go round the tab10 cycle
+        assert_eq!(x.len(), y.len(), "one y per x");
+        let colour = PALETTE[self.series.len() % PALETTE.len()];
+        self.series.push(Series { label: label.to_string(), x: x.to_vec(), y: y.to_vec(), colour });
+    }
+
+    fn frame(&self) -> Frame {
+        // Data range of the finite points, y padded by 5% so lines don't sit on the frame
+        let points = self.series.iter().flat_map(|s| s.segments()).flatten();
+        let init = (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY);
+        let (x0, x1, y0, y1) =
+            points.fold(init, |(a, b, c, d), (x, y)| (a.min(x), b.max(x), c.min(y), d.max(y)));
+        let widen = |lo: f64, hi: f64, pad: f64| {
+            if lo > hi {
+                (0.0, 1.0)
+            } else if lo == hi {
+                (lo - 0.5, hi + 0.5)
+            } else {
+                (lo - pad * (hi - lo), hi + pad * (hi - lo))
+            }
+        };
+        let (left, top, right, bottom) = (70.0, 40.0, 20.0, 50.0);
+        Frame {
+            left,
+            top,
+            width: self.width as f64 - left - right,
+            height: self.height as f64 - top - bottom,
+            x_range: widen(x0, x1, 0.0),
+            y_range: widen(y0, y1, 0.05),
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let f = self.frame();
+        let mut svg = Svg::new(self.width, self.height);
+        // Grid and ticks
+        let x_ticks = nice_ticks(f.x_range.0, f.x_range.1, 8);
+        let y_ticks = nice_ticks(f.y_range.0, f.y_range.1, 6);
+        let bottom = f.top + f.height;
+        for &t in &x_ticks {
+            let (x, _) = f.px(t, 0.0);
+            svg.line((x, f.top), (x, bottom), GRID, 1.0);
+            svg.text((x, bottom + 16.0), &format_tick(t, tick_step(&x_ticks)), r#"text-anchor="middle""#);
+        }
+        for &t in &y_ticks {
+            let (_, y) = f.px(0.0, t);
+            svg.line((f.left, y), (f.left + f.width, y), GRID, 1.0);
+            svg.text((f.left - 6.0, y + 4.0), &format_tick(t, tick_step(&y_ticks)), r#"text-anchor="end""#);
+        }
+        svg.rect((f.left, f.top), (f.width, f.height), r#"fill="none" stroke="black""#);
+        // Data, clipped to the frame
+        svg.raw(r#"<clipPath id="frame">"#);
+        svg.rect((f.left, f.top), (f.width, f.height), "");
+        svg.raw("</clipPath>");
+        for series in &self.series {
+            for segment in series.segments() {
+                let points: Vec<String> =
+                    segment.iter().map(|&(x, y)| f.px(x, y)).map(|(x, y)| format!("{:.2},{:.2}", x, y)).collect();
+                let style = format!(r#"fill="none" stroke="{}" stroke-width="2""#, hex(series.colour));
+                svg.raw(&format!(r#"<polyline points="{}" {} clip-path="url(#frame)"/>"#, points.join(" "), style));
+            }
+        }
+        // Legend in the top right corner
+        for (k, series) in self.series.iter().enumerate() {
+            let (x, y) = (f.left + f.width - 130.0, f.top + 16.0 + 16.0 * k as f64);
+            svg.line((x, y - 4.0), (x + 20.0, y - 4.0), series.colour, 2.0);
+            svg.text((x + 26.0, y), &series.label, "");
+        }
+        // Title and axis labels
+        let centre = f.left + f.width / 2.0;
+        svg.text((centre, 24.0), &self.title, r#"text-anchor="middle" font-size="15""#);
+        svg.text((centre, self.height as f64 - 10.0), &self.x_label, r#"text-anchor="middle""#);
+        let (x, y) = (18.0, f.top + f.height / 2.0);
+        let rotate = format!(r#"text-anchor="middle" transform="rotate(-90 {:.1} {:.1})""#, x, y);
+        svg.text((x, y), &self.y_label, &rotate);
+        svg.finish()
+    }
+
+    pub fn to_canvas(&self) -> Canvas {
+        let f = self.frame();
+        let mut c = Canvas::new(self.width, self.height, BACKGROUND);
+        let x_ticks = nice_ticks(f.x_range.0, f.x_range.1, 8);
+        let y_ticks = nice_ticks(f.y_range.0, f.y_range.1, 6);
+        let bottom = f.top + f.height;
+        for &t in &x_ticks {
+            let (x, _) = f.px(t, 0.0);
+            c.line((x, f.top), (x, bottom), GRID, 1);
+            let label = format_tick(t, tick_step(&x_ticks));
+            c.text((x - Canvas::text_width(&label, 2) as f64 / 2.0) as i64, (bottom + 8.0) as i64, &label, 2, BLACK);
+        }
+        for &t in &y_ticks {
+            let (_, y) = f.px(0.0, t);
+            c.line((f.left, y), (f.left + f.width, y), GRID, 1);
+            let label = format_tick(t, tick_step(&y_ticks));
+            c.text((f.left - 8.0) as i64 - Canvas::text_width(&label, 2) as i64, (y - 5.0) as i64, &label, 2, BLACK);
+        }
+        for series in &self.series {
+            for segment in series.segments() {
+                for pair in segment.windows(2) {
+                    c.line(f.px(pair[0].0, pair[0].1), f.px(pair[1].0, pair[1].1), series.colour, 2);
+                }
+            }
+        }
+        // Clear what the thick lines drew over the top and right margins, then the frame
+        let right = (f.left + f.width) as i64 + 1;
+        c.fill_rect(0, 0, self.width, f.top as usize, BACKGROUND);
+        c.fill_rect(right, f.top as i64, self.width.saturating_sub(right as usize), f.height as usize + 1, BACKGROUND);
+        c.rect(f.left as i64, f.top as i64, f.width as usize + 1, f.height as usize + 1, BLACK);
+        for (k, series) in self.series.iter().enumerate() {
+            // Legend: just the colours, the labels are in the SVG
+            let y = f.top + 12.0 + 10.0 * k as f64;
+            c.line((f.left + f.width - 30.0, y), (f.left + f.width - 10.0, y), series.colour, 3);
+        }
+        c
+    }
+
+    pub fn save_svg(&self, path: &str) -> io::Result<()> {
+        save_text(path, &self.to_svg())
+    }
+
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        self.to_canvas().save_png(path)
+    }
+}
+
+// Heatmaps
+
+#[derive(Clone, Debug)]
+pub struct Heatmap {
+    pub data: Array<f64>,
+    pub title: String,
+    pub colormap: Colormap,
+    pub range: Option<(f64, f64)>, // colour scale, None for Colormap::auto_range
+    pub cell: usize,               // pixels per element
+}
+
+const BAR_GAP: usize = 15;
+const BAR_WIDTH: usize = 18;
+
+impl Heatmap {
+    pub fn new(data: &Array<f64>, title: &str) -> Result<Self, ShapeError> {
+        // Viridis, automatic range, cells sized to make the image ~ 320 pixels across
+        // An empty axis has no pixel 0 to draw
+        let (rows, cols) = match *data.shape() {
+            [0, _] => return Err(ShapeError::OutOfBounds { axis: 0, index: 0, len: 0 }),
+            [_, 0] => return Err(ShapeError::OutOfBounds { axis: 1, index: 0, len: 0 }),
+            [rows, cols] => (rows, cols),
+            _ => return Err(ShapeError::WrongNdim { expected: 2, shape: data.shape().to_vec() }),
+        };
+        let cell = (320 / rows.max(cols)).max(1);
+        Ok(Heatmap { data: data.clone(), title: title.to_string(), colormap: Colormap::Viridis, range: None, cell })
+    }
+
+    pub fn value_range(&self) -> (f64, f64) {
+        self.range.unwrap_or_else(|| self.colormap.auto_range(self.data.as_slice()))
+    }
+
+    fn dims(&self) -> (usize, usize) {
+        (self.data.shape()[0], self.data.shape()[1])
+    }
+
+    pub fn cells(&self) -> Canvas {
+        // The data alone, one pixel per element
+        let (rows, cols) = self.dims();
+        let range = self.value_range();
+        let mut c = Canvas::new(cols, rows, BACKGROUND);
+        for i in 0..rows {
+            for j in 0..cols {
+                c.set(j as i64, i as i64, self.colormap.map(self.data[(i, j)], range));
+            }
+        }
+        c
+    }
+
+    fn layout(&self) -> (usize, usize, usize, usize, usize) {
+        // (left, top, image width, image height, total width)
+        let (rows, cols) = self.dims();
+        let (left, top) = (20, 40);
+        let (w, h) = (cols * self.cell, rows * self.cell);
+        (left, top, w, h, left + w + BAR_GAP + BAR_WIDTH + 80)
+    }
+
+    pub fn to_canvas(&self) -> Canvas {
+        let (left, top, w, h, total) = self.layout();
+        let mut c = Canvas::new(total, top + h + 20, BACKGROUND);
+        let cells = self.cells();
+        for y in 0..h {
+            for x in 0..w {
+                c.set((left + x) as i64, (top + y) as i64, cells.get(x / self.cell, y / self.cell));
+            }
+        }
+        // Colour bar: top colour at the top
+        let (lo, hi) = self.value_range();
+        let bar_x = left + w + BAR_GAP;
+        for y in 0..h {
+            let t = 1.0 - y as f64 / (h - 1).max(1) as f64;
+            c.fill_rect(bar_x as i64, (top + y) as i64, BAR_WIDTH, 1, self.colormap.rgb(t));
+        }
+        c.rect(bar_x as i64, top as i64, BAR_WIDTH, h, BLACK);
+        let ticks = nice_ticks(lo, hi, 5);
+        for &t in &ticks {
+            let y = top as f64 + (1.0 - (t - lo) / (hi - lo)) * (h - 1) as f64;
+            let x = (bar_x + BAR_WIDTH) as f64;
+            c.line((x, y), (x + 4.0, y), BLACK, 1);
+            c.text(x as i64 + 8, y as i64 - 5, &format_tick(t, tick_step(&ticks)), 2, BLACK);
+        }
+        c
+    }
+
+    pub fn to_svg(&self) -> String {
+        // The cells go in as an embedded PNG scaled up without smoothing, the colour bar
+        // as a gradient
+        let (left, top, w, h, total) = self.layout();
+        let (lo, hi) = self.value_range();
+        let mut svg = Svg::new(total, top + h + 20);
+        svg.text(((left + w / 2) as f64, 24.0), &self.title, r#"text-anchor="middle" font-size="15""#);
+        let data = base64(&self.cells().to_png());
+        let place = format!(r#"x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none""#, left, top, w, h);
+        let image = format!(r#"style="image-rendering:pixelated" href="data:image/png;base64,{}""#, data);
+        svg.raw(&format!("<image {} {}/>", place, image));
+        svg.raw(r#"<linearGradient id="bar" x1="0" y1="1" x2="0" y2="0">"#);
+        for k in 0..=10 {
+            let t = k as f64 / 10.0;
+            svg.raw(&format!(r#"<stop offset="{:.1}" stop-color="{}"/>"#, t, hex(self.colormap.rgb(t))));
+        }
+        svg.raw("</linearGradient>");
+        let bar_x = (left + w + BAR_GAP) as f64;
+        svg.rect((bar_x, top as f64), (BAR_WIDTH as f64, h as f64), r#"fill="url(#bar)" stroke="black""#);
+        let ticks = nice_ticks(lo, hi, 5);
+        for &t in &ticks {
+            let y = top as f64 + (1.0 - (t - lo) / (hi - lo)) * h as f64;
+            let x = bar_x + BAR_WIDTH as f64;
+            svg.line((x, y), (x + 4.0, y), BLACK, 1.0);
+            svg.text((x + 7.0, y + 4.0), &format_tick(t, tick_step(&ticks)), "");
+        }
+        svg.finish()
+    }
+
+    pub fn save_svg(&self, path: &str) -> io::Result<()> {
+        save_text(path, &self.to_svg())
+    }
+
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        self.to_canvas().save_png(path)
+    }
+}
@@ -0,0 +1,11 @@
+// Plots without external tools: line plots and false-colour heatmaps written to SVG or
+// PNG (with a small pure-Rust PNG encoder), for reports on headless machines, and a
+// heatmap preview drawn straight in the terminal
+// Scripts need `mod numerics;` and `mod plot;` since the heatmaps take an Array
+#![allow(dead_code)]
+
+pub mod canvas;
+pub mod colormap;
+pub mod figure;
+pub mod png;
+pub mod terminal;
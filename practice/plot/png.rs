@@ -0,0 +1,191 @@
+// Minimal PNG encoder: 8-bit RGB, one IDAT chunk, zlib stream compressed with LZ77 and
+// the fixed Huffman codes of deflate (RFC 1951 section 3.2.6)
+//
+// Fixed codes skip building and storing Huffman tables, at the price of a few percent of
+// size against a real zlib. LZ77 does the heavy lifting: plots are mostly runs of the
+// same colour, and a heatmap drawn with big cells repeats every row of cells.
+
+use std::fs::File;
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+// Deflate length codes 257..=285 and distance codes 0..=29: (base, extra bits)
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0), (11, 1), (13, 1), (15, 1), (17, 1),
+    (19, 2), (23, 2), (27, 2), (31, 2), (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4),
+    (115, 4), (131, 5), (163, 5), (195, 5), (227, 5), (258, 0),
+];
+const DISTANCES: [(u16, u8); 30] = [
+    (1, 0), (2, 0), (3, 0), (4, 0), (5, 1), (7, 1), (9, 2), (13, 2), (17, 3), (25, 3), (33, 4), (49, 4),
+    (65, 5), (97, 5), (129, 6), (193, 6), (257, 7), (385, 7), (513, 8), (769, 8), (1025, 9), (1537, 9),
+    (2049, 10), (3073, 10), (4097, 11), (6145, 11), (8193, 12), (12289, 12), (16385, 13), (24577, 13),
+];
+
+const WINDOW: usize = 32768;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: usize = 15;
+
+pub fn crc32(data: &[u8]) -> u32 {
+    // The CRC of every PNG chunk (reflected polynomial 0xEDB88320)
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    // zlib's checksum of the uncompressed data
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        // 5552 bytes is the most that can't overflow b before the modulo
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    n_bits: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, n: u32) {
+        // Deflate packs values starting from the least significant bit
+        self.buffer |= value << self.n_bits;
+        self.n_bits += n;
+        while self.n_bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.n_bits -= 8;
+        }
+    }
+
+    fn code(&mut self, code: u32, len: u32) {
+        // Huffman codes go most significant bit first
+        self.bits(code.reverse_bits() >> (32 - len), len);
+    }
+
+    fn literal_length(&mut self, symbol: u32) {
+        // The fixed literal / length code
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xC0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.n_bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+fn code_of(table: &[(u16, u8)], value: usize) -> usize {
+    // Last entry whose base is <= value
+    table.iter().rposition(|&(base, _)| base as usize <= value).unwrap()
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    // One final block with the fixed codes. Greedy LZ77: the most recent earlier position
+    // with the same 3 bytes (hashed) is the match candidate
+    let mut out = BitWriter { bytes: Vec::new(), buffer: 0, n_bits: 0 };
+    out.bits(1, 1); // BFINAL
+    out.bits(1, 2); // BTYPE = 01, fixed Huffman
+    let hash = |i: usize| {
+        let v = (data[i] as usize) << 16 | (data[i + 1] as usize) << 8 | data[i + 2] as usize;
+        (v.wrapping_mul(2_654_435_761) >> 8) & ((1 << HASH_BITS) - 1)
+    };
+    let mut last_seen = vec![usize::MAX; 1 << HASH_BITS];
+    let mut i = 0;
+    while i < data.len() {
+        let mut best = (0, 0); // (length, distance)
+        if i + 3 <= data.len() {
+            let h = hash(i);
+            let candidate = last_seen[h];
+            last_seen[h] = i;
+            if candidate != usize::MAX && i - candidate <= WINDOW {
+                let max_len = MAX_MATCH.min(data.len() - i);
+                let len = (0..max_len).take_while(|&k| data[candidate + k] == data[i + k]).count();
+                if len >= 3 {
+                    best = (len, i - candidate);
+                }
+            }
+        }
+        let (len, distance) = best;
+        if len == 0 {
+            out.literal_length(data[i] as u32);
+            i += 1;
+            continue;
+        }
+        let l = code_of(&LENGTHS, len);
+        out.literal_length(257 + l as u32);
+        out.bits((len - LENGTHS[l].0 as usize) as u32, LENGTHS[l].1 as u32);
+        let d = code_of(&DISTANCES, distance);
+        out.code(d as u32, 5);
+        out.bits((distance - DISTANCES[d].0 as usize) as u32, DISTANCES[d].1 as u32);
+        // Keep the hash table up to date inside the match, so later matches can start there
+        for k in i + 1..(i + len).min(data.len().saturating_sub(2)) {
+            last_seen[hash(k)] = k;
+        }
+        i += len;
+    }
+    out.literal_length(256); // end of block
+    out.finish()
+}
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    // 0x78 0x01: deflate with a 32 KB window, no preset dictionary
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    // length, type, data, CRC of type + data
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+pub fn encode_rgb(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    // rgb: width * height * 3 bytes, row by row from the top
+    assert!(width > 0 && height > 0, "an image needs at least one pixel");
+    assert_eq!(rgb.len(), width * height * 3, "{} x {} RGB image needs {} bytes", width, height, width * height * 3);
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    header.extend([8, 2, 0, 0, 0]); // bit depth 8, colour type RGB, deflate, no filter, not interlaced
+    // Each scanline starts with its filter type, 0 (none)
+    let mut raw = Vec::with_capacity(height * (3 * width + 1));
+    for row in rgb.chunks(3 * width) {
+        raw.push(0);
+        raw.extend(row);
+    }
+    let mut out = SIGNATURE.to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &zlib(&raw));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+pub fn write_rgb(path: &str, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    File::create(path)?.write_all(&encode_rgb(width, height, rgb))
+}
@@ -0,0 +1,237 @@
+// Heatmap preview in the terminal, for a quick look at a wavefront or PSF over SSH
+//
+// Every character cell shows two pixels stacked: the upper half block "▀" in the
+// foreground colour of the top pixel over the background colour of the bottom one. With
+// cells about twice as tall as wide that makes the pixels square. Colours are the
+// 256-colour ANSI codes, which every terminal emulator of the last 20 years understands:
+// the 6 x 6 x 6 cube at 16..=231 and the 24 greys at 232..=255. The Ascii style is for
+// logs and terminals without colour, one character from a ramp per two pixels.
+//
+// Arrays wider than the requested columns are shrunk by averaging blocks of pixels, NaN
+// ignored, so a masked pupil keeps its edge. NaN (outside the pupil) is left blank.
+
+use crate::numerics::array::{Array, ShapeError};
+use crate::plot::colormap::{Colormap, Rgb};
+use crate::plot::figure::{format_tick, nice_ticks, Heatmap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+const ASCII_RAMP: &[u8] = b".:-=+*#%@";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    Ansi256,
+    Ascii,
+}
+
+pub fn ansi256_rgb(code: u8) -> Rgb {
+    // The colour the terminal shows for a code of the cube or the grey ramp (the first 16
+    // depend on the terminal's theme, so they're never produced)
+    match code {
+        16..=231 => {
+            let k = code as usize - 16;
+            [CUBE_LEVELS[k / 36], CUBE_LEVELS[k / 6 % 6], CUBE_LEVELS[k % 6]]
+        }
+        232..=255 => [8 + 10 * (code - 232); 3],
+        _ => panic!("ANSI colour {} depends on the terminal theme", code),
+    }
+}
+
+pub fn ansi256(c: Rgb) -> u8 {
+    // Nearest of the cube colour and the grey, by squared RGB distance
+    let level = |v: u8| (0..6).min_by_key(|&k| (CUBE_LEVELS[k] as i32 - v as i32).abs()).unwrap();
+    let cube = 16 + 36 * level(c[0]) + 6 * level(c[1]) + level(c[2]);
+    let mean = (c[0] as i32 + c[1] as i32 + c[2] as i32) / 3;
+    let grey = 232 + ((mean - 8).max(0) / 10).min(23) as usize;
+    let distance = |code: usize| {
+        let d = ansi256_rgb(code as u8);
+        (0..3).map(|i| (d[i] as i32 - c[i] as i32).pow(2)).sum::<i32>()
+    };
+    if distance(grey) < distance(cube) { grey as u8 } else { cube as u8 }
+}
+
+fn shrink(data: &Array<f64>, columns: usize) -> Array<f64> {
+    // Mean of square blocks of pixels, NaN ignored, so the result is at most `columns` wide
+    let (rows, cols) = (data.shape()[0], data.shape()[1]);
+    let block = cols.div_ceil(columns.max(1)).max(1);
+    if block == 1 {
+        return data.clone();
+    }
+    let (out_rows, out_cols) = (rows.div_ceil(block), cols.div_ceil(block));
+    let mut out = Vec::with_capacity(out_rows * out_cols);
+    for bi in 0..out_rows {
+        for bj in 0..out_cols {
+            let (mut sum, mut n) = (0.0, 0);
+            for i in bi * block..((bi + 1) * block).min(rows) {
+                for j in bj * block..((bj + 1) * block).min(cols) {
+                    let v = data[(i, j)];
+                    if !v.is_nan() {
+                        sum += v;
+                        n += 1;
+                    }
+                }
+            }
+            out.push(if n > 0 { sum / n as f64 } else { f64::NAN });
+        }
+    }
+    Array::from_shape_vec(&[out_rows, out_cols], out).unwrap()
+}
+
+fn half_block(top: Option<u8>, bottom: Option<u8>) -> String {
+    // One character cell; None is a NaN pixel, drawn in the terminal's own background
+    match (top, bottom) {
+        (Some(t), Some(b)) => format!("\x1b[38;5;{}m\x1b[48;5;{}m▀", t, b),
+        (Some(t), None) => format!("{}\x1b[38;5;{}m▀", RESET, t),
+        (None, Some(b)) => format!("{}\x1b[38;5;{}m▄", RESET, b),
+        (None, None) => format!("{} ", RESET),
+    }
+}
+
+fn ascii(top: f64, bottom: f64) -> char {
+    // Mean brightness of the two pixels (t in [0, 1]) on the ramp, blank if both are NaN
+    let t: Vec<f64> = [top, bottom].iter().copied().filter(|v| !v.is_nan()).collect();
+    if t.is_empty() {
+        return ' ';
+    }
+    let mean = (t.iter().sum::<f64>() / t.len() as f64).clamp(0.0, 1.0);
+    ASCII_RAMP[(mean * (ASCII_RAMP.len() - 1) as f64).round() as usize] as char
+}
+
+fn legend(map: &Heatmap, width: usize, style: Style) -> String {
+    // Colour bar from low to high across `width` cells, then the ticks underneath
+    let (lo, hi) = map.value_range();
+    let width = width.max(12);
+    let mut bar = String::new();
+    for k in 0..width {
+        let t = k as f64 / (width - 1) as f64;
+        match style {
+            Style::Ansi256 => bar.push_str(&format!("\x1b[48;5;{}m ", ansi256(map.colormap.rgb(t)))),
+            Style::Ascii => bar.push(ascii(t, t)),
+        }
+    }
+    if style == Style::Ansi256 {
+        bar.push_str(RESET);
+    }
+    // Tick labels under their position, about one per 10 cells. The last one always shows,
+    // the first one if it keeps a space from it, and the ones in between where they fit
+    let ticks = nice_ticks(lo, hi, (width / 10).clamp(2, 5));
+    let step = if ticks.len() > 1 { ticks[1] - ticks[0] } else { 1.0 };
+    let placed: Vec<(usize, String)> = ticks
+        .iter()
+        .map(|&t| {
+            let label = format_tick(t, step);
+            let at = ((t - lo) / (hi - lo) * (width - 1) as f64).round() as usize;
+            (at.saturating_sub(label.len() / 2).min(width.saturating_sub(label.len())), label)
+        })
+        .collect();
+    let fits = |a: &(usize, String), b: &(usize, String)| a.0 + a.1.len() < b.0;
+    let mut shown: Vec<&(usize, String)> = Vec::new();
+    if let Some((last, rest)) = placed.split_last() {
+        for label in rest {
+            if shown.last().is_none_or(|prev| fits(prev, label)) && fits(label, last) {
+                shown.push(label);
+            }
+        }
+        shown.push(last);
+    }
+    let mut labels = vec![' '; width.max(placed.iter().map(|(start, label)| start + label.len()).max().unwrap_or(0))];
+    for &(start, ref label) in shown {
+        labels.splice(start..start + label.len(), label.chars());
+    }
+    let labels: String = labels.into_iter().collect();
+    format!("{}\n{}\n", bar, labels.trim_end())
+}
+
+pub fn render(map: &Heatmap, columns: usize, style: Style) -> String {
+    // The title, the image at most `columns` characters wide and the legend
+    let (lo, hi) = map.value_range();
+    let data = shrink(&map.data, columns);
+    let (rows, cols) = (data.shape()[0], data.shape()[1]);
+    let t = |i: usize, j: usize| if i < rows { (data[(i, j)] - lo) / (hi - lo) } else { f64::NAN };
+    let mut out = String::new();
+    if !map.title.is_empty() {
+        let step = (hi - lo) / 100.0;
+        out.push_str(&format!("{} [{}, {}]\n", map.title, format_tick(lo, step), format_tick(hi, step)));
+    }
+    for i in (0..rows).step_by(2) {
+        for j in 0..cols {
+            let (top, bottom) = (t(i, j), t(i + 1, j));
+            match style {
+                Style::Ansi256 => {
+                    let code = |v: f64| if v.is_nan() { None } else { Some(ansi256(map.colormap.rgb(v))) };
+                    out.push_str(&half_block(code(top), code(bottom)));
+                }
+                Style::Ascii => out.push(ascii(top, bottom)),
+            }
+        }
+        if style == Style::Ansi256 {
+            out.push_str(RESET);
+        }
+        out.push('\n');
+    }
+    out.push_str(&legend(map, cols, style));
+    out
+}
+
+impl Heatmap {
+    pub fn to_terminal(&self, columns: usize) -> String {
+        render(self, columns, Style::Ansi256)
+    }
+}
+
+// For `println!("{:?}", wavefront.preview())`: the heatmap with the default Viridis map,
+// 64 columns at most. Anything but a 2D array prints as usual
+pub struct Preview<'a> {
+    data: &'a Array<f64>,
+    colormap: Colormap,
+}
+
+impl Array<f64> {
+    pub fn preview(&self) -> Preview<'_> {
+        Preview { data: self, colormap: Colormap::Viridis }
+    }
+
+    pub fn preview_signed(&self) -> Preview<'_> {
+        // Diverging map, 0 in white: for wavefronts
+        Preview { data: self, colormap: Colormap::Diverging }
+    }
+}
+
+impl fmt::Debug for Preview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match Heatmap::new(self.data, "") {
+            Ok(mut map) => {
+                map.colormap = self.colormap;
+                write!(f, "{:?} array\n{}", self.data.shape(), render(&map, 64, Style::Ansi256))
+            }
+            Err(_) => write!(f, "{:?}", self.data),
+        }
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub fn load_text(path: &str) -> io::Result<Array<f64>> {
+    // A grid saved as text, one row per line, values split by spaces, tabs or commas
+    // (np.savetxt / CSV). "nan" marks the pixels outside the aperture; # starts a comment
+    let mut rows: Vec<Vec<f64>> = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let row = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<f64>().map_err(|e| invalid(format!("{:?}: {}", s, e))))
+            .collect::<io::Result<Vec<f64>>>()?;
+        rows.push(row);
+    }
+    Array::from_rows(rows).map_err(|e: ShapeError| invalid(e.to_string()))
+}